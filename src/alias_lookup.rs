@@ -0,0 +1,89 @@
+use crate::formatting::{
+    format_error_chain, format_optional_float, format_optional_rank, ranking_label,
+};
+use colored::Colorize;
+use langrank::{AliasResolution, Fetcher};
+
+pub async fn print_alias_lookup(fetcher: &Fetcher, name: &str) {
+    let resolution = fetcher.aliases().resolve(name);
+    println!("{} {}", "Name".bright_yellow().bold(), name.bright_white());
+    match resolution {
+        AliasResolution::Alias(canonical) => {
+            println!(
+                "{} {} {}",
+                "Canonical".bright_yellow().bold(),
+                canonical.bright_white().bold(),
+                "(alias)".dimmed()
+            );
+        }
+        AliasResolution::Verbatim(canonical) => {
+            println!(
+                "{} {} {}",
+                "Canonical".bright_yellow().bold(),
+                canonical.bright_white().bold(),
+                "(not in the alias registry, used verbatim)".dimmed()
+            );
+        }
+        AliasResolution::Ignored => {
+            println!(
+                "{} {}",
+                "Canonical".bright_yellow().bold(),
+                "ignored: entries with this name are dropped from every source".bright_red()
+            );
+            return;
+        }
+        AliasResolution::Empty => {
+            println!("{}", "Empty language name".bright_red());
+            return;
+        }
+    }
+    let Some(language) = fetcher.aliases().canonicalize(name) else {
+        return;
+    };
+
+    let partial = fetcher.fetch_all_partial().await;
+    let bundle = partial.bundle();
+    println!("{}", "Sources".bright_yellow().bold());
+    for dataset in bundle.rankings() {
+        let label = ranking_label(dataset.source());
+        match dataset.iter().find(|entry| entry.lang == language) {
+            Some(entry) => println!(
+                "  {label:<16} {} rank {}, share {}%",
+                "found".bright_green(),
+                format_optional_rank(entry.rank),
+                format_optional_float(entry.share)
+            ),
+            None => println!("  {label:<16} {}", "not found".dimmed()),
+        }
+    }
+    // Незагруженный набор производительности пуст и попадает в список сбоев ниже.
+    let performance = [
+        (
+            "Benchmarks",
+            bundle.benchmarks().is_empty(),
+            bundle.benchmarks().get(language),
+        ),
+        (
+            "TechEmpower",
+            bundle.techempower().is_empty(),
+            bundle.techempower().get(language),
+        ),
+    ];
+    for (label, missing, score) in performance {
+        if missing {
+            continue;
+        }
+        match score {
+            Some(score) => println!("  {label:<16} {} score {score:.3}", "found".bright_green()),
+            None => println!("  {label:<16} {}", "not found".dimmed()),
+        }
+    }
+    for failure in partial.failures() {
+        println!(
+            "  {:<16} {} {}",
+            failure.source_name().unwrap_or("unknown"),
+            "unavailable:".bright_red(),
+            format_error_chain(failure)
+        );
+    }
+}
@@ -0,0 +1,526 @@
+use crate::{AliasError, Language};
+use rustc_hash::FxHashMap;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+/// Реестр синонимов названий языков.
+///
+/// Источники называют одни и те же языки по-разному (`golang`, `node.js`,
+/// `gpp`), а реестр сводит их к каноническому имени. Ключи сравниваются без
+/// учёта регистра и пробелов; неизвестное имя остаётся как есть. Синоним
+/// можно отметить как игнорируемый, тогда записи с ним отбрасываются.
+///
+/// Встроенный набор из [`AliasRegistry::builtin`] дополняется или
+/// переопределяется через [`AliasRegistry::insert`] и файлы TOML или JSON:
+///
+/// ```toml
+/// ignore = ["vw"]
+///
+/// [aliases]
+/// bun = "JavaScript"
+/// "graalvm" = "Java"
+///
+/// [variants]
+/// graalvm = { language = "Java", name = "GraalVM" }
+/// ```
+///
+/// Раздел `variants` описывает компиляторы и рантаймы Benchmarks Game,
+/// которые в режиме [`BenchmarkScoring::split_variants`] оцениваются как
+/// отдельные реализации языка.
+///
+/// [`BenchmarkScoring::split_variants`]: crate::BenchmarkScoring::split_variants
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AliasRegistry {
+    aliases: FxHashMap<String, Option<String>>,
+    variants: FxHashMap<String, VariantEntry>,
+}
+
+/// Результат поиска имени в реестре.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AliasResolution<'a> {
+    /// Имя найдено в реестре и сводится к каноническому.
+    Alias(&'a str),
+    /// Имя отмечено как игнорируемое.
+    Ignored,
+    /// Имя отсутствует в реестре и используется как есть.
+    Verbatim(&'a str),
+    /// Пустое имя.
+    Empty,
+}
+
+impl<'a> AliasResolution<'a> {
+    /// Возвращает каноническое имя или `None`, если запись отбрасывается.
+    #[must_use]
+    pub const fn canonical(self) -> Option<&'a str> {
+        match self {
+            Self::Alias(name) | Self::Verbatim(name) => Some(name),
+            Self::Ignored | Self::Empty => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AliasFile {
+    #[serde(default)]
+    aliases: FxHashMap<String, String>,
+    #[serde(default)]
+    ignore: Vec<String>,
+    #[serde(default)]
+    variants: FxHashMap<String, VariantEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VariantEntry {
+    language: String,
+    name: String,
+}
+
+impl AliasRegistry {
+    /// Создаёт пустой реестр, в котором все имена используются как есть.
+    #[must_use]
+    pub fn empty() -> Self {
+        Self {
+            aliases: FxHashMap::default(),
+            variants: FxHashMap::default(),
+        }
+    }
+
+    /// Создаёт реестр со встроенными синонимами.
+    #[must_use]
+    pub fn builtin() -> Self {
+        builtin().as_ref().clone()
+    }
+
+    /// Загружает встроенный реестр, дополненный файлом синонимов.
+    ///
+    /// # Errors
+    ///
+    /// Возвращает ошибку, если файл не удалось прочитать или разобрать.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AliasError> {
+        let mut registry = Self::builtin();
+        registry.extend_from_file(path)?;
+        Ok(registry)
+    }
+
+    /// Добавляет синоним или переопределяет существующий.
+    pub fn insert(&mut self, alias: &str, canonical: impl Into<String>) {
+        self.aliases
+            .insert(normalize_alias_key(alias), Some(canonical.into()));
+    }
+
+    /// Отмечает имя как игнорируемое: записи с ним отбрасываются.
+    pub fn ignore(&mut self, alias: &str) {
+        self.aliases.insert(normalize_alias_key(alias), None);
+    }
+
+    /// Удаляет синоним, после чего имя используется как есть.
+    pub fn remove(&mut self, alias: &str) {
+        self.aliases.remove(&normalize_alias_key(alias));
+    }
+
+    /// Добавляет реализацию языка или переопределяет существующую: имя
+    /// `alias` в CSV Benchmarks Game относится к языку `language`, а
+    /// реализация называется `variant`.
+    pub fn insert_variant(
+        &mut self,
+        alias: &str,
+        language: impl Into<String>,
+        variant: impl Into<String>,
+    ) {
+        self.variants.insert(
+            normalize_alias_key(alias),
+            VariantEntry {
+                language: language.into(),
+                name: variant.into(),
+            },
+        );
+    }
+
+    /// Удаляет реализацию, после чего имя оценивается вместе с языком.
+    pub fn remove_variant(&mut self, alias: &str) {
+        self.variants.remove(&normalize_alias_key(alias));
+    }
+
+    /// Возвращает язык и название реализации для имени компилятора или
+    /// рантайма либо `None`, если имя не описано как реализация.
+    #[must_use]
+    pub fn variant(&self, name: &str) -> Option<(&str, &str)> {
+        self.variants
+            .get(&normalize_alias_key(name))
+            .map(|entry| (entry.language.as_str(), entry.name.as_str()))
+    }
+
+    /// Дополняет реестр файлом синонимов. Формат выбирается по расширению:
+    /// `.json` разбирается как JSON, остальные файлы — как TOML.
+    ///
+    /// # Errors
+    ///
+    /// Возвращает ошибку, если файл не удалось прочитать или разобрать.
+    pub fn extend_from_file(&mut self, path: impl AsRef<Path>) -> Result<(), AliasError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|error| AliasError::Read {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        let is_json = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        if is_json {
+            self.extend_from_json(&text)
+        } else {
+            self.extend_from_toml(&text)
+        }
+    }
+
+    /// Дополняет реестр синонимами в формате TOML.
+    ///
+    /// # Errors
+    ///
+    /// Возвращает ошибку, если текст не соответствует формату файла синонимов.
+    pub fn extend_from_toml(&mut self, text: &str) -> Result<(), AliasError> {
+        let file: AliasFile = toml::from_str(text).map_err(AliasError::Toml)?;
+        self.apply(file);
+        Ok(())
+    }
+
+    /// Дополняет реестр синонимами в формате JSON.
+    ///
+    /// # Errors
+    ///
+    /// Возвращает ошибку, если текст не соответствует формату файла синонимов.
+    pub fn extend_from_json(&mut self, text: &str) -> Result<(), AliasError> {
+        let file: AliasFile = serde_json::from_str(text).map_err(AliasError::Json)?;
+        self.apply(file);
+        Ok(())
+    }
+
+    /// Ищет имя в реестре.
+    #[must_use]
+    pub fn resolve<'a>(&'a self, name: &'a str) -> AliasResolution<'a> {
+        let trimmed = name.trim();
+        if trimmed.is_empty() {
+            return AliasResolution::Empty;
+        }
+        match self.aliases.get(&normalize_alias_key(trimmed)) {
+            Some(Some(canonical)) => AliasResolution::Alias(canonical),
+            Some(None) => AliasResolution::Ignored,
+            None => AliasResolution::Verbatim(trimmed),
+        }
+    }
+
+    /// Возвращает канонический язык или `None`, если запись отбрасывается.
+    #[must_use]
+    pub fn canonicalize(&self, name: &str) -> Option<Language> {
+        self.resolve(name).canonical().map(Language::intern)
+    }
+
+    /// Проверяет, что имя известно реестру: является синонимом или
+    /// каноническим именем, на которое ссылается синоним.
+    #[must_use]
+    pub fn is_mapped(&self, name: &str) -> bool {
+        match self.resolve(name) {
+            AliasResolution::Alias(_) | AliasResolution::Ignored => true,
+            AliasResolution::Verbatim(name) => self
+                .aliases
+                .values()
+                .any(|canonical| canonical.as_deref() == Some(name)),
+            AliasResolution::Empty => false,
+        }
+    }
+
+    /// Возвращает количество синонимов, включая игнорируемые имена, без
+    /// учёта реализаций.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.aliases.len()
+    }
+
+    /// Проверяет, что реестр пуст.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+    }
+
+    fn apply(&mut self, file: AliasFile) {
+        for (alias, canonical) in file.aliases {
+            self.insert(&alias, canonical);
+        }
+        for alias in file.ignore {
+            self.ignore(&alias);
+        }
+        for (alias, VariantEntry { language, name }) in file.variants {
+            self.insert_variant(&alias, language, name);
+        }
+    }
+}
+
+impl Default for AliasRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+/// Возвращает общий экземпляр встроенного реестра.
+pub fn builtin() -> &'static Arc<AliasRegistry> {
+    static BUILTIN: OnceLock<Arc<AliasRegistry>> = OnceLock::new();
+    BUILTIN.get_or_init(|| {
+        let mut registry = AliasRegistry::empty();
+        for (alias, canonical) in BUILTIN_ALIASES {
+            registry.insert(alias, canonical);
+        }
+        for alias in BUILTIN_IGNORED {
+            registry.ignore(alias);
+        }
+        for (alias, language, variant) in BUILTIN_VARIANTS {
+            registry.insert_variant(alias, language, variant);
+        }
+        Arc::new(registry)
+    })
+}
+
+pub fn normalize_alias_key(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for ch in input.chars() {
+        if ch.is_whitespace() {
+            continue;
+        }
+        out.extend(ch.to_lowercase());
+    }
+    out
+}
+
+const BUILTIN_ALIASES: [(&str, &str); 92] = [
+    ("delphi/objectpascal", "Delphi/Pascal"),
+    ("matlab", "Matlab"),
+    ("cobol", "COBOL"),
+    ("powershell", "PowerShell"),
+    ("vbscript", "VBA/VBS"),
+    ("vba", "VBA/VBS"),
+    // ("classicvisualbasic", "VBA/VBS"),
+    ("abap", "Abap"),
+    ("(visual)foxpro", "FoxPro"),
+    ("c", "C"),
+    ("c#", "C#"),
+    ("csharp", "C#"),
+    ("c-sharp", "C#"),
+    ("c++", "C++"),
+    ("c/c++", "C/C++"),
+    ("f#", "F#"),
+    ("fsharp", "F#"),
+    ("f-sharp", "F#"),
+    ("javascript", "JavaScript"),
+    ("js", "JavaScript"),
+    ("node", "JavaScript"),
+    ("node.js", "JavaScript"),
+    ("nodejs", "JavaScript"),
+    ("typescript", "TypeScript"),
+    ("ts", "TypeScript"),
+    ("objective-c", "Objective-C"),
+    ("objectivec", "Objective-C"),
+    ("obj-c", "Objective-C"),
+    ("objc", "Objective-C"),
+    ("golang", "Go"),
+    ("go", "Go"),
+    ("cpp", "C++"),
+    ("vb", "Visual Basic"),
+    ("vb.net", "Visual Basic"),
+    ("vbnet", "Visual Basic"),
+    ("visualbasic", "Visual Basic"),
+    ("visualbasic.net", "Visual Basic"),
+    ("cfml", "CFML"),
+    ("clojure", "Clojure"),
+    ("commonlisp", "Lisp"),
+    ("crystal", "Crystal"),
+    ("d", "D"),
+    ("dart", "Dart"),
+    ("elixir", "Elixir"),
+    ("fortran", "Fortran"),
+    ("haskell", "Haskell"),
+    ("julia", "Julia"),
+    ("kotlin", "Kotlin"),
+    ("lua", "Lua"),
+    ("luau", "Luau"),
+    ("nim", "Nim"),
+    ("pascal", "Delphi/Pascal"),
+    ("prolog", "Prolog"),
+    ("python", "Python"),
+    ("r", "R"),
+    ("ruby", "Ruby"),
+    ("rust", "Rust"),
+    ("scala", "Scala"),
+    ("swift", "Swift"),
+    ("ur", "Ur"),
+    ("v", "V"),
+    ("vala", "Vala"),
+    ("zig", "Zig"),
+    // Benchmarks Game aliases and runtimes.
+    ("chapel", "Chapel"),
+    ("clang", "C/C++"),
+    ("csharpaot", "C#"),
+    ("csharpcore", "C#"),
+    ("dartexe", "Dart"),
+    ("dartjit", "Dart"),
+    ("erlang", "Erlang"),
+    ("fpascal", "Delphi/Pascal"),
+    ("fsharpcore", "F#"),
+    ("gcc", "C/C++"),
+    ("ghc", "Haskell"),
+    ("gnat", "Ada"),
+    ("gpp", "C/C++"),
+    ("graalvm", "Graal"),
+    ("icx", "C/C++"),
+    ("ifc", "Fortran"),
+    ("ifx", "Fortran"),
+    ("java", "Java"),
+    ("javaxint", "Java"),
+    ("micropython", "Python"),
+    ("mri", "Ruby"),
+    ("ocaml", "OCaml"),
+    ("openj9", "Java"),
+    ("perl", "Perl"),
+    ("pharo", "Smalltalk"),
+    ("php", "PHP"),
+    ("python3", "Python"),
+    ("racket", "Racket"),
+    ("sbcl", "Lisp"),
+    ("toit", "Toit"),
+];
+
+// Рантаймы Benchmarks Game, которые не соответствуют отдельному языку.
+const BUILTIN_IGNORED: [&str; 1] = ["vw"];
+
+// Компиляторы и рантаймы Benchmarks Game: имя в CSV, язык и название
+// реализации.
+const BUILTIN_VARIANTS: [(&str, &str, &str); 17] = [
+    ("gcc", "C", "GCC"),
+    ("clang", "C", "Clang"),
+    ("icx", "C", "Intel"),
+    ("gpp", "C++", "GCC"),
+    ("clangpp", "C++", "Clang"),
+    ("icpx", "C++", "Intel"),
+    ("csharpcore", "C#", ".NET"),
+    ("csharpaot", "C#", "Native AOT"),
+    ("dartjit", "Dart", "JIT"),
+    ("dartexe", "Dart", "AOT"),
+    ("java", "Java", "HotSpot"),
+    ("openj9", "Java", "OpenJ9"),
+    ("javaxint", "Java", "interpreter"),
+    ("python3", "Python", "CPython"),
+    ("micropython", "Python", "MicroPython"),
+    ("ifc", "Fortran", "ifort"),
+    ("ifx", "Fortran", "ifx"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::{AliasRegistry, AliasResolution, BUILTIN_ALIASES};
+    use crate::Language;
+
+    #[test]
+    fn builtin_aliases_ignore_case_and_whitespace() {
+        let registry = AliasRegistry::builtin();
+
+        assert_eq!(
+            registry.resolve(" Node.JS "),
+            AliasResolution::Alias("JavaScript")
+        );
+        assert_eq!(
+            registry.resolve("Visual Basic"),
+            AliasResolution::Alias("Visual Basic")
+        );
+        assert_eq!(registry.resolve("vw"), AliasResolution::Ignored);
+        assert_eq!(
+            registry.resolve("Gleam"),
+            AliasResolution::Verbatim("Gleam")
+        );
+        assert_eq!(registry.resolve("  "), AliasResolution::Empty);
+    }
+
+    #[test]
+    fn builtin_canonical_names_have_distinct_slugs() {
+        let mut names: Vec<&str> = BUILTIN_ALIASES
+            .iter()
+            .map(|&(_, canonical)| canonical)
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        let mut slugs: Vec<&str> = names
+            .iter()
+            .map(|&name| Language::intern(name).slug())
+            .collect();
+        slugs.sort_unstable();
+        let count = slugs.len();
+        slugs.dedup();
+
+        assert_eq!(slugs.len(), count);
+    }
+
+    #[test]
+    fn files_extend_and_override_builtin_aliases() {
+        let mut registry = AliasRegistry::builtin();
+        registry
+            .extend_from_toml(
+                r#"
+                ignore = ["micropython"]
+
+                [aliases]
+                bun = "JavaScript"
+                vw = "Smalltalk"
+                "#,
+            )
+            .expect("TOML alias file should parse");
+        registry
+            .extend_from_json(r#"{"aliases": {"Go Lang": "Go"}}"#)
+            .expect("JSON alias file should parse");
+
+        assert_eq!(
+            registry.canonicalize("Bun").map(Language::name),
+            Some("JavaScript")
+        );
+        assert_eq!(
+            registry.canonicalize("vw").map(Language::name),
+            Some("Smalltalk")
+        );
+        assert_eq!(
+            registry.canonicalize("golang").map(Language::name),
+            Some("Go")
+        );
+        assert_eq!(
+            registry.canonicalize("go lang").map(Language::name),
+            Some("Go")
+        );
+        assert_eq!(registry.canonicalize("MicroPython"), None);
+        assert!(registry.extend_from_toml("unknown = 1").is_err());
+    }
+
+    #[test]
+    fn files_add_and_override_runtime_variants() {
+        let mut registry = AliasRegistry::builtin();
+        assert_eq!(registry.variant("GPP"), Some(("C++", "GCC")));
+        assert_eq!(registry.variant("rust"), None);
+
+        registry
+            .extend_from_toml(
+                r#"
+                [variants]
+                graalvm = { language = "Java", name = "GraalVM" }
+                python3 = { language = "Python", name = "CPython 3" }
+                "#,
+            )
+            .expect("TOML variants should parse");
+        registry.remove_variant("clang");
+
+        assert_eq!(registry.variant("GraalVM"), Some(("Java", "GraalVM")));
+        assert_eq!(registry.variant("python3"), Some(("Python", "CPython 3")));
+        assert_eq!(registry.variant("clang"), None);
+        assert!(
+            registry
+                .extend_from_toml("[variants]\nbun = { language = \"JavaScript\" }")
+                .is_err()
+        );
+    }
+}
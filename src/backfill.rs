@@ -0,0 +1,44 @@
+use crate::save_schulze_csv;
+use anyhow::{Context, Result, anyhow};
+use colored::Colorize;
+use langrank::{Fetcher, compute_languish_backfill};
+use std::path::Path;
+
+/// Строит ранжирование Шульце только по Languish для каждого квартала истории
+/// и сохраняет по CSV на квартал. Тренд и бюллетени сигналов берутся из
+/// стратегии Languish загрузчика.
+pub async fn backfill_rankings(
+    fetcher: &Fetcher,
+    output_dir: &Path,
+    since: Option<&str>,
+    archive: bool,
+) -> Result<()> {
+    let history = fetcher.fetch_languish_history().await?;
+    let mut quarters =
+        compute_languish_backfill(&history, since, fetcher.languish_scoring()).peekable();
+    if quarters.peek().is_none() {
+        return Err(anyhow!(
+            "Languish history has no quarters since {}",
+            since.unwrap_or("the beginning")
+        ));
+    }
+
+    for (quarter, records) in quarters {
+        let records =
+            records.with_context(|| format!("failed to rank Languish quarter {quarter}"))?;
+        let path = output_dir.join(format!("schulze_{quarter}.csv"));
+        let saved = save_schulze_csv(&records, &path, archive).await?;
+        let leaders: Vec<&str> = records
+            .iter()
+            .take(3)
+            .map(|record| record.lang.name())
+            .collect();
+        println!(
+            "{} {} {}",
+            quarter.bright_yellow().bold(),
+            leaders.join(", ").bright_white(),
+            saved.display().to_string().dimmed()
+        );
+    }
+    Ok(())
+}
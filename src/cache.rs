@@ -0,0 +1,263 @@
+use crate::HttpResponse;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+const DEFAULT_PERIOD_MAX_AGE: Duration = Duration::from_secs(SECONDS_PER_DAY);
+
+/// Дисковый кэш HTTP-ответов источников.
+///
+/// Ответ считается свежим и берётся из кэша без обращения к сети, если он
+/// моложе `max_age` или если источник ещё не опубликовал новый выпуск: TIOBE и
+/// PYPL обновляются раз в месяц, Languish — раз в квартал (по UTC). Точная дата
+/// публикации внутри периода неизвестна, поэтому ответ из текущего периода
+/// остаётся свежим не дольше `period_max_age` (по умолчанию сутки), иначе
+/// сохранённый до выхода нового выпуска ответ жил бы до конца периода.
+/// Устаревший ответ перепроверяется условным запросом с `If-None-Match` и
+/// `If-Modified-Since`; на ответ 304 используется сохранённое тело.
+///
+/// Ошибки чтения и записи кэша не прерывают загрузку: запрос просто
+/// выполняется по сети.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpCache {
+    dir: PathBuf,
+    max_age: Option<Duration>,
+    period_max_age: Duration,
+}
+
+/// Периодичность публикации источника.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PublicationPeriod {
+    Monthly,
+    Quarterly,
+}
+
+impl PublicationPeriod {
+    const fn index(self, unix_seconds: u64) -> u64 {
+        let (year, month) = civil_year_month(unix_seconds / SECONDS_PER_DAY);
+        let months = year * 12 + (month - 1);
+        match self {
+            Self::Monthly => months,
+            Self::Quarterly => months / 3,
+        }
+    }
+}
+
+/// Сохранённый ответ вместе со временем последней проверки.
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    stored_at: u64,
+    response: HttpResponse,
+}
+
+impl CachedResponse {
+    pub fn into_response(self) -> HttpResponse {
+        self.response
+    }
+
+    /// Возвращает заголовки условного запроса для перепроверки ответа.
+    pub fn validators(&self) -> Vec<(String, String)> {
+        let mut headers = Vec::new();
+        if let Some(etag) = self.response.header("etag") {
+            headers.push(("If-None-Match".to_owned(), etag.to_owned()));
+        }
+        if let Some(modified) = self.response.header("last-modified") {
+            headers.push(("If-Modified-Since".to_owned(), modified.to_owned()));
+        }
+        headers
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheMetadata {
+    url: String,
+    stored_at: u64,
+    status: u16,
+    headers: Vec<(String, String)>,
+}
+
+impl HttpCache {
+    /// Создаёт кэш в указанном каталоге. Каталог создаётся при первой записи.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_age: None,
+            period_max_age: DEFAULT_PERIOD_MAX_AGE,
+        }
+    }
+
+    /// Считает ответы моложе `max_age` свежими для любых источников.
+    #[must_use]
+    pub const fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Ограничивает возраст ответа, который считается свежим только потому,
+    /// что источник ещё не опубликовал новый выпуск.
+    #[must_use]
+    pub const fn with_period_max_age(mut self, period_max_age: Duration) -> Self {
+        self.period_max_age = period_max_age;
+        self
+    }
+
+    /// Возвращает каталог кэша.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Возвращает максимальный возраст свежего ответа, если он задан.
+    #[must_use]
+    pub const fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+
+    /// Возвращает предельный возраст ответа из текущего периода публикации.
+    #[must_use]
+    pub const fn period_max_age(&self) -> Duration {
+        self.period_max_age
+    }
+
+    pub(crate) fn load(&self, url: &str) -> Option<CachedResponse> {
+        let (metadata_path, body_path) = self.entry_paths(url);
+        let metadata: CacheMetadata =
+            serde_json::from_slice(&fs::read(metadata_path).ok()?).ok()?;
+        if metadata.url != url {
+            return None;
+        }
+        let body = fs::read(body_path).ok()?;
+        let response = metadata.headers.into_iter().fold(
+            HttpResponse::new(metadata.status, body),
+            |response, (name, value)| response.with_header(name, value),
+        );
+        Some(CachedResponse {
+            stored_at: metadata.stored_at,
+            response,
+        })
+    }
+
+    pub(crate) fn is_fresh(
+        &self,
+        cached: &CachedResponse,
+        period: Option<PublicationPeriod>,
+        now: u64,
+    ) -> bool {
+        let age = Duration::from_secs(now.saturating_sub(cached.stored_at));
+        let within_max_age = self.max_age.is_some_and(|max_age| age < max_age);
+        let same_period = age < self.period_max_age
+            && period.is_some_and(|period| period.index(cached.stored_at) == period.index(now));
+        within_max_age || same_period
+    }
+
+    pub(crate) fn store(&self, url: &str, response: &HttpResponse, now: u64) {
+        let (metadata_path, body_path) = self.entry_paths(url);
+        let metadata = CacheMetadata {
+            url: url.to_owned(),
+            stored_at: now,
+            status: response.status(),
+            headers: response.headers().to_vec(),
+        };
+        let Ok(serialized) = serde_json::to_vec_pretty(&metadata) else {
+            return;
+        };
+        let _ = fs::create_dir_all(&self.dir)
+            .and_then(|()| fs::write(body_path, response.body()))
+            .and_then(|()| fs::write(metadata_path, serialized));
+    }
+
+    fn entry_paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let key = format!("{:016x}", fnv1a(url.as_bytes()));
+        (
+            self.dir.join(format!("{key}.json")),
+            self.dir.join(format!("{key}.bin")),
+        )
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+// Алгоритм civil_from_days Говарда Хиннанта для дат не раньше 1970 года.
+const fn civil_year_month(days_since_epoch: u64) -> (u64, u64) {
+    let z = days_since_epoch + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as u64;
+    (year, month)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HttpCache, PublicationPeriod, civil_year_month};
+    use crate::HttpResponse;
+    use std::time::Duration;
+
+    const DAY: u64 = 86_400;
+    // 2026-03-31T12:00:00Z
+    const MARCH_31: u64 = 1_774_958_400;
+
+    #[test]
+    fn converts_days_to_calendar_months() {
+        assert_eq!(civil_year_month(0), (1970, 1));
+        assert_eq!(civil_year_month(MARCH_31 / DAY), (2026, 3));
+        assert_eq!(civil_year_month(MARCH_31 / DAY + 1), (2026, 4));
+    }
+
+    #[test]
+    fn freshness_follows_max_age_and_publication_period() {
+        let dir = std::env::temp_dir().join(format!("langrank-cache-{}", std::process::id()));
+        let cache = HttpCache::new(&dir)
+            .with_max_age(Duration::from_hours(1))
+            .with_period_max_age(Duration::from_secs(30 * DAY));
+        let response = HttpResponse::new(200, b"body".to_vec()).with_header("ETag", "\"v1\"");
+        cache.store("https://example.com/a", &response, MARCH_31 - 20 * DAY);
+
+        let cached = cache
+            .load("https://example.com/a")
+            .expect("stored response should be loaded");
+        std::fs::remove_dir_all(&dir).expect("temporary cache should be removed");
+
+        assert!(cache.is_fresh(&cached, Some(PublicationPeriod::Monthly), MARCH_31));
+        assert!(!cache.is_fresh(&cached, Some(PublicationPeriod::Monthly), MARCH_31 + DAY));
+        assert!(cache.is_fresh(&cached, Some(PublicationPeriod::Quarterly), MARCH_31));
+        assert!(!cache.is_fresh(&cached, None, MARCH_31));
+
+        // Ответ, сохранённый до публикации выпуска, не живёт до конца месяца.
+        let ceiling = HttpCache::new(&dir);
+        assert!(!ceiling.is_fresh(&cached, Some(PublicationPeriod::Monthly), MARCH_31));
+        assert!(ceiling.is_fresh(
+            &cached,
+            Some(PublicationPeriod::Monthly),
+            MARCH_31 - 20 * DAY + DAY / 2
+        ));
+        assert_eq!(
+            cached.validators(),
+            [("If-None-Match".to_owned(), "\"v1\"".to_owned())]
+        );
+        assert_eq!(cached.into_response(), response);
+    }
+}
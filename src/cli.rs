@@ -3,20 +3,72 @@ use std::io::{self, Write};
 use std::path::PathBuf;
 
 use anyhow::{Context, Result, anyhow};
-use clap::{CommandFactory, Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::{Shell, generate, generate_to};
+use langrank::{
+    BenchmarkMetric, BenchmarkScoring, FrameworkApproach, FrameworkClassification, FrameworkOrm,
+    LanguishScoring, RunSelection, TechEmpowerAggregation, TechEmpowerFilter, TechEmpowerScoring,
+    TimeMeasure,
+};
 
 pub const DEFAULT_RANKINGS_PATH: &str = "data/input/rankings.csv";
 pub const DEFAULT_BENCHMARKS_PATH: &str = "data/input/benchmarksgame.csv";
 pub const DEFAULT_SCHULZE_PATH: &str = "data/output/schulze_rankings.csv";
 pub const DEFAULT_HTML_PATH: &str = "data/output/report.html";
+pub const DEFAULT_NAME_REPORT_PATH: &str = "data/output/name_report.json";
+pub const DEFAULT_CACHE_DIR: &str = "data/cache";
+pub const DEFAULT_BACKFILL_DIR: &str = "data/output/backfill";
 
 pub const SAVE_RANKINGS_HELP: &str = "Save combined TIOBE/PYPL rankings to the given CSV file (defaults to data/input/rankings.csv when no path is provided). Use --archive-csv to store a .gz instead.";
 pub const SAVE_BENCHMARKS_HELP: &str = "Save the downloaded benchmark dataset to the given CSV file (defaults to data/input/benchmarksgame.csv when no path is provided). Use --archive-csv to store a .gz instead.";
 pub const SAVE_SCHULZE_HELP: &str = "Save the computed Schulze ranking to the given CSV file (defaults to data/output/schulze_rankings.csv when no path is provided). Use --archive-csv to store a .gz instead.";
 pub const SAVE_HTML_HELP: &str = "Save the HTML report to the given file (defaults to data/output/report.html when no path is provided).";
+pub const SAVE_NAME_REPORT_HELP: &str = "Save the language name report (names found in a single source or missing from the alias registry, with likely matches) to the given JSON file (defaults to data/output/name_report.json when no path is provided).";
 pub const NO_MINIFY_HTML_HELP: &str =
     "Disable HTML minification (minification is enabled by default when saving HTML).";
+pub const RECORD_HELP: &str =
+    "Record every raw HTTP response downloaded during the run into the given snapshot directory.";
+pub const REPLAY_HELP: &str =
+    "Rebuild the ranking from a snapshot directory created with --record, without network access.";
+pub const ALLOW_PARTIAL_HELP: &str = "Finish the ranking with the remaining sources when some sources fail to download, and flag the missing ones in the summary and HTML report.";
+pub const MAX_ATTEMPTS_HELP: &str =
+    "Number of attempts for each HTTP request, including the first one (default: 3).";
+pub const REQUEST_TIMEOUT_HELP: &str =
+    "Timeout in seconds for a single HTTP request attempt (default: 20).";
+pub const CACHE_DIR_HELP: &str = "Cache raw HTTP responses in the given directory (defaults to data/cache when no path is provided). TIOBE and PYPL responses are reused within the same month, Languish within the same quarter, but for at most one day (see --cache-period-max-age); stale entries are revalidated with conditional requests.";
+pub const CACHE_MAX_AGE_HELP: &str =
+    "Reuse cached responses of any source that are younger than the given number of seconds.";
+pub const CACHE_PERIOD_MAX_AGE_HELP: &str = "Reuse TIOBE, PYPL and Languish responses cached in the current publication period only while they are younger than the given number of seconds (default: 86400).";
+pub const TIOBE_URL_HELP: &str =
+    "Download the TIOBE index page from the given URL instead of tiobe.com.";
+pub const PYPL_URL_HELP: &str =
+    "Download the PYPL page from the given URL instead of pypl.github.io.";
+pub const LANGUISH_URL_HELP: &str = "Base URL of the Languish site (with a trailing slash); the JS bundle is resolved relative to it.";
+pub const BENCHMARKS_GAME_URL_HELP: &str =
+    "Download the Benchmarks Game CSV from the given URL instead of salsa.debian.org.";
+pub const TECHEMPOWER_URL_HELP: &str = "Base URL of the TechEmpower benchmarks site (with a trailing slash); round results are resolved relative to it.";
+pub const ALIASES_HELP: &str = "Load extra language aliases from a TOML or JSON file; its entries extend and override the built-in alias registry.";
+pub const BG_BALLOTS_HELP: &str = "Add a separate Schulze ballot for each listed Benchmarks Game metric (comma-separated: cpu-time, memory, source-size, reliability).";
+pub const BG_PERF_METRICS_HELP: &str = "Fold the listed Benchmarks Game metrics into the combined performance score, each weighted like the elapsed-time and TechEmpower scores (comma-separated: cpu-time, memory, source-size, reliability).";
+pub const BG_RUNS_HELP: &str = "Which run represents a language on a Benchmarks Game task: the best one (default) or the median of all successful runs.";
+pub const BG_TIME_HELP: &str = "Compare Benchmarks Game runs by elapsed (default) or CPU time.";
+pub const BG_MIN_COVERAGE_HELP: &str = "Drop languages that have results for less than the given share (0..1) of the scored Benchmarks Game tasks.";
+pub const BG_MIN_PARTICIPANTS_HELP: &str =
+    "Ignore Benchmarks Game tasks with results from fewer than the given number of languages.";
+pub const BG_TASK_WEIGHT_HELP: &str = "Weight of a Benchmarks Game task in the geometric mean, e.g. regex-redux=0.5 (default 1, 0 excludes the task). May be repeated.";
+pub const BG_VARIANTS_HELP: &str = "Score Benchmarks Game compilers and runtimes (e.g. gcc/clang for C, gpp for C++, MicroPython) as separate implementations; each language takes its best implementation and the summary shows the spread across them.";
+pub const TE_AGGREGATION_HELP: &str = "How a language's TechEmpower frameworks are combined into its score: the best framework (default), the mean of the top K frameworks, or the median of all frameworks.";
+pub const TE_TOP_K_HELP: &str =
+    "Number of best frameworks averaged by --te-aggregation top-k (default 3).";
+pub const TE_CLASSIFICATION_HELP: &str = "Score only TechEmpower frameworks of the listed classes (comma-separated: fullstack, micro, platform).";
+pub const TE_APPROACH_HELP: &str = "Score only TechEmpower implementations with the listed approaches (comma-separated: realistic, stripped).";
+pub const TE_ORM_HELP: &str = "Score only TechEmpower implementations with the listed kinds of database access (comma-separated: full, micro, raw).";
+pub const TE_DATABASE_HELP: &str = "Score only TechEmpower implementations using the listed databases, e.g. postgres,mysql (case-insensitive).";
+pub const TE_OS_HELP: &str = "Score only TechEmpower implementations running on the listed operating systems, e.g. linux (case-insensitive).";
+pub const TE_ROUND_HELP: &str = "Score the given TechEmpower round (e.g. 22) instead of the latest available one; list rounds with the te-rounds command.";
+pub const LANGUISH_WEIGHT_HELP: &str = "Weight of a Languish signal in the Languish score, e.g. stars=0 or issues=2,so-questions=0.5 (signals: issues, pulls, so-questions, stars; default 1 each, 0 excludes the signal).";
+pub const LANGUISH_TREND_QUARTERS_HELP: &str = "Number of quarters the Languish trend looks back: 1 compares with the previous quarter (default), 4 gives year-over-year changes, 0 disables the trend.";
+pub const LANGUISH_BALLOTS_HELP: &str = "Vote with a separate Schulze ballot for each Languish signal (GitHub issues, pull requests, stars and Stack Overflow questions) instead of one ballot for the averaged share; --languish-weight does not affect these ballots.";
 pub const ARCHIVE_CSV_HELP: &str =
     "Archive saved CSV outputs into .gz files (recommended for publishing).";
 
@@ -60,6 +112,14 @@ pub struct Cli {
         help = SAVE_HTML_HELP
     )]
     pub save_html: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        default_missing_value = DEFAULT_NAME_REPORT_PATH,
+        help = SAVE_NAME_REPORT_HELP
+    )]
+    pub save_name_report: Option<PathBuf>,
     #[arg(long, help = NO_MINIFY_HTML_HELP)]
     pub no_minify_html: bool,
     #[arg(long, help = ARCHIVE_CSV_HELP)]
@@ -71,10 +131,383 @@ pub struct Cli {
     pub full_output: bool,
     #[arg(long, help = "Disable progress spinner output.")]
     pub no_progress: bool,
+    #[arg(long, value_name = "DIR", conflicts_with = "replay", help = RECORD_HELP)]
+    pub record: Option<PathBuf>,
+    #[arg(long, value_name = "DIR", help = REPLAY_HELP)]
+    pub replay: Option<PathBuf>,
+    #[arg(long, help = ALLOW_PARTIAL_HELP)]
+    pub allow_partial: bool,
+    #[arg(long, value_name = "N", help = MAX_ATTEMPTS_HELP)]
+    pub max_attempts: Option<u32>,
+    #[arg(long, value_name = "SECONDS", help = REQUEST_TIMEOUT_HELP)]
+    pub request_timeout: Option<u64>,
+    #[arg(
+        long,
+        value_name = "DIR",
+        num_args = 0..=1,
+        default_missing_value = DEFAULT_CACHE_DIR,
+        conflicts_with_all = ["record", "replay"],
+        help = CACHE_DIR_HELP
+    )]
+    pub cache_dir: Option<PathBuf>,
+    #[arg(long, value_name = "SECONDS", requires = "cache_dir", help = CACHE_MAX_AGE_HELP)]
+    pub cache_max_age: Option<u64>,
+    #[arg(long, value_name = "SECONDS", requires = "cache_dir", help = CACHE_PERIOD_MAX_AGE_HELP)]
+    pub cache_period_max_age: Option<u64>,
+    #[arg(long, value_name = "URL", env = "LANGRANK_TIOBE_URL", help = TIOBE_URL_HELP)]
+    pub tiobe_url: Option<String>,
+    #[arg(long, value_name = "URL", env = "LANGRANK_PYPL_URL", help = PYPL_URL_HELP)]
+    pub pypl_url: Option<String>,
+    #[arg(long, value_name = "URL", env = "LANGRANK_LANGUISH_URL", help = LANGUISH_URL_HELP)]
+    pub languish_url: Option<String>,
+    #[arg(
+        long,
+        value_name = "URL",
+        env = "LANGRANK_BENCHMARKS_GAME_URL",
+        help = BENCHMARKS_GAME_URL_HELP
+    )]
+    pub benchmarks_game_url: Option<String>,
+    #[arg(
+        long,
+        value_name = "URL",
+        env = "LANGRANK_TECHEMPOWER_URL",
+        help = TECHEMPOWER_URL_HELP
+    )]
+    pub techempower_url: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        env = "LANGRANK_ALIASES",
+        help = ALIASES_HELP
+    )]
+    pub aliases: Option<PathBuf>,
+    #[arg(long, value_name = "METRICS", value_delimiter = ',', help = BG_BALLOTS_HELP)]
+    pub bg_ballots: Vec<ExtraBenchmarkMetric>,
+    #[arg(long, value_name = "METRICS", value_delimiter = ',', help = BG_PERF_METRICS_HELP)]
+    pub bg_perf_metrics: Vec<ExtraBenchmarkMetric>,
+    #[arg(long, value_enum, value_name = "RUNS", help = BG_RUNS_HELP)]
+    pub bg_runs: Option<BenchmarkRuns>,
+    #[arg(long, value_enum, value_name = "TIME", help = BG_TIME_HELP)]
+    pub bg_time: Option<BenchmarkTime>,
+    #[arg(long, value_name = "SHARE", value_parser = parse_share, help = BG_MIN_COVERAGE_HELP)]
+    pub bg_min_coverage: Option<f64>,
+    #[arg(long, value_name = "N", help = BG_MIN_PARTICIPANTS_HELP)]
+    pub bg_min_participants: Option<usize>,
+    #[arg(
+        long,
+        value_name = "TASK=WEIGHT",
+        value_parser = parse_task_weight,
+        help = BG_TASK_WEIGHT_HELP
+    )]
+    pub bg_task_weight: Vec<(String, f64)>,
+    #[arg(long, help = BG_VARIANTS_HELP)]
+    pub bg_variants: bool,
+    #[arg(long, value_enum, value_name = "AGGREGATION", help = TE_AGGREGATION_HELP)]
+    pub te_aggregation: Option<TechEmpowerAggregationArg>,
+    #[arg(long, value_name = "K", value_parser = parse_top_k, help = TE_TOP_K_HELP)]
+    pub te_top_k: Option<usize>,
+    #[arg(long, value_enum, value_name = "CLASSES", value_delimiter = ',', help = TE_CLASSIFICATION_HELP)]
+    pub te_classification: Vec<FrameworkClassificationArg>,
+    #[arg(long, value_enum, value_name = "APPROACHES", value_delimiter = ',', help = TE_APPROACH_HELP)]
+    pub te_approach: Vec<FrameworkApproachArg>,
+    #[arg(long, value_enum, value_name = "ORMS", value_delimiter = ',', help = TE_ORM_HELP)]
+    pub te_orm: Vec<FrameworkOrmArg>,
+    #[arg(long, value_name = "DATABASES", value_delimiter = ',', help = TE_DATABASE_HELP)]
+    pub te_database: Vec<String>,
+    #[arg(long, value_name = "SYSTEMS", value_delimiter = ',', help = TE_OS_HELP)]
+    pub te_os: Vec<String>,
+    #[arg(long, value_name = "ROUND", help = TE_ROUND_HELP)]
+    pub te_round: Option<u16>,
+    #[arg(
+        long,
+        value_name = "SIGNAL=WEIGHT",
+        value_delimiter = ',',
+        value_parser = parse_languish_weight,
+        help = LANGUISH_WEIGHT_HELP
+    )]
+    pub languish_weight: Vec<(LanguishSignalArg, f64)>,
+    #[arg(long, value_name = "N", help = LANGUISH_TREND_QUARTERS_HELP)]
+    pub languish_trend_quarters: Option<usize>,
+    #[arg(long, help = LANGUISH_BALLOTS_HELP)]
+    pub languish_ballots: bool,
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
 
+impl Cli {
+    /// Собирает стратегию вычисления показателей Benchmarks Game из флагов.
+    pub fn benchmark_scoring(&self) -> BenchmarkScoring {
+        let defaults = BenchmarkScoring::default();
+        BenchmarkScoring {
+            selection: self
+                .bg_runs
+                .map_or(defaults.selection, BenchmarkRuns::selection),
+            measure: self
+                .bg_time
+                .map_or(defaults.measure, BenchmarkTime::measure),
+            min_task_coverage: self.bg_min_coverage.unwrap_or(defaults.min_task_coverage),
+            min_task_participants: self
+                .bg_min_participants
+                .unwrap_or(defaults.min_task_participants),
+            task_weights: self.bg_task_weight.iter().cloned().collect(),
+            split_variants: self.bg_variants,
+        }
+    }
+
+    /// Собирает веса сигналов и окно тренда Languish из флагов.
+    pub fn languish_scoring(&self) -> Result<LanguishScoring> {
+        let defaults = LanguishScoring::default();
+        let mut weights = defaults.weights;
+        for &(signal, weight) in &self.languish_weight {
+            match signal {
+                LanguishSignalArg::Issues => weights.issues = weight,
+                LanguishSignalArg::Pulls => weights.pulls = weight,
+                LanguishSignalArg::SoQuestions => weights.so_questions = weight,
+                LanguishSignalArg::Stars => weights.stars = weight,
+            }
+        }
+        if weights.total() <= 0.0 {
+            return Err(anyhow!(
+                "--languish-weight must leave at least one signal with a positive weight"
+            ));
+        }
+        Ok(LanguishScoring {
+            weights,
+            trend_quarters: self
+                .languish_trend_quarters
+                .unwrap_or(defaults.trend_quarters),
+            signal_ballots: self.languish_ballots,
+        })
+    }
+
+    /// Собирает стратегию вычисления показателей `TechEmpower` из флагов.
+    pub fn techempower_scoring(&self) -> TechEmpowerScoring {
+        let defaults = TechEmpowerScoring::default();
+        TechEmpowerScoring {
+            aggregation: self
+                .te_aggregation
+                .map_or(defaults.aggregation, TechEmpowerAggregationArg::aggregation),
+            top_k: self.te_top_k.unwrap_or(defaults.top_k),
+            filter: TechEmpowerFilter {
+                classifications: self
+                    .te_classification
+                    .iter()
+                    .copied()
+                    .map(FrameworkClassificationArg::classification)
+                    .collect(),
+                approaches: self
+                    .te_approach
+                    .iter()
+                    .copied()
+                    .map(FrameworkApproachArg::approach)
+                    .collect(),
+                orms: self
+                    .te_orm
+                    .iter()
+                    .copied()
+                    .map(FrameworkOrmArg::orm)
+                    .collect(),
+                databases: self.te_database.clone(),
+                operating_systems: self.te_os.clone(),
+            },
+        }
+    }
+}
+
+/// Выбор запуска Benchmarks Game, доступный из командной строки.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum BenchmarkRuns {
+    Best,
+    Median,
+}
+
+impl BenchmarkRuns {
+    pub const fn selection(self) -> RunSelection {
+        match self {
+            Self::Best => RunSelection::Best,
+            Self::Median => RunSelection::Median,
+        }
+    }
+}
+
+/// Время Benchmarks Game, доступное из командной строки.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum BenchmarkTime {
+    Elapsed,
+    Cpu,
+}
+
+impl BenchmarkTime {
+    pub const fn measure(self) -> TimeMeasure {
+        match self {
+            Self::Elapsed => TimeMeasure::Elapsed,
+            Self::Cpu => TimeMeasure::CpuTime,
+        }
+    }
+}
+
+/// Способ объединения фреймворков `TechEmpower`, доступный из командной строки.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum TechEmpowerAggregationArg {
+    Best,
+    TopK,
+    Median,
+}
+
+impl TechEmpowerAggregationArg {
+    pub const fn aggregation(self) -> TechEmpowerAggregation {
+        match self {
+            Self::Best => TechEmpowerAggregation::Best,
+            Self::TopK => TechEmpowerAggregation::TopKMean,
+            Self::Median => TechEmpowerAggregation::Median,
+        }
+    }
+}
+
+/// Класс фреймворка `TechEmpower`, доступный из командной строки.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum FrameworkClassificationArg {
+    Fullstack,
+    Micro,
+    Platform,
+}
+
+impl FrameworkClassificationArg {
+    pub const fn classification(self) -> FrameworkClassification {
+        match self {
+            Self::Fullstack => FrameworkClassification::Fullstack,
+            Self::Micro => FrameworkClassification::Micro,
+            Self::Platform => FrameworkClassification::Platform,
+        }
+    }
+}
+
+/// Подход к реализации тестов `TechEmpower`, доступный из командной строки.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum FrameworkApproachArg {
+    Realistic,
+    Stripped,
+}
+
+impl FrameworkApproachArg {
+    pub const fn approach(self) -> FrameworkApproach {
+        match self {
+            Self::Realistic => FrameworkApproach::Realistic,
+            Self::Stripped => FrameworkApproach::Stripped,
+        }
+    }
+}
+
+/// Вид доступа к базе данных в `TechEmpower`, доступный из командной строки.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum FrameworkOrmArg {
+    Full,
+    Micro,
+    Raw,
+}
+
+impl FrameworkOrmArg {
+    pub const fn orm(self) -> FrameworkOrm {
+        match self {
+            Self::Full => FrameworkOrm::Full,
+            Self::Micro => FrameworkOrm::Micro,
+            Self::Raw => FrameworkOrm::Raw,
+        }
+    }
+}
+
+fn parse_quarter(value: &str) -> Result<String, String> {
+    let quarter = value.trim().to_ascii_uppercase();
+    let valid = quarter.len() == 6
+        && quarter[..4].bytes().all(|byte| byte.is_ascii_digit())
+        && matches!(&quarter[4..], "Q1" | "Q2" | "Q3" | "Q4");
+    if valid {
+        Ok(quarter)
+    } else {
+        Err(format!("'{value}' is not a quarter like 2018Q1"))
+    }
+}
+
+fn parse_top_k(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(0) | Err(_) => Err(format!("'{value}' is not a positive integer")),
+        Ok(top_k) => Ok(top_k),
+    }
+}
+
+fn parse_share(value: &str) -> Result<f64, String> {
+    let share: f64 = value
+        .parse()
+        .map_err(|_| format!("'{value}' is not a number"))?;
+    if !(0.0..=1.0).contains(&share) {
+        return Err(format!("'{value}' is not between 0 and 1"));
+    }
+    Ok(share)
+}
+
+fn parse_task_weight(value: &str) -> Result<(String, f64), String> {
+    let (task, weight) = value
+        .split_once('=')
+        .ok_or_else(|| format!("'{value}' is not in TASK=WEIGHT form"))?;
+    let weight: f64 = weight
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{weight}' is not a number"))?;
+    if !weight.is_finite() || weight < 0.0 {
+        return Err(format!(
+            "task weight '{weight}' must be a non-negative number"
+        ));
+    }
+    Ok((task.trim().to_owned(), weight))
+}
+
+fn parse_languish_weight(value: &str) -> Result<(LanguishSignalArg, f64), String> {
+    let (signal, weight) = value
+        .split_once('=')
+        .ok_or_else(|| format!("'{value}' is not in SIGNAL=WEIGHT form"))?;
+    let signal = LanguishSignalArg::from_str(signal.trim(), true)
+        .map_err(|_| format!("'{signal}' is not a Languish signal"))?;
+    let weight: f64 = weight
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{weight}' is not a number"))?;
+    if !weight.is_finite() || weight < 0.0 {
+        return Err(format!(
+            "signal weight '{weight}' must be a non-negative number"
+        ));
+    }
+    Ok((signal, weight))
+}
+
+/// Сигнал Languish, доступный из командной строки.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum LanguishSignalArg {
+    Issues,
+    Pulls,
+    SoQuestions,
+    Stars,
+}
+
+/// Дополнительный показатель Benchmarks Game, доступный из командной строки.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum ExtraBenchmarkMetric {
+    CpuTime,
+    Memory,
+    SourceSize,
+    Reliability,
+}
+
+impl ExtraBenchmarkMetric {
+    pub const fn metric(self) -> BenchmarkMetric {
+        match self {
+            Self::CpuTime => BenchmarkMetric::CpuTime,
+            Self::Memory => BenchmarkMetric::Memory,
+            Self::SourceSize => BenchmarkMetric::SourceSize,
+            Self::Reliability => BenchmarkMetric::Reliability,
+        }
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Commands {
     /// Generate shell completion scripts, optionally installing them for the current user.
@@ -93,19 +526,48 @@ pub enum Commands {
         )]
         install: bool,
     },
+    /// Show the canonical name for a language and which sources contain it.
+    Alias {
+        #[arg(help = "Language name as spelled by any source, e.g. node.js or gpp.")]
+        name: String,
+    },
+    /// List the available TechEmpower rounds, or compare language scores across rounds.
+    #[allow(clippy::doc_markdown)]
+    TeRounds {
+        #[arg(
+            long,
+            value_name = "ROUNDS",
+            value_delimiter = ',',
+            num_args = 1..,
+            help = "Score the listed rounds (at least two, e.g. 21,22,23) and show how each language changed from the first to the last."
+        )]
+        compare: Vec<u16>,
+    },
+    /// Rebuild a popularity-only Schulze ranking for every past quarter from the Languish history
+    /// and save one CSV per quarter.
+    Backfill {
+        #[arg(
+            long,
+            value_name = "DIR",
+            default_value = DEFAULT_BACKFILL_DIR,
+            help = "Directory for the per-quarter CSV files (schulze_<quarter>.csv)."
+        )]
+        output_dir: PathBuf,
+        #[arg(
+            long,
+            value_name = "QUARTER",
+            value_parser = parse_quarter,
+            help = "Skip quarters before the given one, e.g. 2018Q1."
+        )]
+        since: Option<String>,
+    },
 }
 
-pub fn handle_command(command: Commands) -> Result<()> {
-    match command {
-        Commands::Completions {
-            shell,
-            output_dir,
-            install,
-        } => generate_completions(shell, output_dir, install),
-    }
-}
-
-fn generate_completions(shell: Shell, output_dir: Option<PathBuf>, install: bool) -> Result<()> {
+pub fn generate_completions(
+    shell: Shell,
+    output_dir: Option<PathBuf>,
+    install: bool,
+) -> Result<()> {
     let mut command = Cli::command();
     let bin_name = command.get_name().to_string();
 
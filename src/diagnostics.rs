@@ -0,0 +1,253 @@
+use crate::aliases::normalize_alias_key;
+use crate::{AliasRegistry, DataSource, Language, PerformanceSource, SourceBundle};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Отчёт о названиях языков, которые могли не сопоставиться между
+/// источниками.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct NameReport {
+    issues: Vec<NameIssue>,
+}
+
+/// Подозрительное название языка.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct NameIssue {
+    /// Язык после применения синонимов.
+    pub name: Language,
+    /// Источники, в которых встречается имя.
+    pub sources: Vec<DataSource>,
+    /// Имя встречается только в одном источнике из нескольких загруженных.
+    pub single_source: bool,
+    /// Имя не известно реестру синонимов и взято из источника как есть.
+    pub unmapped: bool,
+    /// Похожие имена из других источников, начиная с самых близких.
+    pub suggestions: Vec<NameSuggestion>,
+}
+
+/// Вероятное соответствие подозрительному имени.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct NameSuggestion {
+    /// Язык из другого источника.
+    pub name: Language,
+    /// Источники, в которых встречается это имя.
+    pub sources: Vec<DataSource>,
+    /// Расстояние Левенштейна без учёта регистра и пробелов; для составных
+    /// имён вида `Delphi/Pascal` учитывается и каждая часть отдельно.
+    pub distance: usize,
+}
+
+impl NameReport {
+    /// Возвращает подозрительные имена в алфавитном порядке.
+    #[must_use]
+    pub fn issues(&self) -> &[NameIssue] {
+        &self.issues
+    }
+
+    /// Возвращает имена, для которых найдены похожие варианты, то есть
+    /// вероятные дубликаты.
+    pub fn likely_duplicates(&self) -> impl Iterator<Item = &NameIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| !issue.suggestions.is_empty())
+    }
+
+    /// Проверяет, что подозрительных имён нет.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Находит имена, которые встречаются только в одном источнике или не
+/// известны реестру синонимов, и подбирает для них похожие имена из других
+/// источников.
+#[must_use]
+pub fn diagnose_language_names(bundle: &SourceBundle, aliases: &AliasRegistry) -> NameReport {
+    let sources_by_name = collect_sources_by_name(bundle);
+    let source_count = bundle.rankings().len()
+        + usize::from(!bundle.benchmarks().is_empty())
+        + usize::from(!bundle.techempower().is_empty());
+
+    let mut issues = Vec::new();
+    for (&name, sources) in &sources_by_name {
+        let single_source = source_count > 1 && sources.len() == 1;
+        let unmapped = !aliases.is_mapped(name.name());
+        if !single_source && !unmapped {
+            continue;
+        }
+        issues.push(NameIssue {
+            name,
+            sources: sources.clone(),
+            single_source,
+            unmapped,
+            suggestions: suggest_names(name, sources, &sources_by_name),
+        });
+    }
+    NameReport { issues }
+}
+
+fn collect_sources_by_name(bundle: &SourceBundle) -> BTreeMap<Language, Vec<DataSource>> {
+    let mut sources_by_name: BTreeMap<Language, Vec<DataSource>> = BTreeMap::new();
+    for dataset in bundle.rankings() {
+        for entry in dataset {
+            sources_by_name
+                .entry(entry.lang)
+                .or_default()
+                .push(dataset.source().into());
+        }
+    }
+    let performance = [
+        (
+            PerformanceSource::BenchmarksGame,
+            bundle.benchmarks().scores(),
+        ),
+        (
+            PerformanceSource::TechEmpower,
+            bundle.techempower().scores(),
+        ),
+    ];
+    for (source, scores) in performance {
+        for name in scores.keys() {
+            sources_by_name
+                .entry(*name)
+                .or_default()
+                .push(source.into());
+        }
+    }
+    for sources in sources_by_name.values_mut() {
+        sources.dedup();
+    }
+    sources_by_name
+}
+
+fn suggest_names(
+    name: Language,
+    sources: &[DataSource],
+    sources_by_name: &BTreeMap<Language, Vec<DataSource>>,
+) -> Vec<NameSuggestion> {
+    let mut suggestions: Vec<NameSuggestion> = sources_by_name
+        .iter()
+        .filter(|(other, other_sources)| {
+            **other != name && other_sources.iter().any(|source| !sources.contains(source))
+        })
+        .filter_map(|(&other, other_sources)| {
+            let distance = name_distance(name.name(), other.name());
+            (distance <= max_distance(name.name(), other.name())).then(|| NameSuggestion {
+                name: other,
+                sources: other_sources.clone(),
+                distance,
+            })
+        })
+        .collect();
+    suggestions.sort_by(|a, b| {
+        a.distance
+            .cmp(&b.distance)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    suggestions.truncate(MAX_SUGGESTIONS);
+    suggestions
+}
+
+fn name_distance(left: &str, right: &str) -> usize {
+    let left = normalize_alias_key(left);
+    let right = normalize_alias_key(right);
+    let mut best = levenshtein(&left, &right);
+    for left_part in left.split('/') {
+        for right_part in right.split('/') {
+            if !left_part.is_empty() && !right_part.is_empty() {
+                best = best.min(levenshtein(left_part, right_part));
+            }
+        }
+    }
+    best
+}
+
+// Короткие имена вроде `C`, `D` или `Go` различаются одной буквой, поэтому для
+// них допускается только совпадение без учёта регистра.
+fn max_distance(left: &str, right: &str) -> usize {
+    match left.chars().count().min(right.chars().count()) {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+fn levenshtein(left: &str, right: &str) -> usize {
+    let right: Vec<char> = right.chars().collect();
+    let mut previous: Vec<usize> = (0..=right.len()).collect();
+    let mut current = vec![0; right.len() + 1];
+    for (i, left_char) in left.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, right_char) in right.iter().enumerate() {
+            let substitution = previous[j] + usize::from(left_char != *right_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[right.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diagnose_language_names, levenshtein, name_distance};
+    use crate::{
+        AliasRegistry, BenchmarkScores, DataSource, Language, RankingDataset, RankingEntry,
+        RankingSource, SourceBundle, TechEmpowerScores,
+    };
+
+    fn dataset(source: RankingSource, names: &[&str]) -> RankingDataset {
+        let entries = names
+            .iter()
+            .map(|name| RankingEntry {
+                lang: Language::intern(name),
+                rank: None,
+                share: Some(1.0),
+                previous_rank: None,
+                trend: None,
+            })
+            .collect();
+        RankingDataset::new(source, entries)
+    }
+
+    #[test]
+    fn measures_distance_case_insensitively_and_by_name_parts() {
+        assert_eq!(levenshtein("kotlin", "kotlinn"), 1);
+        assert_eq!(name_distance("OCaml", "Ocaml"), 0);
+        assert_eq!(name_distance("Delphi", "Delphi/Pascal"), 0);
+    }
+
+    #[test]
+    fn reports_single_source_and_unmapped_names_with_suggestions() {
+        let bundle = SourceBundle::new(
+            vec![
+                dataset(RankingSource::Tiobe, &["Rust", "Delphi/Pascal", "Gleam"]),
+                dataset(RankingSource::Pypl, &["Rust", "Delphi"]),
+            ],
+            BenchmarkScores::default(),
+            TechEmpowerScores::default(),
+        );
+
+        let report = diagnose_language_names(&bundle, &AliasRegistry::builtin());
+        let names: Vec<_> = report
+            .issues()
+            .iter()
+            .map(|issue| issue.name.name())
+            .collect();
+        assert_eq!(names, ["Delphi", "Delphi/Pascal", "Gleam"]);
+
+        let delphi = &report.issues()[0];
+        assert!(delphi.single_source);
+        assert!(delphi.unmapped);
+        assert_eq!(delphi.sources, [DataSource::from(RankingSource::Pypl)]);
+        assert_eq!(delphi.suggestions[0].name, "Delphi/Pascal");
+
+        let gleam = &report.issues()[2];
+        assert!(gleam.suggestions.is_empty());
+        assert_eq!(report.likely_duplicates().count(), 2);
+    }
+}
@@ -1,4 +1,6 @@
-use crate::RankingSource;
+use crate::{DataSource, PerformanceSource, RankingSource};
+use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Ошибка высокоуровневой загрузки рейтингов.
@@ -31,9 +33,210 @@ pub enum FetchError {
         /// Минимальное ожидаемое количество записей.
         minimum: usize,
     },
+
+    /// Не удалось загрузить или разобрать показатели производительности.
+    #[error("failed to fetch {performance_source}")]
+    Performance {
+        /// Источник показателей, при работе с которым произошла ошибка.
+        performance_source: PerformanceSource,
+        /// Исходная ошибка с полной цепочкой причин.
+        #[source]
+        error: anyhow::Error,
+    },
+
+    /// Источник производительности вернул показатели для слишком малого числа языков.
+    #[error(
+        "{performance_source} returned {actual} languages (expected at least {minimum}); the source format may have changed"
+    )]
+    TooFewScores {
+        /// Источник с подозрительно коротким ответом.
+        performance_source: PerformanceSource,
+        /// Фактическое количество языков.
+        actual: usize,
+        /// Минимальное ожидаемое количество языков.
+        minimum: usize,
+    },
+
+    /// Для запрошенного источника не зарегистрирован провайдер.
+    #[error("no ranking provider is registered for {ranking_source}")]
+    UnknownSource {
+        /// Запрошенный источник.
+        ranking_source: RankingSource,
+    },
+}
+
+/// Типизированная причина сбоя встроенного источника.
+///
+/// Доступна через [`FetchError::source_error`] и позволяет отличить
+/// недоступность сайта от изменения формата данных.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SourceError {
+    /// HTTP-запрос не удался после всех попыток.
+    #[error("failed to fetch {url} after {attempts} attempts: {detail}")]
+    Http {
+        /// Запрошенный адрес.
+        url: String,
+        /// HTTP-статус последней попытки; `None` при сетевой ошибке или тайм-ауте.
+        status: Option<u16>,
+        /// Количество выполненных попыток.
+        attempts: u32,
+        /// Описание последней ошибки.
+        detail: String,
+    },
+
+    /// Источник не уложился в общий тайм-аут загрузки.
+    #[error("{data_source} did not finish within {limit:?}")]
+    Timeout {
+        /// Источник, загрузка которого прервана.
+        data_source: DataSource,
+        /// Тайм-аут источника.
+        limit: Duration,
+    },
+
+    /// В разметке не найден ожидаемый элемент, селектор или маркер.
+    #[error("{element} not found in {document}")]
+    MarkupNotFound {
+        /// Документ, в котором выполнялся поиск.
+        document: &'static str,
+        /// Искомый элемент.
+        element: &'static str,
+    },
+
+    /// Маркеры начала и конца фрагмента найдены в обратном порядке.
+    #[error("{end} precedes {start} in {document}")]
+    MarkersOutOfOrder {
+        /// Документ, в котором выполнялся поиск.
+        document: &'static str,
+        /// Маркер начала фрагмента.
+        start: &'static str,
+        /// Маркер конца фрагмента.
+        end: &'static str,
+    },
+
+    /// Полезная нагрузка не разбирается в ожидаемом формате.
+    #[error("failed to decode {payload}")]
+    Decode {
+        /// Разбираемые данные.
+        payload: &'static str,
+        /// Ошибка декодера.
+        #[source]
+        error: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// В данных нет ожидаемой колонки или ключа.
+    #[error("missing {field} in {payload}")]
+    MissingField {
+        /// Разбираемые данные.
+        payload: &'static str,
+        /// Имя колонки или ключа.
+        field: String,
+    },
+}
+
+impl SourceError {
+    /// Проверяет, что сбой вызван недоступностью сайта, а не форматом данных.
+    #[must_use]
+    pub const fn is_transport(&self) -> bool {
+        matches!(self, Self::Http { .. } | Self::Timeout { .. })
+    }
+
+    pub(crate) fn decode(
+        payload: &'static str,
+        error: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        Self::Decode {
+            payload,
+            error: error.into(),
+        }
+    }
+
+    pub(crate) fn missing_field(payload: &'static str, field: impl Into<String>) -> Self {
+        Self::MissingField {
+            payload,
+            field: field.into(),
+        }
+    }
+}
+
+/// Ошибка загрузки файла синонимов языков.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum AliasError {
+    /// Не удалось прочитать файл.
+    #[error("failed to read alias file {}", path.display())]
+    Read {
+        /// Путь к файлу синонимов.
+        path: PathBuf,
+        /// Ошибка ввода-вывода.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// Файл не соответствует формату TOML.
+    #[error("failed to parse TOML alias file")]
+    Toml(#[source] toml::de::Error),
+
+    /// Файл не соответствует формату JSON.
+    #[error("failed to parse JSON alias file")]
+    Json(#[source] serde_json::Error),
+}
+
+/// Ошибка расчёта ранжирования по методу Шульце.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SchulzeError {
+    /// После фильтрации по пересечению источников осталось меньше двух языков.
+    #[error("Not enough overlapping languages ({actual}) to compute Schulze ranking")]
+    NotEnoughCandidates {
+        /// Количество оставшихся языков.
+        actual: usize,
+    },
 }
 
 impl FetchError {
+    /// Возвращает имя источника, к которому относится ошибка.
+    #[must_use]
+    pub const fn source_name(&self) -> Option<&'static str> {
+        match self {
+            Self::ClientBuild(_) => None,
+            Self::Source { ranking_source, .. }
+            | Self::TooFewEntries { ranking_source, .. }
+            | Self::UnknownSource { ranking_source } => Some(ranking_source.as_str()),
+            Self::Performance {
+                performance_source, ..
+            }
+            | Self::TooFewScores {
+                performance_source, ..
+            } => Some(performance_source.as_str()),
+        }
+    }
+
+    /// Возвращает типизированную причину сбоя загрузки или разбора, если она
+    /// известна.
+    ///
+    /// Для проверок правдоподобия и ошибок пользовательских провайдеров, не
+    /// использующих [`SourceError`], возвращает `None`.
+    #[must_use]
+    pub fn source_error(&self) -> Option<&SourceError> {
+        match self {
+            Self::Source { error, .. } | Self::Performance { error, .. } => error.downcast_ref(),
+            _ => None,
+        }
+    }
+
+    /// Проверяет, что ошибка похожа на изменение формата источника: данные
+    /// получены, но не разобраны или подозрительно коротки.
+    #[must_use]
+    pub fn is_format_change(&self) -> bool {
+        match self {
+            Self::TooFewEntries { .. } | Self::TooFewScores { .. } => true,
+            _ => self
+                .source_error()
+                .is_some_and(|error| !error.is_transport()),
+        }
+    }
+
     pub(crate) const fn source_failure(
         ranking_source: RankingSource,
         error: anyhow::Error,
@@ -43,4 +246,14 @@ impl FetchError {
             error,
         }
     }
+
+    pub(crate) const fn performance_failure(
+        performance_source: PerformanceSource,
+        error: anyhow::Error,
+    ) -> Self {
+        Self::Performance {
+            performance_source,
+            error,
+        }
+    }
 }
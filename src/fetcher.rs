@@ -1,20 +1,54 @@
+mod builder;
+
+pub use builder::FetcherBuilder;
+
+use crate::aliases::builtin as builtin_aliases;
+use crate::cache::PublicationPeriod;
+use crate::ranking::reconcile_datasets;
+use crate::sources::{
+    HttpContext, RetryPolicy, SourceUrls, canonicalize_entries, discover_techempower_rounds_with,
+    download_benchmark_data_with, fetch_languish_history_with, fetch_languish_with,
+    fetch_pypl_with, fetch_techempower_with, fetch_tiobe_history_with, fetch_tiobe_with,
+    load_benchmark_results_with, with_static_rounds,
+};
 use crate::{
-    FetchError, RankingDataset, RankingEntry, RankingSource, fetch_languish, fetch_pypl,
-    fetch_tiobe, reconcile_pypl_with_tiobe,
+    AliasRegistry, BenchmarkScores, BenchmarkScoring, DataSource, FetchError, HttpCache,
+    LanguishHistory, LanguishScoring, PartialSourceBundle, PerformanceSource, RankingDataset,
+    RankingEntry, RankingProvider, RankingSource, SourceBundle, SourceError, TechEmpowerScores,
+    TechEmpowerScoring, TiobeHistory, Transport,
 };
 use reqwest::Client;
+use rustc_hash::FxHashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
 use std::time::Duration;
 
-const HTTP_TIMEOUT: Duration = Duration::from_secs(20);
-const USER_AGENT: &str = "lang-rank-fetcher/0.1";
-
 /// Минимальное правдоподобное количество записей рейтингового источника.
 pub const MIN_RANKING_ENTRIES: usize = 10;
+/// Минимальное правдоподобное количество языков в Benchmarks Game.
+pub const MIN_BENCHMARK_LANGUAGES: usize = 10;
+/// Минимальное правдоподобное количество языков в `TechEmpower`.
+pub const MIN_TECHEMPOWER_LANGUAGES: usize = 10;
 
-/// Клиент для загрузки рейтингов популярности.
-#[derive(Debug, Clone)]
+/// Клиент для загрузки рейтингов популярности и показателей производительности.
+///
+/// Повторы запросов, тайм-ауты и User-Agent настраиваются через
+/// [`Fetcher::builder`].
+#[derive(Clone)]
 pub struct Fetcher {
-    client: Client,
+    transport: Arc<dyn Transport>,
+    client: Option<Client>,
+    providers: Vec<Arc<dyn RankingProvider>>,
+    policy: RetryPolicy,
+    source_timeouts: FxHashMap<DataSource, Duration>,
+    cache: Option<HttpCache>,
+    urls: SourceUrls,
+    aliases: Arc<AliasRegistry>,
+    benchmark_scoring: Arc<BenchmarkScoring>,
+    techempower_scoring: Arc<TechEmpowerScoring>,
+    techempower_round: Option<u16>,
+    languish_scoring: LanguishScoring,
 }
 
 impl Fetcher {
@@ -24,24 +58,99 @@ impl Fetcher {
     ///
     /// Возвращает ошибку, если не удалось создать HTTP-клиент.
     pub fn new() -> Result<Self, FetchError> {
-        let client = Client::builder()
-            .user_agent(USER_AGENT)
-            .timeout(HTTP_TIMEOUT)
-            .build()
-            .map_err(FetchError::ClientBuild)?;
-        Ok(Self { client })
+        FetcherBuilder::new().build()
+    }
+
+    /// Возвращает построитель загрузчика с настройками по умолчанию.
+    #[must_use]
+    pub fn builder() -> FetcherBuilder {
+        FetcherBuilder::new()
     }
 
     /// Создаёт загрузчик поверх пользовательского HTTP-клиента.
     #[must_use]
-    pub const fn from_client(client: Client) -> Self {
-        Self { client }
+    pub fn from_client(client: Client) -> Self {
+        Self {
+            client: Some(client.clone()),
+            ..Self::from_transport(client)
+        }
     }
 
-    /// Возвращает HTTP-клиент для низкоуровневых функций загрузки.
+    /// Создаёт загрузчик поверх пользовательского транспорта.
+    #[must_use]
+    pub fn from_transport(transport: impl Transport + 'static) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            client: None,
+            providers: Vec::new(),
+            policy: RetryPolicy::DEFAULT,
+            source_timeouts: FxHashMap::default(),
+            cache: None,
+            urls: SourceUrls::default(),
+            aliases: Arc::clone(builtin_aliases()),
+            benchmark_scoring: Arc::default(),
+            techempower_scoring: Arc::default(),
+            techempower_round: None,
+            languish_scoring: LanguishScoring::default(),
+        }
+    }
+
+    /// Регистрирует пользовательский источник рейтинга.
+    ///
+    /// Провайдер с уже зарегистрированным идентификатором заменяет прежний.
+    #[must_use]
+    pub fn with_provider(mut self, provider: impl RankingProvider + 'static) -> Self {
+        let id = provider.id();
+        self.providers.retain(|existing| existing.id() != id);
+        self.providers.push(Arc::new(provider));
+        self
+    }
+
+    /// Возвращает источники, зарегистрированные через [`Self::with_provider`],
+    /// в порядке регистрации.
+    pub fn custom_sources(&self) -> impl Iterator<Item = RankingSource> + '_ {
+        self.providers
+            .iter()
+            .map(|provider| RankingSource::Custom(provider.id()))
+    }
+
+    /// Возвращает HTTP-клиент, через который загрузчик выполняет запросы.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если загрузчик создан поверх пользовательского транспорта, а
+    /// не HTTP-клиента.
+    #[deprecated(note = "use `Fetcher::transport`, which also covers custom transports")]
     #[must_use]
     pub const fn client(&self) -> &Client {
-        &self.client
+        self.client
+            .as_ref()
+            .expect("Fetcher::client requires a fetcher built on a reqwest client")
+    }
+
+    /// Возвращает транспорт для низкоуровневых функций загрузки.
+    #[must_use]
+    pub fn transport(&self) -> &dyn Transport {
+        self.transport.as_ref()
+    }
+
+    /// Возвращает разделяемый транспорт, например чтобы обернуть его в
+    /// [`RecordingTransport`](crate::RecordingTransport).
+    #[must_use]
+    pub fn shared_transport(&self) -> Arc<dyn Transport> {
+        Arc::clone(&self.transport)
+    }
+
+    /// Возвращает реестр синонимов, которым нормализуются названия языков.
+    #[must_use]
+    pub fn aliases(&self) -> &AliasRegistry {
+        &self.aliases
+    }
+
+    /// Возвращает стратегию Languish из [`FetcherBuilder::languish_scoring`].
+    #[must_use]
+    pub const fn languish_scoring(&self) -> LanguishScoring {
+        self.languish_scoring
     }
 
     /// Загружает один рейтинг без межисточниковых преобразований.
@@ -51,9 +160,24 @@ impl Fetcher {
     /// Возвращает ошибку при сбое HTTP-запроса, разбора ответа или если
     /// источник вернул подозрительно мало записей.
     pub async fn fetch(&self, source: RankingSource) -> Result<RankingDataset, FetchError> {
-        let entries = fetch_source(&self.client, source).await?;
-        ensure_min_entries(source, &entries)?;
-        Ok(RankingDataset::new(source, entries))
+        let dataset = self.fetch_source(source).await?;
+        ensure_min_entries(source, dataset.entries())?;
+        Ok(dataset)
+    }
+
+    /// Последовательно загружает все пользовательские источники.
+    ///
+    /// Наборы возвращаются в порядке регистрации провайдеров.
+    ///
+    /// # Errors
+    ///
+    /// Возвращает первую ошибку загрузки или проверки количества записей.
+    pub async fn fetch_custom(&self) -> Result<Vec<RankingDataset>, FetchError> {
+        let mut datasets = Vec::with_capacity(self.providers.len());
+        for source in self.custom_sources() {
+            datasets.push(self.fetch(source).await?);
+        }
+        Ok(datasets)
     }
 
     /// Параллельно загружает TIOBE, PYPL и Languish.
@@ -69,35 +193,272 @@ impl Fetcher {
     /// Возвращает ошибку при сбое загрузки, разбора ответа или если один из
     /// источников вернул подозрительно мало записей.
     pub async fn fetch_rankings(&self) -> Result<[RankingDataset; 3], FetchError> {
-        let (tiobe, mut pypl, languish) = tokio::try_join!(
-            fetch_source(&self.client, RankingSource::Tiobe),
-            fetch_source(&self.client, RankingSource::Pypl),
-            fetch_source(&self.client, RankingSource::Languish),
+        let mut rankings: [RankingDataset; 3] = tokio::try_join!(
+            self.fetch(RankingSource::Tiobe),
+            self.fetch(RankingSource::Pypl),
+            self.fetch(RankingSource::Languish),
+        )?
+        .into();
+        reconcile_datasets(&mut rankings);
+        Ok(rankings)
+    }
+
+    /// Загружает долгосрочную историю мест TIOBE и «Зал славы».
+    ///
+    /// # Errors
+    ///
+    /// Возвращает ошибку при сбое загрузки или если на странице нет таблицы
+    /// долгосрочной истории.
+    pub async fn fetch_tiobe_history(&self) -> Result<TiobeHistory, FetchError> {
+        let source = RankingSource::Tiobe;
+        let http = self.http().with_period(PublicationPeriod::Monthly);
+        self.within_deadline(source.into(), fetch_tiobe_history_with(http, &self.aliases))
+            .await
+            .map_err(|error| FetchError::source_failure(source, error))
+    }
+
+    /// Загружает поквартальную историю Languish с весами сигналов из
+    /// [`FetcherBuilder::languish_scoring`].
+    ///
+    /// # Errors
+    ///
+    /// Возвращает ошибку при сбое загрузки или несовместимом формате данных.
+    pub async fn fetch_languish_history(&self) -> Result<LanguishHistory, FetchError> {
+        let source = RankingSource::Languish;
+        let http = self.http().with_period(PublicationPeriod::Quarterly);
+        self.within_deadline(
+            source.into(),
+            fetch_languish_history_with(http, &self.aliases, self.languish_scoring.weights),
+        )
+        .await
+        .map_err(|error| FetchError::source_failure(source, error))
+    }
+
+    /// Загружает CSV Benchmarks Game и вычисляет показатели языков.
+    ///
+    /// # Errors
+    ///
+    /// Возвращает ошибку при сбое загрузки, разбора CSV или если показатели
+    /// получены для подозрительно малого числа языков.
+    pub async fn fetch_benchmarks(&self) -> Result<BenchmarkScores, FetchError> {
+        self.fetch_benchmarks_with_csv()
+            .await
+            .map(|(scores, _)| scores)
+    }
+
+    /// Загружает CSV Benchmarks Game и возвращает показатели языков вместе с
+    /// исходным CSV, например чтобы сохранить его.
+    ///
+    /// # Errors
+    ///
+    /// Возвращает те же ошибки, что и [`Self::fetch_benchmarks`].
+    pub async fn fetch_benchmarks_with_csv(
+        &self,
+    ) -> Result<(BenchmarkScores, Vec<u8>), FetchError> {
+        let source = PerformanceSource::BenchmarksGame;
+        let download = async {
+            let raw_csv = download_benchmark_data_with(self.http()).await?;
+            load_benchmark_results_with(
+                raw_csv,
+                Arc::clone(&self.aliases),
+                Arc::clone(&self.benchmark_scoring),
+            )
+            .await
+        };
+        let (results, raw_csv) = self
+            .within_deadline(source.into(), download)
+            .await
+            .map_err(|error| FetchError::performance_failure(source, error))?;
+        ensure_min_scores(source, results.len(), MIN_BENCHMARK_LANGUAGES)?;
+        Ok((BenchmarkScores::from_results(results), raw_csv))
+    }
+
+    /// Загружает раунд `TechEmpower`, заданный
+    /// [`FetcherBuilder::techempower_round`], или последний доступный, и
+    /// вычисляет показатели языков. Использованный раунд возвращает
+    /// [`TechEmpowerResults::round`](crate::TechEmpowerResults::round).
+    ///
+    /// # Errors
+    ///
+    /// Возвращает ошибку при сбое загрузки, разбора результатов или если
+    /// показатели получены для подозрительно малого числа языков.
+    pub async fn fetch_techempower(&self) -> Result<TechEmpowerScores, FetchError> {
+        self.fetch_techempower_from(self.techempower_round).await
+    }
+
+    /// Загружает указанный раунд `TechEmpower` и вычисляет показатели языков.
+    ///
+    /// # Errors
+    ///
+    /// Возвращает ошибку, если раунд не поддерживается, при сбое загрузки,
+    /// разбора результатов или если показатели получены для подозрительно
+    /// малого числа языков.
+    pub async fn fetch_techempower_round(
+        &self,
+        round: u16,
+    ) -> Result<TechEmpowerScores, FetchError> {
+        self.fetch_techempower_from(Some(round)).await
+    }
+
+    /// Возвращает поддерживаемые раунды `TechEmpower` от нового к старому.
+    /// Если страницу бенчмарков не удалось загрузить или разобрать за
+    /// отведённое время, возвращаются встроенные известные раунды.
+    pub async fn list_techempower_rounds(&self) -> Vec<u16> {
+        let source = PerformanceSource::TechEmpower;
+        let discovered = self
+            .within_deadline(source.into(), discover_techempower_rounds_with(self.http()))
+            .await;
+        with_static_rounds(discovered)
+    }
+
+    async fn fetch_techempower_from(
+        &self,
+        round: Option<u16>,
+    ) -> Result<TechEmpowerScores, FetchError> {
+        let source = PerformanceSource::TechEmpower;
+        let results = self
+            .within_deadline(
+                source.into(),
+                fetch_techempower_with(
+                    self.http(),
+                    &self.aliases,
+                    &self.techempower_scoring,
+                    round,
+                ),
+            )
+            .await
+            .map_err(|error| FetchError::performance_failure(source, error))?;
+        ensure_min_scores(source, results.len(), MIN_TECHEMPOWER_LANGUAGES)?;
+        Ok(TechEmpowerScores::from_results(results))
+    }
+
+    /// Параллельно загружает все входные данные ранжирования: TIOBE, PYPL,
+    /// Languish, Benchmarks Game, `TechEmpower` и пользовательские источники.
+    ///
+    /// Каждый источник проверяется так же, как в [`Self::fetch`],
+    /// [`Self::fetch_benchmarks`] и [`Self::fetch_techempower`].
+    ///
+    /// # Errors
+    ///
+    /// Возвращает первую ошибку загрузки или проверки любого источника.
+    pub async fn fetch_all(&self) -> Result<SourceBundle, FetchError> {
+        let (tiobe, pypl, languish, benchmarks, techempower, custom) = tokio::try_join!(
+            self.fetch(RankingSource::Tiobe),
+            self.fetch(RankingSource::Pypl),
+            self.fetch(RankingSource::Languish),
+            self.fetch_benchmarks_with_csv(),
+            self.fetch_techempower(),
+            self.fetch_custom(),
         )?;
 
-        ensure_min_entries(RankingSource::Tiobe, &tiobe)?;
-        ensure_min_entries(RankingSource::Pypl, &pypl)?;
-        ensure_min_entries(RankingSource::Languish, &languish)?;
-        reconcile_pypl_with_tiobe(&tiobe, &mut pypl);
+        let mut rankings = vec![tiobe, pypl, languish];
+        rankings.extend(custom);
+        let (benchmarks, benchmark_csv) = benchmarks;
+        Ok(SourceBundle::new(rankings, benchmarks, techempower).with_benchmark_csv(benchmark_csv))
+    }
+
+    /// Загружает все входные данные ранжирования, не прерываясь на ошибке
+    /// отдельного источника.
+    ///
+    /// В отличие от [`Self::fetch_all`], каждый источник загружается до
+    /// конца, а ошибки собираются в [`PartialSourceBundle::failures`].
+    pub async fn fetch_all_partial(&self) -> PartialSourceBundle {
+        let (tiobe, pypl, languish, benchmarks, techempower, custom) = tokio::join!(
+            self.fetch(RankingSource::Tiobe),
+            self.fetch(RankingSource::Pypl),
+            self.fetch(RankingSource::Languish),
+            self.fetch_benchmarks_with_csv(),
+            self.fetch_techempower(),
+            self.fetch_custom_each(),
+        );
+
+        let mut rankings = vec![tiobe, pypl, languish];
+        rankings.extend(custom);
+        let (benchmarks, benchmark_csv) = match benchmarks {
+            Ok((scores, csv)) => (Ok(scores), Some(csv)),
+            Err(error) => (Err(error), None),
+        };
+        PartialSourceBundle::new(rankings, benchmarks, techempower)
+            .with_benchmark_csv(benchmark_csv)
+    }
 
-        Ok([
-            RankingDataset::new(RankingSource::Tiobe, tiobe),
-            RankingDataset::new(RankingSource::Pypl, pypl),
-            RankingDataset::new(RankingSource::Languish, languish),
-        ])
+    async fn fetch_custom_each(&self) -> Vec<Result<RankingDataset, FetchError>> {
+        let mut results = Vec::with_capacity(self.providers.len());
+        for source in self.custom_sources() {
+            results.push(self.fetch(source).await);
+        }
+        results
+    }
+
+    async fn fetch_source(&self, source: RankingSource) -> Result<RankingDataset, FetchError> {
+        let result = match source {
+            RankingSource::Tiobe => {
+                let http = self.http().with_period(PublicationPeriod::Monthly);
+                self.within_deadline(source.into(), fetch_tiobe_with(http, &self.aliases))
+                    .await
+            }
+            RankingSource::Pypl => {
+                let http = self.http().with_period(PublicationPeriod::Monthly);
+                self.within_deadline(source.into(), fetch_pypl_with(http, &self.aliases))
+                    .await
+                    .map(|entries| RankingDataset::new(source, entries))
+            }
+            RankingSource::Languish => {
+                let http = self.http().with_period(PublicationPeriod::Quarterly);
+                self.within_deadline(
+                    source.into(),
+                    fetch_languish_with(http, &self.aliases, self.languish_scoring),
+                )
+                .await
+            }
+            RankingSource::Custom(id) => {
+                let provider = self
+                    .providers
+                    .iter()
+                    .find(|provider| provider.id() == id)
+                    .ok_or(FetchError::UnknownSource {
+                        ranking_source: source,
+                    })?;
+                self.within_deadline(source.into(), provider.fetch(self.transport()))
+                    .await
+                    .map(|entries| {
+                        RankingDataset::new(source, canonicalize_entries(entries, &self.aliases))
+                    })
+            }
+        };
+        result.map_err(|error| FetchError::source_failure(source, error))
+    }
+
+    fn http(&self) -> HttpContext<'_> {
+        HttpContext::new(self.transport.as_ref(), &self.policy)
+            .with_cache(self.cache.as_ref())
+            .with_urls(&self.urls)
+    }
+
+    async fn within_deadline<T>(
+        &self,
+        source: DataSource,
+        fetch: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        let Some(&limit) = self.source_timeouts.get(&source) else {
+            return fetch.await;
+        };
+        tokio::time::timeout(limit, fetch)
+            .await
+            .unwrap_or_else(|_| {
+                Err(SourceError::Timeout {
+                    data_source: source,
+                    limit,
+                }
+                .into())
+            })
     }
 }
 
-async fn fetch_source(
-    client: &Client,
-    source: RankingSource,
-) -> Result<Vec<RankingEntry>, FetchError> {
-    let result = match source {
-        RankingSource::Tiobe => fetch_tiobe(client).await,
-        RankingSource::Pypl => fetch_pypl(client).await,
-        RankingSource::Languish => fetch_languish(client).await,
-    };
-    result.map_err(|error| FetchError::source_failure(source, error))
+impl fmt::Debug for Fetcher {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_struct("Fetcher").finish_non_exhaustive()
+    }
 }
 
 const fn ensure_min_entries(
@@ -114,10 +475,27 @@ const fn ensure_min_entries(
     Ok(())
 }
 
+const fn ensure_min_scores(
+    source: PerformanceSource,
+    actual: usize,
+    minimum: usize,
+) -> Result<(), FetchError> {
+    if actual < minimum {
+        return Err(FetchError::TooFewScores {
+            performance_source: source,
+            actual,
+            minimum,
+        });
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{MIN_RANKING_ENTRIES, ensure_min_entries};
-    use crate::{FetchError, RankingSource};
+    use super::{
+        MIN_RANKING_ENTRIES, MIN_TECHEMPOWER_LANGUAGES, ensure_min_entries, ensure_min_scores,
+    };
+    use crate::{FetchError, PerformanceSource, RankingSource};
 
     #[test]
     fn reports_source_and_counts_for_short_dataset() {
@@ -133,4 +511,15 @@ mod tests {
             }
         ));
     }
+
+    #[test]
+    fn reports_performance_source_for_short_score_set() {
+        let error = ensure_min_scores(PerformanceSource::TechEmpower, 3, MIN_TECHEMPOWER_LANGUAGES)
+            .expect_err("short score set should be rejected");
+
+        assert_eq!(
+            error.to_string(),
+            "techempower returned 3 languages (expected at least 10); the source format may have changed"
+        );
+    }
 }
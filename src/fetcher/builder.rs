@@ -0,0 +1,271 @@
+use super::Fetcher;
+use crate::aliases::builtin as builtin_aliases;
+use crate::sources::{RetryPolicy, SourceUrls};
+use crate::{
+    AliasRegistry, BenchmarkScoring, DataSource, FetchError, HttpCache, LanguishScoring,
+    RankingProvider, TechEmpowerScoring, Transport,
+};
+use reqwest::Client;
+use rustc_hash::FxHashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+const USER_AGENT: &str = "lang-rank-fetcher/0.1";
+
+/// Построитель [`Fetcher`] с настройками HTTP-запросов.
+///
+/// По умолчанию запрос повторяется до трёх раз с экспоненциальной задержкой
+/// от 2 до 60 секунд со случайным разбросом, а каждая попытка ограничена
+/// 20 секундами. Ответы 429 и 503 с заголовком `Retry-After` (в секундах или
+/// датой HTTP) ждут указанное сервером время, но не дольше максимальной
+/// задержки. Прочие ответы 4xx, кроме 408 и 425, не повторяются.
+///
+/// ```
+/// use langrank::{Fetcher, PerformanceSource};
+/// use std::time::Duration;
+///
+/// let fetcher = Fetcher::builder()
+///     .max_attempts(5)
+///     .backoff(Duration::from_secs(1), Duration::from_secs(30))
+///     .source_timeout(PerformanceSource::TechEmpower, Duration::from_secs(300))
+///     .user_agent("nightly-job/1.0")
+///     .build()?;
+/// # Ok::<(), langrank::FetchError>(())
+/// ```
+#[derive(Clone)]
+pub struct FetcherBuilder {
+    transport: Option<Arc<dyn Transport>>,
+    client: Option<Client>,
+    user_agent: String,
+    policy: RetryPolicy,
+    source_timeouts: FxHashMap<DataSource, Duration>,
+    providers: Vec<Arc<dyn RankingProvider>>,
+    cache: Option<HttpCache>,
+    urls: SourceUrls,
+    aliases: Option<AliasRegistry>,
+    benchmark_scoring: BenchmarkScoring,
+    techempower_scoring: TechEmpowerScoring,
+    techempower_round: Option<u16>,
+    languish_scoring: LanguishScoring,
+}
+
+impl FetcherBuilder {
+    /// Создаёт построитель с настройками по умолчанию.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            transport: None,
+            client: None,
+            user_agent: USER_AGENT.to_owned(),
+            policy: RetryPolicy::DEFAULT,
+            source_timeouts: FxHashMap::default(),
+            providers: Vec::new(),
+            cache: None,
+            urls: SourceUrls::default(),
+            aliases: None,
+            benchmark_scoring: BenchmarkScoring::default(),
+            techempower_scoring: TechEmpowerScoring::default(),
+            techempower_round: None,
+            languish_scoring: LanguishScoring::default(),
+        }
+    }
+
+    /// Использует собственный транспорт вместо HTTP-клиента по умолчанию.
+    ///
+    /// User-Agent из [`Self::user_agent`] к такому транспорту не применяется.
+    #[must_use]
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self.client = None;
+        self
+    }
+
+    /// Использует готовый HTTP-клиент.
+    #[must_use]
+    pub fn client(self, client: Client) -> Self {
+        let mut builder = self.transport(client.clone());
+        builder.client = Some(client);
+        builder
+    }
+
+    /// Задаёт User-Agent HTTP-клиента по умолчанию.
+    #[must_use]
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Задаёт общее число попыток запроса, включая первую. Значение `0`
+    /// считается одной попыткой.
+    #[must_use]
+    pub const fn max_attempts(mut self, attempts: u32) -> Self {
+        self.policy.max_attempts = attempts;
+        self
+    }
+
+    /// Задаёт начальную и максимальную задержку между попытками.
+    ///
+    /// Задержка удваивается после каждой попытки. Нулевые значения отключают
+    /// ожидание, что удобно в тестах.
+    #[must_use]
+    pub const fn backoff(mut self, base: Duration, max: Duration) -> Self {
+        self.policy.base_backoff = base;
+        self.policy.max_backoff = max;
+        self
+    }
+
+    /// Включает или отключает случайный разброс задержки.
+    ///
+    /// С разбросом фактическая задержка выбирается между половиной и полным
+    /// значением экспоненциальной задержки.
+    #[must_use]
+    pub const fn jitter(mut self, enabled: bool) -> Self {
+        self.policy.jitter = enabled;
+        self
+    }
+
+    /// Ограничивает время одной попытки запроса.
+    #[must_use]
+    pub const fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.policy.request_timeout = timeout;
+        self
+    }
+
+    /// Ограничивает общее время загрузки источника, включая все запросы и
+    /// повторы.
+    #[must_use]
+    pub fn source_timeout(mut self, source: impl Into<DataSource>, timeout: Duration) -> Self {
+        self.source_timeouts.insert(source.into(), timeout);
+        self
+    }
+
+    /// Включает дисковый кэш ответов встроенных источников.
+    ///
+    /// Пользовательские провайдеры получают транспорт напрямую и кэш не
+    /// используют.
+    #[must_use]
+    pub fn cache(mut self, cache: HttpCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Загружает встроенный источник с другого адреса, например с зеркала или
+    /// локального тестового сервера.
+    ///
+    /// Для TIOBE, PYPL и Benchmarks Game задаётся адрес самих данных. Для
+    /// Languish и `TechEmpower` это адрес каталога с завершающим `/`: от него
+    /// разрешаются ссылки на JS-бандлы и `results/round<N>/ph.json`.
+    /// Пользовательские провайдеры сами выбирают адреса, и для них настройка
+    /// не действует.
+    #[must_use]
+    pub fn source_url(mut self, source: impl Into<DataSource>, url: impl Into<String>) -> Self {
+        self.urls.set(source.into(), url.into());
+        self
+    }
+
+    /// Задаёт реестр синонимов названий языков вместо встроенного.
+    ///
+    /// Реестр применяется ко всем источникам, включая пользовательские.
+    #[must_use]
+    pub fn aliases(mut self, aliases: AliasRegistry) -> Self {
+        self.aliases = Some(aliases);
+        self
+    }
+
+    /// Задаёт стратегию вычисления показателей Benchmarks Game.
+    #[must_use]
+    pub fn benchmark_scoring(mut self, scoring: BenchmarkScoring) -> Self {
+        self.benchmark_scoring = scoring;
+        self
+    }
+
+    /// Задаёт стратегию вычисления показателей `TechEmpower`.
+    #[must_use]
+    pub fn techempower_scoring(mut self, scoring: TechEmpowerScoring) -> Self {
+        self.techempower_scoring = scoring;
+        self
+    }
+
+    /// Задаёт веса сигналов и окно тренда Languish.
+    #[must_use]
+    pub const fn languish_scoring(mut self, scoring: LanguishScoring) -> Self {
+        self.languish_scoring = scoring;
+        self
+    }
+
+    /// Загружает указанный раунд `TechEmpower` вместо последнего доступного.
+    #[must_use]
+    pub const fn techempower_round(mut self, round: u16) -> Self {
+        self.techempower_round = Some(round);
+        self
+    }
+
+    /// Регистрирует пользовательский источник рейтинга, как
+    /// [`Fetcher::with_provider`].
+    #[must_use]
+    pub fn provider(mut self, provider: impl RankingProvider + 'static) -> Self {
+        let id = provider.id();
+        self.providers.retain(|existing| existing.id() != id);
+        self.providers.push(Arc::new(provider));
+        self
+    }
+
+    /// Создаёт загрузчик.
+    ///
+    /// # Errors
+    ///
+    /// Возвращает ошибку, если не удалось создать HTTP-клиент по умолчанию.
+    pub fn build(self) -> Result<Fetcher, FetchError> {
+        let (transport, client) = if let Some(transport) = self.transport {
+            (transport, self.client)
+        } else {
+            let client = Client::builder()
+                .user_agent(self.user_agent)
+                .build()
+                .map_err(FetchError::ClientBuild)?;
+            let transport: Arc<dyn Transport> = Arc::new(client.clone());
+            (transport, Some(client))
+        };
+        Ok(Fetcher {
+            transport,
+            client,
+            providers: self.providers,
+            policy: self.policy,
+            source_timeouts: self.source_timeouts,
+            cache: self.cache,
+            urls: self.urls,
+            aliases: self
+                .aliases
+                .map_or_else(|| Arc::clone(builtin_aliases()), Arc::new),
+            benchmark_scoring: Arc::new(self.benchmark_scoring),
+            techempower_scoring: Arc::new(self.techempower_scoring),
+            techempower_round: self.techempower_round,
+            languish_scoring: self.languish_scoring,
+        })
+    }
+}
+
+impl Default for FetcherBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for FetcherBuilder {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("FetcherBuilder")
+            .field("user_agent", &self.user_agent)
+            .field("policy", &self.policy)
+            .field("source_timeouts", &self.source_timeouts)
+            .field("cache", &self.cache)
+            .field("urls", &self.urls)
+            .field("aliases", &self.aliases.as_ref().map(AliasRegistry::len))
+            .field("benchmark_scoring", &self.benchmark_scoring)
+            .field("techempower_scoring", &self.techempower_scoring)
+            .field("techempower_round", &self.techempower_round)
+            .field("languish_scoring", &self.languish_scoring)
+            .finish_non_exhaustive()
+    }
+}
@@ -1,3 +1,5 @@
+use langrank::{RankingSource, SubBallotRank};
+
 pub fn format_trend(trend: Option<f64>) -> String {
     format_trend_with_class(trend).0
 }
@@ -13,6 +15,34 @@ pub fn format_optional_float(value: Option<f64>) -> String {
     }
 }
 
+pub const fn ranking_label(source: RankingSource) -> &'static str {
+    match source {
+        RankingSource::Tiobe => "TIOBE",
+        RankingSource::Pypl => "PYPL",
+        RankingSource::Languish => "Languish",
+        _ => source.as_str(),
+    }
+}
+
+pub fn format_sub_ballot_label(ballot: &SubBallotRank) -> String {
+    format!("{} {}", ranking_label(ballot.source), ballot.ballot)
+}
+
+pub fn format_source_count(count: Option<usize>) -> String {
+    count.map_or_else(|| "missing".to_string(), |value| value.to_string())
+}
+
+pub fn format_error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
 pub fn format_perf_score(
     perf_score: f64,
     benchmark: Option<f64>,
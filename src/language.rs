@@ -0,0 +1,195 @@
+use rustc_hash::FxHashMap;
+use serde::{Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+/// Канонический язык программирования.
+///
+/// Значения интернируются: каждое каноническое имя хранится в процессе один
+/// раз, поэтому `Language` копируется без выделения памяти, а сравнение не
+/// требует сравнения строк. Язык получается через
+/// [`AliasRegistry::canonicalize`](crate::AliasRegistry::canonicalize), так что
+/// все наборы данных и таблицы показателей используют одно и то же написание.
+///
+/// Интернированные имена живут до конца процесса и не освобождаются, поэтому
+/// таблица растёт с каждым новым именем. Для названий языков из источников это
+/// сотни записей, но произвольный пользовательский ввод лучше сначала
+/// сопоставлять с известными языками, а не превращать в `Language`.
+#[derive(Clone, Copy)]
+pub struct Language(&'static LanguageData);
+
+#[derive(Debug)]
+struct LanguageData {
+    name: Box<str>,
+    slug: Box<str>,
+}
+
+impl Language {
+    /// Возвращает язык с указанным каноническим именем, добавляя его в
+    /// таблицу интернирования при первом обращении.
+    pub(crate) fn intern(name: &str) -> Self {
+        static INTERNED: OnceLock<Mutex<FxHashMap<&'static str, Language>>> = OnceLock::new();
+        let mut interned = INTERNED
+            .get_or_init(Mutex::default)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(&language) = interned.get(name) {
+            return language;
+        }
+        let data: &'static LanguageData = Box::leak(Box::new(LanguageData {
+            name: name.into(),
+            slug: slugify(name).into_boxed_str(),
+        }));
+        let language = Self(data);
+        interned.insert(&data.name, language);
+        language
+    }
+
+    /// Возвращает отображаемое каноническое имя, например `C++`.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        &self.0.name
+    }
+
+    /// Возвращает стабильный идентификатор из строчных латинских букв, цифр и
+    /// дефисов, например `cpp` для `C++` или `delphi-pascal` для
+    /// `Delphi/Pascal`.
+    ///
+    /// Слаг зависит только от имени, поэтому не меняется между запусками. Имена,
+    /// различающиеся лишь регистром и знаками (`C++` и `Cpp`), получают один
+    /// слаг: такие написания следует сводить синонимом в [`AliasRegistry`].
+    ///
+    /// [`AliasRegistry`]: crate::AliasRegistry
+    #[must_use]
+    pub fn slug(self) -> &'static str {
+        &self.0.slug
+    }
+}
+
+fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for ch in name.chars() {
+        match ch {
+            '+' => slug.push('p'),
+            '#' => slug.push_str("sharp"),
+            ch if ch.is_alphanumeric() => slug.extend(ch.to_lowercase()),
+            _ if slug.is_empty() || slug.ends_with('-') => {}
+            _ => slug.push('-'),
+        }
+    }
+    if slug.ends_with('-') {
+        slug.pop();
+    }
+    slug
+}
+
+impl PartialEq for Language {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for Language {}
+
+impl PartialEq<str> for Language {
+    fn eq(&self, other: &str) -> bool {
+        self.name() == other
+    }
+}
+
+impl PartialEq<&str> for Language {
+    fn eq(&self, other: &&str) -> bool {
+        self.name() == *other
+    }
+}
+
+// Хэш и порядок считаются по имени, а не по адресу, чтобы обход таблиц и
+// сортировка не зависели от порядка интернирования.
+impl Hash for Language {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name().hash(state);
+    }
+}
+
+impl PartialOrd for Language {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Language {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self == other {
+            return Ordering::Equal;
+        }
+        self.name().cmp(other.name())
+    }
+}
+
+impl fmt::Debug for Language {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_tuple("Language")
+            .field(&self.name())
+            .finish()
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.pad(self.name())
+    }
+}
+
+impl Serialize for Language {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Language;
+
+    #[test]
+    fn derives_slugs_from_names_only() {
+        let plus = Language::intern("Slugtest+");
+        let plain = Language::intern("Slugtestp");
+
+        assert_ne!(plus, plain);
+        assert_eq!(plus.slug(), "slugtestp");
+        assert_eq!(plain.slug(), "slugtestp");
+    }
+
+    #[test]
+    fn interns_names_and_builds_stable_slugs() {
+        let rust = Language::intern("Rust");
+        assert_eq!(rust, Language::intern("Rust"));
+        assert_ne!(rust, Language::intern("Go"));
+        assert_eq!(rust, "Rust");
+        assert_eq!(format!("{rust:<6}|"), "Rust  |");
+
+        let slugs = [
+            "C++",
+            "C#",
+            "Delphi/Pascal",
+            "Objective-C",
+            "Visual Basic",
+            "C/C++",
+        ]
+        .map(|name| Language::intern(name).slug());
+        assert_eq!(
+            slugs,
+            [
+                "cpp",
+                "csharp",
+                "delphi-pascal",
+                "objective-c",
+                "visual-basic",
+                "c-cpp"
+            ]
+        );
+    }
+}
@@ -1,9 +1,13 @@
 //! Загрузка и нормализация рейтингов языков программирования.
 //!
-//! Низкоуровневые функции принимают настроенный [`reqwest::Client`]. Для
+//! Низкоуровневые функции принимают [`Transport`], который выполняет
+//! HTTP-запросы. Транспорт реализован для [`reqwest::Client`]; собственная
+//! реализация позволяет отдавать ответы из памяти или локальных файлов. Для
 //! большинства сценариев удобнее [`Fetcher`], который создаёт клиент с
 //! подходящими значениями тайм-аута и User-Agent.
 //!
+//! Итоговое ранжирование по методу Шульце строит [`compute_schulze_records`].
+//!
 //! ```no_run
 //! use langrank::{FetchError, Fetcher};
 //!
@@ -18,16 +22,56 @@
 //! }
 //! ```
 
+mod aliases;
+mod cache;
+mod diagnostics;
 mod error;
 mod fetcher;
+mod language;
 mod parsing;
+mod performance;
+mod provider;
 mod ranking;
+mod schulze;
+mod snapshot;
 mod sources;
+mod transport;
 
-pub use error::FetchError;
-pub use fetcher::{Fetcher, MIN_RANKING_ENTRIES};
-pub use ranking::{RankingDataset, RankingEntry, RankingSource, reconcile_pypl_with_tiobe};
+pub use aliases::{AliasRegistry, AliasResolution};
+pub use cache::HttpCache;
+pub use diagnostics::{NameIssue, NameReport, NameSuggestion, diagnose_language_names};
+pub use error::{AliasError, FetchError, SchulzeError, SourceError};
+pub use fetcher::{
+    Fetcher, FetcherBuilder, MIN_BENCHMARK_LANGUAGES, MIN_RANKING_ENTRIES,
+    MIN_TECHEMPOWER_LANGUAGES,
+};
+pub use language::Language;
+pub use performance::{
+    BenchmarkMetric, BenchmarkResults, BenchmarkScores, BenchmarkScoring, DataSource,
+    LanguageBenchmark, PartialSourceBundle, PerformanceSource, RunSelection, SourceBundle,
+    TaskBenchmark, TechEmpowerScores, TimeMeasure, VariantScore,
+};
+pub use performance::{
+    FrameworkApproach, FrameworkClassification, FrameworkOrm, FrameworkResult, LanguageTechEmpower,
+    TechEmpowerAggregation, TechEmpowerChange, TechEmpowerFilter, TechEmpowerResults,
+    TechEmpowerScoring,
+};
+pub use provider::{ProviderFuture, RankingProvider};
+pub use ranking::{
+    CoreWeights, LanguishHistory, LanguishQuarter, LanguishScoring, LanguishSignal,
+    LanguishSignals, RankingDataset, RankingEntry, RankingSource, SubBallot, TiobeAward,
+    TiobeHistory, reconcile_pypl_with_tiobe,
+};
+pub use schulze::{
+    MetricUsage, PerformanceMetric, SchulzeConfig, SchulzeRecord, SubBallotRank,
+    compute_languish_backfill, compute_schulze_records, compute_schulze_records_with_metrics,
+};
+pub use snapshot::{RecordingTransport, SNAPSHOT_FORMAT_VERSION, Snapshot};
 pub use sources::{
-    TECHEMPOWER_MAX_SCORE, download_benchmark_data, fetch_languish, fetch_pypl, fetch_techempower,
-    fetch_tiobe, load_benchmark_scores,
+    TECHEMPOWER_MAX_SCORE, download_benchmark_data, fetch_languish, fetch_languish_history,
+    fetch_languish_with_options, fetch_pypl, fetch_techempower, fetch_techempower_results,
+    fetch_techempower_round, fetch_tiobe, fetch_tiobe_history, list_techempower_rounds,
+    load_benchmark_results, load_benchmark_results_with_options, load_benchmark_scores,
+    load_benchmark_scores_with_options,
 };
+pub use transport::{HttpResponse, Transport, TransportFuture};
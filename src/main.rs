@@ -1,33 +1,40 @@
-use crate::cli::Cli;
+use crate::alias_lookup::print_alias_lookup;
+use crate::backfill::backfill_rankings;
+use crate::cli::{Cli, Commands, generate_completions};
 use crate::progress::{ProgressState, Stage, run_with_spinner};
 use crate::report::{HtmlReportContext, HtmlReportPaths, save_html_report};
-use crate::schulze::{SchulzeConfig, SchulzeRecord, compute_schulze_records};
 use crate::summary::{SummaryContext, SummaryPaths, print_summary};
-use anyhow::{Context, Result, anyhow};
+use crate::techempower_rounds::print_techempower_rounds;
+use anyhow::{Context, Result};
 use chrono::Local;
 use clap::Parser;
-use csv::Writer;
+use csv::{Writer, WriterBuilder};
 use flate2::Compression;
 use flate2::write::GzEncoder;
 use langrank::{
-    Fetcher, MIN_RANKING_ENTRIES, RankingEntry, RankingSource, TECHEMPOWER_MAX_SCORE,
-    download_benchmark_data, fetch_languish, fetch_pypl, fetch_techempower, fetch_tiobe,
-    load_benchmark_scores, reconcile_pypl_with_tiobe,
+    AliasRegistry, DataSource, Fetcher, HttpCache, Language, MetricUsage, NameReport,
+    PerformanceMetric, PerformanceSource, RankingDataset, RankingSource, RecordingTransport,
+    SchulzeConfig, SchulzeRecord, Snapshot, TECHEMPOWER_MAX_SCORE, Transport,
+    compute_schulze_records_with_metrics, diagnose_language_names,
 };
 use serde::Serialize;
 use std::io::{IsTerminal, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::fs;
 
+mod alias_lookup;
+mod backfill;
 mod cli;
 mod formatting;
 mod progress;
 mod report;
-mod schulze;
 mod summary;
+mod techempower_rounds;
+
+type Recorder = RecordingTransport<Arc<dyn Transport>>;
 
-const MIN_BENCHMARK_LANGUAGES: usize = 10;
-const MIN_TECHEMPOWER_LANGUAGES: usize = 10;
 const MIN_SOURCE_OVERLAP: usize = 3;
 const MAX_RANKED_LANGUAGES: usize = 0;
 
@@ -40,19 +47,51 @@ async fn main() -> Result<()> {
     let mut cli = Cli::parse();
 
     if let Some(command) = cli.command.take() {
-        crate::cli::handle_command(command)?;
-        return Ok(());
+        return match command {
+            Commands::Completions {
+                shell,
+                output_dir,
+                install,
+            } => generate_completions(shell, output_dir, install),
+            Commands::Alias { name } => {
+                let (fetcher, recorder) = build_fetcher(&cli)?;
+                print_alias_lookup(&fetcher, &name).await;
+                save_snapshot(cli.record.as_deref(), recorder)?;
+                Ok(())
+            }
+            Commands::TeRounds { compare } => {
+                let (fetcher, recorder) = build_fetcher(&cli)?;
+                let result = print_techempower_rounds(&fetcher, &compare).await;
+                save_snapshot(cli.record.as_deref(), recorder)?;
+                result
+            }
+            Commands::Backfill { output_dir, since } => {
+                let (fetcher, recorder) = build_fetcher(&cli)?;
+                let result =
+                    backfill_rankings(&fetcher, &output_dir, since.as_deref(), cli.archive_csv)
+                        .await;
+                save_snapshot(cli.record.as_deref(), recorder)?;
+                result
+            }
+        };
     }
 
+    let (fetcher, recorder) = build_fetcher(&cli)?;
+
     let Cli {
         save_rankings,
         save_benchmarks,
         save_schulze,
         save_html,
+        save_name_report,
         no_minify_html,
         full_output,
         no_progress,
         archive_csv,
+        record,
+        allow_partial,
+        bg_ballots,
+        bg_perf_metrics,
         ..
     } = cli;
 
@@ -63,9 +102,6 @@ async fn main() -> Result<()> {
 
     let run_started_at = Local::now();
 
-    let fetcher = Fetcher::new()?;
-    let client = fetcher.client();
-
     let progress_enabled = !no_progress && std::io::stderr().is_terminal();
     let progress = if progress_enabled {
         Some(ProgressState::new(use_color))
@@ -73,100 +109,110 @@ async fn main() -> Result<()> {
         None
     };
 
-    let (tiobe, mut pypl, languish, bench_bytes, techempower_scores) =
-        if let Some(progress) = progress.as_ref() {
-            tokio::try_join!(
-                run_with_spinner(progress, Stage::Fetch, "TIOBE", fetch_tiobe(client)),
-                run_with_spinner(progress, Stage::Fetch, "PYPL", fetch_pypl(client)),
-                run_with_spinner(progress, Stage::Fetch, "Languish", fetch_languish(client)),
-                run_with_spinner(
-                    progress,
-                    Stage::Fetch,
-                    "Benchmarks",
-                    download_benchmark_data(client)
-                ),
-                run_with_spinner(
-                    progress,
-                    Stage::Fetch,
-                    "TechEmpower",
-                    fetch_techempower(client)
-                )
-            )?
+    let download = Box::pin(async {
+        let partial = fetcher.fetch_all_partial().await;
+        if partial.is_complete() {
+            Ok(partial)
         } else {
-            tokio::try_join!(
-                fetch_tiobe(client),
-                fetch_pypl(client),
-                fetch_languish(client),
-                download_benchmark_data(client),
-                fetch_techempower(client)
-            )?
-        };
-
-    let pypl_original_len = pypl.len();
-    reconcile_pypl_with_tiobe(&tiobe, &mut pypl);
-
-    ensure_min_entries("TIOBE", tiobe.len(), MIN_RANKING_ENTRIES)?;
-    ensure_min_entries("PYPL", pypl_original_len, MIN_RANKING_ENTRIES)?;
-    ensure_min_entries("Languish", languish.len(), MIN_RANKING_ENTRIES)?;
+            Err(partial)
+        }
+    });
+    let partial = if let Some(progress) = progress.as_ref() {
+        run_with_spinner(progress, Stage::Fetch, "sources", download).await
+    } else {
+        download.await
+    }
+    .unwrap_or_else(|partial| partial);
+
+    let snapshot_output = save_snapshot(record.as_deref(), recorder)?;
+    let ranking_count = |source| partial.bundle().ranking(source).map(RankingDataset::len);
+    let tiobe_count = ranking_count(RankingSource::Tiobe);
+    let pypl_count = ranking_count(RankingSource::Pypl);
+    let languish_count = ranking_count(RankingSource::Languish);
+    let performance_count = |source: PerformanceSource, len: usize| {
+        let failed = partial
+            .failures()
+            .iter()
+            .any(|error| error.source_name() == Some(source.as_str()));
+        (!failed).then_some(len)
+    };
+    let benchmark_lang_count = performance_count(
+        PerformanceSource::BenchmarksGame,
+        partial.bundle().benchmarks().len(),
+    );
+    let techempower_lang_count = performance_count(
+        PerformanceSource::TechEmpower,
+        partial.bundle().techempower().len(),
+    );
+    let (bundle, failures) = if allow_partial {
+        partial.into_parts()
+    } else {
+        (partial.into_complete()?, Vec::new())
+    };
 
     let rankings_output = if let Some(path) = save_rankings.as_ref() {
-        Some(
-            save_rankings_csv(
-                path.as_path(),
-                &[
-                    (RankingSource::Tiobe, &tiobe),
-                    (RankingSource::Pypl, &pypl),
-                    (RankingSource::Languish, &languish),
-                ],
-                archive_csv,
-            )
-            .await?,
-        )
+        Some(save_rankings_csv(path.as_path(), bundle.rankings(), archive_csv).await?)
     } else {
         None
     };
 
-    let benchmarks_output = if let Some(path) = save_benchmarks.as_ref() {
-        Some(save_benchmarks_csv(&bench_bytes, path.as_path(), archive_csv).await?)
+    let benchmarks_output = match (save_benchmarks.as_ref(), bundle.benchmark_csv()) {
+        (Some(path), Some(raw_benchmarks)) => {
+            Some(save_benchmarks_csv(raw_benchmarks, path.as_path(), archive_csv).await?)
+        }
+        _ => None,
+    };
+
+    let name_report = diagnose_language_names(&bundle, fetcher.aliases());
+    let name_report_output = if let Some(path) = save_name_report.as_ref() {
+        save_name_report_json(&name_report, path.as_path()).await?;
+        Some(path.as_path())
     } else {
         None
     };
 
-    let benchmark_scores = if let Some(progress) = progress.as_ref() {
-        run_with_spinner(
-            progress,
-            Stage::Compute,
-            "Compute benchmarks",
-            load_benchmark_scores(bench_bytes),
+    let schulze_config = SchulzeConfig {
+        min_source_overlap: MIN_SOURCE_OVERLAP.min(bundle.source_count()),
+        max_ranked_languages: MAX_RANKED_LANGUAGES,
+        techempower_max_score: TECHEMPOWER_MAX_SCORE,
+    };
+    let metric_usages: Vec<_> = bg_ballots
+        .iter()
+        .map(|metric| (metric.metric(), MetricUsage::Ballot))
+        .chain(
+            bg_perf_metrics
+                .iter()
+                .map(|metric| (metric.metric(), MetricUsage::PerfScore { weight: 1.0 })),
         )
-        .await?
+        .collect();
+    let metric_scores: Vec<_> = metric_usages
+        .iter()
+        .map(|&(metric, _)| bundle.benchmarks().results().metric_scores(metric))
+        .collect();
+    for (scores, &(metric, _)) in metric_scores.iter().zip(&metric_usages) {
+        if scores.is_empty() && !bundle.benchmarks().is_empty() {
+            eprintln!("Warning: the Benchmarks Game data has no {metric} values; ignoring it.");
+        }
+    }
+    let performance_metrics: Vec<_> = metric_scores
+        .iter()
+        .zip(&metric_usages)
+        .map(|(scores, &(_, usage))| PerformanceMetric { scores, usage })
+        .collect();
+    let compute_schulze = async {
+        compute_schulze_records_with_metrics(
+            bundle.rankings(),
+            bundle.benchmarks().scores(),
+            bundle.techempower().scores(),
+            &performance_metrics,
+            schulze_config,
+        )
+    };
+    let schulze_records = if let Some(progress) = progress.as_ref() {
+        run_with_spinner(progress, Stage::Compute, "Schulze ranking", compute_schulze).await?
     } else {
-        load_benchmark_scores(bench_bytes).await?
+        compute_schulze.await?
     };
-    ensure_min_entries(
-        "Benchmarks Game",
-        benchmark_scores.len(),
-        MIN_BENCHMARK_LANGUAGES,
-    )?;
-    ensure_min_entries(
-        "TechEmpower",
-        techempower_scores.len(),
-        MIN_TECHEMPOWER_LANGUAGES,
-    )?;
-    let benchmark_lang_count = benchmark_scores.len();
-    let techempower_lang_count = techempower_scores.len();
-    let schulze_records = compute_schulze_records(
-        &tiobe,
-        &pypl,
-        &languish,
-        &benchmark_scores,
-        &techempower_scores,
-        SchulzeConfig {
-            min_source_overlap: MIN_SOURCE_OVERLAP,
-            max_ranked_languages: MAX_RANKED_LANGUAGES,
-            techempower_max_score: TECHEMPOWER_MAX_SCORE,
-        },
-    )?;
     let schulze_output = if let Some(path) = save_schulze.as_ref() {
         Some(save_schulze_csv(&schulze_records, path.as_path(), archive_csv).await?)
     } else {
@@ -175,13 +221,16 @@ async fn main() -> Result<()> {
 
     if let Some(path) = save_html.as_ref() {
         let html_context = HtmlReportContext {
-            tiobe_count: tiobe.len(),
-            pypl_count: pypl_original_len,
-            languish_count: languish.len(),
+            tiobe_count,
+            pypl_count,
+            languish_count,
             benchmark_lang_count,
             techempower_lang_count,
+            techempower_filter: bundle.techempower().results().filter(),
+            techempower_round: bundle.techempower().results().round(),
             run_started_at: &run_started_at,
             schulze_records: &schulze_records,
+            failures: &failures,
             full_output,
             archive_csv,
             paths: HtmlReportPaths {
@@ -199,25 +248,111 @@ async fn main() -> Result<()> {
     }
 
     print_summary(&SummaryContext {
-        tiobe_count: tiobe.len(),
-        pypl_count: pypl_original_len,
-        languish_count: languish.len(),
+        tiobe_count,
+        pypl_count,
+        languish_count,
         benchmark_lang_count,
         techempower_lang_count,
+        techempower_filter: bundle.techempower().results().filter(),
+        techempower_round: bundle.techempower().results().round(),
         run_started_at: &run_started_at,
         paths: SummaryPaths {
             benchmarks: benchmarks_output.as_deref(),
             rankings: rankings_output.as_deref(),
             schulze: schulze_output.as_deref(),
             html: save_html.as_deref(),
+            name_report: name_report_output,
+            snapshot: snapshot_output,
         },
+        name_report: &name_report,
+        benchmark_results: bundle.benchmarks().results(),
         schulze_records: &schulze_records,
+        failures: &failures,
         full_output,
     });
 
     Ok(())
 }
 
+fn build_fetcher(cli: &Cli) -> Result<(Fetcher, Option<Arc<Recorder>>)> {
+    let mut builder = Fetcher::builder();
+    if let Some(attempts) = cli.max_attempts {
+        builder = builder.max_attempts(attempts);
+    }
+    if let Some(seconds) = cli.request_timeout {
+        builder = builder.request_timeout(Duration::from_secs(seconds));
+    }
+    if let Some(dir) = cli.cache_dir.as_ref() {
+        let mut cache = HttpCache::new(dir);
+        if let Some(seconds) = cli.cache_max_age {
+            cache = cache.with_max_age(Duration::from_secs(seconds));
+        }
+        if let Some(seconds) = cli.cache_period_max_age {
+            cache = cache.with_period_max_age(Duration::from_secs(seconds));
+        }
+        builder = builder.cache(cache);
+    }
+    let source_urls = [
+        (
+            DataSource::from(RankingSource::Tiobe),
+            cli.tiobe_url.clone(),
+        ),
+        (RankingSource::Pypl.into(), cli.pypl_url.clone()),
+        (RankingSource::Languish.into(), cli.languish_url.clone()),
+        (
+            PerformanceSource::BenchmarksGame.into(),
+            cli.benchmarks_game_url.clone(),
+        ),
+        (
+            PerformanceSource::TechEmpower.into(),
+            cli.techempower_url.clone(),
+        ),
+    ];
+    for (source, url) in source_urls {
+        if let Some(url) = url {
+            builder = builder.source_url(source, url);
+        }
+    }
+
+    builder = builder.benchmark_scoring(cli.benchmark_scoring());
+    builder = builder.techempower_scoring(cli.techempower_scoring());
+    builder = builder.languish_scoring(cli.languish_scoring()?);
+    if let Some(round) = cli.te_round {
+        builder = builder.techempower_round(round);
+    }
+
+    if let Some(path) = cli.aliases.as_ref() {
+        let aliases = AliasRegistry::load(path)
+            .with_context(|| format!("failed to load aliases from {}", path.display()))?;
+        builder = builder.aliases(aliases);
+    }
+
+    if let Some(dir) = cli.replay.as_ref() {
+        let snapshot = Snapshot::load(dir)
+            .with_context(|| format!("failed to load snapshot from {}", dir.display()))?;
+        Ok((builder.transport(snapshot).build()?, None))
+    } else if cli.record.is_some() {
+        let network = builder.clone().build()?.shared_transport();
+        let recording = Arc::new(RecordingTransport::new(network));
+        let fetcher = builder.transport(Arc::clone(&recording)).build()?;
+        Ok((fetcher, Some(recording)))
+    } else {
+        Ok((builder.build()?, None))
+    }
+}
+
+/// Сохраняет записанные ответы в каталог `--record`, если запись включена.
+fn save_snapshot(dir: Option<&Path>, recorder: Option<Arc<Recorder>>) -> Result<Option<&Path>> {
+    let (Some(dir), Some(recorder)) = (dir, recorder) else {
+        return Ok(None);
+    };
+    recorder
+        .snapshot()
+        .save(dir)
+        .with_context(|| format!("failed to save snapshot to {}", dir.display()))?;
+    Ok(Some(dir))
+}
+
 async fn save_benchmarks_csv(bytes: &[u8], path: &Path, archive: bool) -> Result<PathBuf> {
     write_csv_output(path, bytes, archive).await
 }
@@ -284,34 +419,26 @@ fn should_use_color() -> bool {
     std::io::stdout().is_terminal()
 }
 
-fn ensure_min_entries(label: &str, count: usize, min: usize) -> Result<()> {
-    if count < min {
-        return Err(anyhow!(
-            "{label} returned {count} entries (expected at least {min}); the source format may have changed."
-        ));
-    }
-    Ok(())
-}
-
 async fn save_rankings_csv(
     path: &Path,
-    sources: &[(RankingSource, &[RankingEntry])],
+    rankings: &[RankingDataset],
     archive: bool,
 ) -> Result<PathBuf> {
-    let serialized = serialize_rankings(sources)?;
+    let serialized = serialize_rankings(rankings)?;
     write_csv_output(path, &serialized, archive).await
 }
 
-fn serialize_rankings(sources: &[(RankingSource, &[RankingEntry])]) -> Result<Vec<u8>> {
+fn serialize_rankings(rankings: &[RankingDataset]) -> Result<Vec<u8>> {
     let mut writer = Writer::from_writer(Vec::new());
-    for (source, entries) in sources {
-        for entry in *entries {
+    for dataset in rankings {
+        for entry in dataset {
             let record = CsvRecord {
-                source: *source,
-                lang: entry.lang.as_str(),
+                source: dataset.source(),
+                lang: entry.lang,
                 rank: entry.rank,
                 share: entry.share,
                 trend: entry.trend,
+                previous_rank: entry.previous_rank,
             };
             writer
                 .serialize(record)
@@ -322,12 +449,19 @@ fn serialize_rankings(sources: &[(RankingSource, &[RankingEntry])]) -> Result<Ve
 }
 
 #[derive(Debug, Serialize)]
-struct CsvRecord<'a> {
+struct CsvRecord {
     source: RankingSource,
-    lang: &'a str,
+    lang: Language,
     rank: Option<u32>,
-    share: f64,
+    share: Option<f64>,
     trend: Option<f64>,
+    previous_rank: Option<u32>,
+}
+
+async fn save_name_report_json(report: &NameReport, path: &Path) -> Result<()> {
+    let serialized =
+        serde_json::to_vec_pretty(report).context("failed to serialize language name report")?;
+    write_output_file(path, &serialized).await
 }
 
 async fn save_schulze_csv(
@@ -335,12 +469,153 @@ async fn save_schulze_csv(
     output_path: &Path,
     archive: bool,
 ) -> Result<PathBuf> {
-    let mut writer = Writer::from_writer(Vec::new());
+    let serialized = serialize_schulze_records(records)?;
+    write_csv_output(output_path, &serialized, archive).await
+}
+
+/// Колонки [`SchulzeRecord`] в порядке сериализации.
+const SCHULZE_COLUMNS: [&str; 16] = [
+    "position",
+    "lang",
+    "tiobe_rank",
+    "tiobe_share",
+    "tiobe_trend",
+    "pypl_rank",
+    "pypl_share",
+    "pypl_trend",
+    "languish_rank",
+    "languish_share",
+    "languish_trend",
+    "benchmark_score",
+    "techempower_score",
+    "perf_score",
+    "schulze_wins",
+    "tiobe_previous_rank",
+];
+
+/// Сериализует записи Шульце; места в отдельных бюллетенях источников
+/// добавляются колонками `<источник>_<бюллетень>_rank` для всех бюллетеней,
+/// встретившихся хотя бы в одной записи, в порядке первого появления.
+fn serialize_schulze_records(records: &[SchulzeRecord]) -> Result<Vec<u8>> {
+    let mut ballots: Vec<(RankingSource, &str)> = Vec::new();
+    for ballot in records.iter().flat_map(|record| &record.sub_ballot_ranks) {
+        let key = (ballot.source, ballot.ballot);
+        if !ballots.contains(&key) {
+            ballots.push(key);
+        }
+    }
+
+    let mut writer = WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    if !records.is_empty() {
+        let header = SCHULZE_COLUMNS.map(str::to_owned).into_iter().chain(
+            ballots
+                .iter()
+                .map(|(source, ballot)| format!("{source}_{ballot}_rank").replace('-', "_")),
+        );
+        writer
+            .write_record(header)
+            .context("failed to write Schulze ranking header")?;
+    }
     for record in records {
+        let ranks: Vec<Option<u32>> = ballots
+            .iter()
+            .map(|&(source, ballot)| {
+                record
+                    .sub_ballot_ranks
+                    .iter()
+                    .find(|rank| rank.source == source && rank.ballot == ballot)
+                    .and_then(|rank| rank.rank)
+            })
+            .collect();
         writer
-            .serialize(record)
+            .serialize((record, ranks))
             .context("failed to serialize Schulze ranking record")?;
     }
-    let serialized = finalize_writer(writer, "Schulze ranking writer")?;
-    write_csv_output(output_path, &serialized, archive).await
+    finalize_writer(writer, "Schulze ranking writer")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SCHULZE_COLUMNS, serialize_schulze_records};
+    use langrank::{
+        AliasRegistry, Language, RankingDataset, RankingEntry, RankingSource, SchulzeConfig,
+        SchulzeRecord, SubBallot, compute_schulze_records,
+    };
+    use std::collections::HashMap;
+
+    fn language(name: &str) -> Language {
+        AliasRegistry::empty()
+            .canonicalize(name)
+            .expect("test language names are not empty")
+    }
+
+    fn rank(languages: &[&str], sub_ballot: bool) -> Vec<SchulzeRecord> {
+        let entries: Vec<RankingEntry> = (1_u32..)
+            .zip(languages)
+            .map(|(rank, &name)| {
+                RankingEntry::new(language(name))
+                    .with_rank(rank)
+                    .with_share(f64::from(10 - rank))
+            })
+            .collect();
+        let mut languish = RankingDataset::new(RankingSource::Languish, entries.clone());
+        if sub_ballot {
+            let stars = languages
+                .iter()
+                .rev()
+                .zip(1_u32..)
+                .map(|(&name, score)| (language(name), f64::from(score)))
+                .collect();
+            languish = languish.with_sub_ballots(vec![SubBallot::new("stars", stars)]);
+        }
+        let rankings = [RankingDataset::new(RankingSource::Tiobe, entries), languish];
+        let config = SchulzeConfig {
+            min_source_overlap: 1,
+            ..SchulzeConfig::default()
+        };
+        let empty: HashMap<Language, f64> = HashMap::new();
+        compute_schulze_records(&rankings, &empty, &empty, config).expect("two languages rank")
+    }
+
+    #[test]
+    fn schulze_columns_match_the_serialized_record() {
+        let records = rank(&["Rust", "Go"], false);
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.serialize(&records[0]).expect("record serializes");
+        let serialized = writer.into_inner().expect("writer flushes");
+        let header = String::from_utf8(serialized).expect("CSV is UTF-8");
+
+        assert_eq!(
+            header.lines().next(),
+            Some(SCHULZE_COLUMNS.join(",").as_str())
+        );
+    }
+
+    #[test]
+    fn serializes_records_with_and_without_sub_ballots() {
+        let mut records = rank(&["Go", "Zig"], false);
+        records.extend(rank(&["Rust", "Go"], true));
+
+        let serialized = serialize_schulze_records(&records).expect("records serialize");
+        let mut reader = csv::Reader::from_reader(serialized.as_slice());
+        let header = reader.headers().expect("header is written").clone();
+        let rows: Vec<csv::StringRecord> = reader
+            .records()
+            .collect::<Result<_, _>>()
+            .expect("rows parse");
+
+        assert_eq!(header.len(), SCHULZE_COLUMNS.len() + 1);
+        assert_eq!(
+            header.get(SCHULZE_COLUMNS.len()),
+            Some("languish_stars_rank")
+        );
+        let stars: Vec<(&str, &str)> = rows
+            .iter()
+            .map(|row| (&row[1], &row[SCHULZE_COLUMNS.len()]))
+            .collect();
+        assert_eq!(stars, [("Go", ""), ("Zig", ""), ("Rust", "1"), ("Go", "2")]);
+        assert!(serialize_schulze_records(&[]).expect("empty").is_empty());
+    }
 }
@@ -0,0 +1,428 @@
+mod benchmarks;
+mod techempower;
+
+pub use benchmarks::{
+    BenchmarkMetric, BenchmarkResults, BenchmarkScoring, LanguageBenchmark, RunSelection,
+    TaskBenchmark, TimeMeasure, VariantScore,
+};
+pub use techempower::{
+    FrameworkApproach, FrameworkClassification, FrameworkOrm, FrameworkResult, LanguageTechEmpower,
+    TechEmpowerAggregation, TechEmpowerChange, TechEmpowerFilter, TechEmpowerResults,
+    TechEmpowerScoring,
+};
+
+use crate::{FetchError, Language, RankingDataset, RankingSource};
+use rustc_hash::{FxBuildHasher, FxHashMap};
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+/// Источник показателей производительности.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum PerformanceSource {
+    BenchmarksGame,
+    TechEmpower,
+}
+
+impl PerformanceSource {
+    /// Возвращает стабильное строковое имя источника.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::BenchmarksGame => "benchmarks-game",
+            Self::TechEmpower => "techempower",
+        }
+    }
+}
+
+impl fmt::Display for PerformanceSource {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+/// Любой источник входных данных ранжирования.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum DataSource {
+    Ranking(RankingSource),
+    Performance(PerformanceSource),
+}
+
+impl DataSource {
+    /// Возвращает стабильное строковое имя источника.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Ranking(source) => source.as_str(),
+            Self::Performance(source) => source.as_str(),
+        }
+    }
+}
+
+impl From<RankingSource> for DataSource {
+    fn from(source: RankingSource) -> Self {
+        Self::Ranking(source)
+    }
+}
+
+impl From<PerformanceSource> for DataSource {
+    fn from(source: PerformanceSource) -> Self {
+        Self::Performance(source)
+    }
+}
+
+impl Serialize for DataSource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl fmt::Display for DataSource {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+/// Нормализованные показатели Benchmarks Game по языкам.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BenchmarkScores {
+    scores: FxHashMap<Language, f64>,
+    results: BenchmarkResults,
+}
+
+impl BenchmarkScores {
+    /// Создаёт набор показателей.
+    #[must_use]
+    pub const fn new(scores: FxHashMap<Language, f64>) -> Self {
+        Self {
+            scores,
+            results: BenchmarkResults::new(FxHashMap::with_hasher(FxBuildHasher)),
+        }
+    }
+
+    /// Создаёт набор показателей из подробных результатов.
+    #[must_use]
+    pub fn from_results(results: BenchmarkResults) -> Self {
+        Self {
+            scores: results.scores(),
+            results,
+        }
+    }
+
+    /// Возвращает показатель языка в диапазоне 0..1.
+    #[must_use]
+    pub fn get(&self, lang: Language) -> Option<f64> {
+        self.scores.get(&lang).copied()
+    }
+
+    /// Возвращает все показатели по языкам.
+    #[must_use]
+    pub const fn scores(&self) -> &FxHashMap<Language, f64> {
+        &self.scores
+    }
+
+    /// Возвращает результаты по задачам, из которых получены показатели.
+    ///
+    /// Пуст, если набор создан через [`Self::new`].
+    #[must_use]
+    pub const fn results(&self) -> &BenchmarkResults {
+        &self.results
+    }
+
+    /// Возвращает количество языков в наборе.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Проверяет, что набор не содержит языков.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+}
+
+/// Композитные показатели `TechEmpower` по языкам.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TechEmpowerScores {
+    scores: FxHashMap<Language, f64>,
+    results: TechEmpowerResults,
+}
+
+impl TechEmpowerScores {
+    /// Создаёт набор показателей.
+    #[must_use]
+    pub const fn new(scores: FxHashMap<Language, f64>) -> Self {
+        Self {
+            scores,
+            results: TechEmpowerResults::new(FxHashMap::with_hasher(FxBuildHasher)),
+        }
+    }
+
+    /// Создаёт набор показателей из подробных результатов.
+    #[must_use]
+    pub fn from_results(results: TechEmpowerResults) -> Self {
+        Self {
+            scores: results.scores(),
+            results,
+        }
+    }
+
+    /// Возвращает показатель языка в диапазоне от 0 до
+    /// [`TECHEMPOWER_MAX_SCORE`](crate::TECHEMPOWER_MAX_SCORE).
+    #[must_use]
+    pub fn get(&self, lang: Language) -> Option<f64> {
+        self.scores.get(&lang).copied()
+    }
+
+    /// Возвращает все показатели по языкам.
+    #[must_use]
+    pub const fn scores(&self) -> &FxHashMap<Language, f64> {
+        &self.scores
+    }
+
+    /// Возвращает результаты по фреймворкам, из которых получены показатели.
+    ///
+    /// Пуст, если набор создан через [`Self::new`].
+    #[must_use]
+    pub const fn results(&self) -> &TechEmpowerResults {
+        &self.results
+    }
+
+    /// Возвращает количество языков в наборе.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Проверяет, что набор не содержит языков.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+}
+
+/// Полный набор входных данных ранжирования.
+///
+/// Содержит рейтинги популярности и показатели производительности в том же
+/// виде, в каком их использует CLI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceBundle {
+    rankings: Vec<RankingDataset>,
+    benchmarks: BenchmarkScores,
+    techempower: TechEmpowerScores,
+    benchmark_csv: Option<Vec<u8>>,
+}
+
+impl SourceBundle {
+    /// Собирает набор входных данных.
+    ///
+    /// Если среди рейтингов есть TIOBE и PYPL, совокупная запись PYPL `C/C++`
+    /// согласуется с отдельными долями C и C++ из TIOBE.
+    #[must_use]
+    pub fn new(
+        mut rankings: Vec<RankingDataset>,
+        benchmarks: BenchmarkScores,
+        techempower: TechEmpowerScores,
+    ) -> Self {
+        crate::ranking::reconcile_datasets(&mut rankings);
+        Self {
+            rankings,
+            benchmarks,
+            techempower,
+            benchmark_csv: None,
+        }
+    }
+
+    /// Прикладывает исходный CSV Benchmarks Game, из которого получены
+    /// показатели.
+    #[must_use]
+    pub fn with_benchmark_csv(mut self, csv: Vec<u8>) -> Self {
+        self.benchmark_csv = Some(csv);
+        self
+    }
+
+    /// Возвращает рейтинги популярности: TIOBE, PYPL, Languish и затем
+    /// пользовательские источники в порядке регистрации.
+    #[must_use]
+    pub fn rankings(&self) -> &[RankingDataset] {
+        &self.rankings
+    }
+
+    /// Возвращает набор указанного рейтингового источника.
+    #[must_use]
+    pub fn ranking(&self, source: RankingSource) -> Option<&RankingDataset> {
+        self.rankings
+            .iter()
+            .find(|dataset| dataset.source() == source)
+    }
+
+    /// Возвращает количество независимых источников ранжирования: рейтинги
+    /// популярности и, если есть хотя бы один набор показателей,
+    /// производительность.
+    #[must_use]
+    pub fn source_count(&self) -> usize {
+        let has_performance = !self.benchmarks.is_empty() || !self.techempower.is_empty();
+        self.rankings.len() + usize::from(has_performance)
+    }
+
+    /// Возвращает показатели Benchmarks Game.
+    #[must_use]
+    pub const fn benchmarks(&self) -> &BenchmarkScores {
+        &self.benchmarks
+    }
+
+    /// Возвращает показатели `TechEmpower`.
+    #[must_use]
+    pub const fn techempower(&self) -> &TechEmpowerScores {
+        &self.techempower
+    }
+
+    /// Возвращает исходный CSV Benchmarks Game, если он приложен через
+    /// [`Self::with_benchmark_csv`]; [`Fetcher::fetch_all`] и
+    /// [`Fetcher::fetch_all_partial`] прикладывают его при успешной загрузке.
+    ///
+    /// [`Fetcher::fetch_all`]: crate::Fetcher::fetch_all
+    /// [`Fetcher::fetch_all_partial`]: crate::Fetcher::fetch_all_partial
+    #[must_use]
+    pub fn benchmark_csv(&self) -> Option<&[u8]> {
+        self.benchmark_csv.as_deref()
+    }
+
+    /// Разбирает набор на составные части.
+    #[must_use]
+    pub fn into_parts(self) -> (Vec<RankingDataset>, BenchmarkScores, TechEmpowerScores) {
+        (self.rankings, self.benchmarks, self.techempower)
+    }
+}
+
+/// Результат загрузки, в котором часть источников могла завершиться ошибкой.
+///
+/// Успешно загруженные источники собираются в [`SourceBundle`]; отсутствующие
+/// наборы производительности заменяются пустыми.
+#[derive(Debug)]
+pub struct PartialSourceBundle {
+    bundle: SourceBundle,
+    failures: Vec<FetchError>,
+}
+
+impl PartialSourceBundle {
+    /// Собирает результат из независимых результатов загрузки источников.
+    #[must_use]
+    pub fn new(
+        rankings: Vec<Result<RankingDataset, FetchError>>,
+        benchmarks: Result<BenchmarkScores, FetchError>,
+        techempower: Result<TechEmpowerScores, FetchError>,
+    ) -> Self {
+        let mut failures = Vec::new();
+        let mut datasets = Vec::with_capacity(rankings.len());
+        for result in rankings {
+            match result {
+                Ok(dataset) => datasets.push(dataset),
+                Err(error) => failures.push(error),
+            }
+        }
+        let benchmarks = benchmarks.unwrap_or_else(|error| {
+            failures.push(error);
+            BenchmarkScores::default()
+        });
+        let techempower = techempower.unwrap_or_else(|error| {
+            failures.push(error);
+            TechEmpowerScores::default()
+        });
+
+        Self {
+            bundle: SourceBundle::new(datasets, benchmarks, techempower),
+            failures,
+        }
+    }
+
+    pub(crate) fn with_benchmark_csv(mut self, csv: Option<Vec<u8>>) -> Self {
+        if let Some(csv) = csv {
+            self.bundle = self.bundle.with_benchmark_csv(csv);
+        }
+        self
+    }
+
+    /// Возвращает данные успешно загруженных источников.
+    #[must_use]
+    pub const fn bundle(&self) -> &SourceBundle {
+        &self.bundle
+    }
+
+    /// Возвращает ошибки источников, которые не удалось загрузить.
+    #[must_use]
+    pub fn failures(&self) -> &[FetchError] {
+        &self.failures
+    }
+
+    /// Проверяет, что все источники загружены успешно.
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Возвращает полный набор данных или первую ошибку загрузки.
+    ///
+    /// # Errors
+    ///
+    /// Возвращает первую ошибку, если хотя бы один источник не загружен.
+    pub fn into_complete(self) -> Result<SourceBundle, FetchError> {
+        match self.failures.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(self.bundle),
+        }
+    }
+
+    /// Разбирает результат на загруженные данные и ошибки источников.
+    #[must_use]
+    pub fn into_parts(self) -> (SourceBundle, Vec<FetchError>) {
+        (self.bundle, self.failures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BenchmarkScores, PartialSourceBundle, PerformanceSource, TechEmpowerScores};
+    use crate::{FetchError, RankingDataset, RankingSource};
+    use anyhow::anyhow;
+
+    #[test]
+    fn partial_bundle_keeps_successful_sources_and_reports_failures() {
+        let fetched = PartialSourceBundle::new(
+            vec![
+                Ok(RankingDataset::new(RankingSource::Tiobe, Vec::new())),
+                Err(FetchError::source_failure(
+                    RankingSource::Pypl,
+                    anyhow!("HTTP status 503"),
+                )),
+            ],
+            Ok(BenchmarkScores::default()),
+            Err(FetchError::performance_failure(
+                PerformanceSource::TechEmpower,
+                anyhow!("no rounds"),
+            )),
+        )
+        .with_benchmark_csv(Some(b"lang,name\n".to_vec()));
+
+        assert!(!fetched.is_complete());
+        assert_eq!(fetched.bundle().benchmark_csv(), Some(&b"lang,name\n"[..]));
+        assert_eq!(fetched.bundle().rankings().len(), 1);
+        assert_eq!(
+            fetched.bundle().techempower(),
+            &TechEmpowerScores::default()
+        );
+        let missing: Vec<_> = fetched
+            .failures()
+            .iter()
+            .filter_map(FetchError::source_name)
+            .collect();
+        assert_eq!(missing, ["pypl", "techempower"]);
+
+        let error = fetched
+            .into_complete()
+            .expect_err("incomplete bundle should report the first failure");
+        assert_eq!(error.source_name(), Some("pypl"));
+    }
+}
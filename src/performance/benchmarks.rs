@@ -0,0 +1,319 @@
+use crate::Language;
+use rustc_hash::{FxBuildHasher, FxHashMap};
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+/// Подробные результаты Benchmarks Game по языкам.
+///
+/// Помимо итогового показателя хранит для каждого языка результаты по задачам,
+/// из которых он получен, что позволяет объяснить показатель или посчитать
+/// собственную агрегацию.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BenchmarkResults {
+    languages: FxHashMap<Language, LanguageBenchmark>,
+    variants: FxHashMap<Language, LanguageBenchmark>,
+}
+
+/// Результаты одного языка в Benchmarks Game.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct LanguageBenchmark {
+    /// Взвешенное геометрическое среднее отношений к лучшему времени по
+    /// задачам, 0..1.
+    pub score: f64,
+    /// Количество задач, вошедших в среднее.
+    pub sample_count: usize,
+    /// Доля учтённых задач, на которых у языка есть результат, 0..1.
+    pub coverage: f64,
+    /// Результаты по задачам в алфавитном порядке.
+    pub tasks: Vec<TaskBenchmark>,
+    /// Эффективность по процессорному времени, 0..1; `None`, если в CSV нет
+    /// этих данных.
+    pub cpu_time_score: Option<f64>,
+    /// Эффективность по пиковой памяти, 0..1; `None`, если в CSV нет этих
+    /// данных.
+    pub memory_score: Option<f64>,
+    /// Компактность исходного кода по размеру в gzip, 0..1; `None`, если в CSV
+    /// нет этих данных.
+    pub source_size_score: Option<f64>,
+    /// Количество запусков учитываемых задач, завершившихся ошибкой или
+    /// тайм-аутом.
+    pub failed_runs: usize,
+    /// Показатели реализаций языка от лучшей к худшей; заполняется только при
+    /// [`BenchmarkScoring::split_variants`], и тогда остальные поля описывают
+    /// лучшую реализацию.
+    pub variants: Vec<VariantScore>,
+}
+
+/// Показатель одной реализации языка в Benchmarks Game.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct VariantScore {
+    /// Реализация, например `Python (MicroPython)`.
+    pub name: Language,
+    /// Показатель реализации, 0..1.
+    pub score: f64,
+}
+
+/// Показатель языка, вычисляемый по данным Benchmarks Game.
+///
+/// Показатели по времени, памяти и размеру кода считаются так же, как
+/// основной: геометрическое среднее по задачам отношения лучшего значения
+/// среди всех языков к лучшему значению языка.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum BenchmarkMetric {
+    /// Время выполнения, основной показатель.
+    ElapsedTime,
+    /// Процессорное время.
+    CpuTime,
+    /// Пиковая память.
+    Memory,
+    /// Размер исходного кода в gzip.
+    SourceSize,
+    /// Доля успешных запусков.
+    Reliability,
+}
+
+/// Результат языка на одной задаче Benchmarks Game.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct TaskBenchmark {
+    /// Название задачи, например `nbody`.
+    pub task: String,
+    /// Время языка на задаче в секундах, выбранное по [`BenchmarkScoring`].
+    pub time: f64,
+    /// Лучшее такое время среди всех языков на задаче в секундах.
+    pub fastest_time: f64,
+    /// Отношение лучшего времени среди всех языков к времени языка, 0..1.
+    pub ratio: f64,
+    /// Вес задачи в среднем.
+    pub weight: f64,
+    /// Количество успешных запусков языка на задаче.
+    pub runs: usize,
+}
+
+/// Стратегия вычисления показателей Benchmarks Game.
+///
+/// По умолчанию для каждой пары язык–задача берётся лучший запуск по
+/// прошедшему времени, а все задачи учитываются с весом 1.
+///
+/// ```
+/// use langrank::{BenchmarkScoring, RunSelection, TimeMeasure};
+///
+/// let mut scoring = BenchmarkScoring {
+///     selection: RunSelection::Median,
+///     measure: TimeMeasure::CpuTime,
+///     min_task_coverage: 0.5,
+///     min_task_participants: 5,
+///     ..BenchmarkScoring::default()
+/// };
+/// scoring.task_weights.insert("regex-redux".to_owned(), 0.5);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BenchmarkScoring {
+    /// Какой запуск языка на задаче представляет язык.
+    pub selection: RunSelection,
+    /// По какому времени сравниваются языки.
+    pub measure: TimeMeasure,
+    /// Минимальная доля учтённых задач, которую должен покрыть язык, 0..1;
+    /// языки с меньшим покрытием не получают показателя.
+    pub min_task_coverage: f64,
+    /// Минимальное число языков с результатом на задаче; задачи с меньшим
+    /// числом участников не учитываются. Реализации одного языка в режиме
+    /// [`BenchmarkScoring::split_variants`] считаются одним участником.
+    pub min_task_participants: usize,
+    /// Веса задач по названию; задачи без веса учитываются с весом 1, а
+    /// задачи с нулевым весом исключаются.
+    pub task_weights: FxHashMap<String, f64>,
+    /// Оценивать компиляторы и рантаймы (`gcc`, `clang`, `micropython`)
+    /// как отдельные реализации вместо объединения в один язык. Список
+    /// реализаций берётся из [`AliasRegistry::variant`](crate::AliasRegistry::variant).
+    pub split_variants: bool,
+}
+
+/// Выбор запуска языка на задаче среди нескольких реализаций.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum RunSelection {
+    /// Лучший (минимальный) результат.
+    #[default]
+    Best,
+    /// Медиана результатов всех успешных запусков.
+    Median,
+}
+
+/// Время, по которому сравниваются языки.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum TimeMeasure {
+    /// Прошедшее (настенное) время, колонка `elapsed-time(s)`.
+    #[default]
+    Elapsed,
+    /// Процессорное время, колонка `cpu-time(s)`.
+    CpuTime,
+}
+
+impl BenchmarkScoring {
+    /// Возвращает вес задачи.
+    #[must_use]
+    pub fn task_weight(&self, task: &str) -> f64 {
+        self.task_weights.get(task).copied().unwrap_or(1.0)
+    }
+}
+
+impl BenchmarkMetric {
+    /// Все показатели в порядке объявления.
+    pub const ALL: [Self; 5] = [
+        Self::ElapsedTime,
+        Self::CpuTime,
+        Self::Memory,
+        Self::SourceSize,
+        Self::Reliability,
+    ];
+
+    /// Возвращает стабильное строковое имя показателя.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::ElapsedTime => "elapsed-time",
+            Self::CpuTime => "cpu-time",
+            Self::Memory => "memory",
+            Self::SourceSize => "source-size",
+            Self::Reliability => "reliability",
+        }
+    }
+}
+
+impl Serialize for BenchmarkMetric {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl fmt::Display for BenchmarkMetric {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl LanguageBenchmark {
+    /// Возвращает общее количество запусков языка на учитываемых задачах,
+    /// включая неудачные.
+    #[must_use]
+    pub fn total_runs(&self) -> usize {
+        self.tasks.iter().map(|task| task.runs).sum::<usize>() + self.failed_runs
+    }
+
+    /// Возвращает долю запусков, завершившихся ошибкой или тайм-аутом.
+    #[must_use]
+    pub fn failure_rate(&self) -> f64 {
+        let (Ok(failed), Ok(total)) = (
+            u32::try_from(self.failed_runs),
+            u32::try_from(self.total_runs()),
+        ) else {
+            return 0.0;
+        };
+        if total == 0 {
+            return 0.0;
+        }
+        f64::from(failed) / f64::from(total)
+    }
+
+    /// Возвращает лучшую реализацию языка.
+    #[must_use]
+    pub fn best_variant(&self) -> Option<Language> {
+        self.variants.first().map(|variant| variant.name)
+    }
+
+    /// Возвращает разброс показателей между лучшей и худшей реализацией.
+    #[must_use]
+    pub fn variant_spread(&self) -> Option<f64> {
+        let best = self.variants.first()?;
+        let worst = self.variants.last()?;
+        Some(best.score - worst.score)
+    }
+
+    /// Возвращает значение показателя в диапазоне 0..1.
+    #[must_use]
+    pub fn metric(&self, metric: BenchmarkMetric) -> Option<f64> {
+        match metric {
+            BenchmarkMetric::ElapsedTime => Some(self.score),
+            BenchmarkMetric::CpuTime => self.cpu_time_score,
+            BenchmarkMetric::Memory => self.memory_score,
+            BenchmarkMetric::SourceSize => self.source_size_score,
+            BenchmarkMetric::Reliability => Some(1.0 - self.failure_rate()),
+        }
+    }
+}
+
+impl BenchmarkResults {
+    /// Создаёт набор результатов.
+    #[must_use]
+    pub const fn new(languages: FxHashMap<Language, LanguageBenchmark>) -> Self {
+        Self {
+            languages,
+            variants: FxHashMap::with_hasher(FxBuildHasher),
+        }
+    }
+
+    /// Добавляет результаты отдельных реализаций языков.
+    #[must_use]
+    pub fn with_variants(self, variants: FxHashMap<Language, LanguageBenchmark>) -> Self {
+        Self { variants, ..self }
+    }
+
+    /// Возвращает результаты реализации, например `C (Clang)`.
+    #[must_use]
+    pub fn variant(&self, name: Language) -> Option<&LanguageBenchmark> {
+        self.variants.get(&name)
+    }
+
+    /// Возвращает результаты всех реализаций; пусто без
+    /// [`BenchmarkScoring::split_variants`].
+    #[must_use]
+    pub const fn variants(&self) -> &FxHashMap<Language, LanguageBenchmark> {
+        &self.variants
+    }
+
+    /// Возвращает результаты языка.
+    #[must_use]
+    pub fn get(&self, lang: Language) -> Option<&LanguageBenchmark> {
+        self.languages.get(&lang)
+    }
+
+    /// Возвращает результаты всех языков.
+    #[must_use]
+    pub const fn languages(&self) -> &FxHashMap<Language, LanguageBenchmark> {
+        &self.languages
+    }
+
+    /// Возвращает итоговые показатели языков.
+    #[must_use]
+    pub fn scores(&self) -> FxHashMap<Language, f64> {
+        self.languages
+            .iter()
+            .map(|(&lang, benchmark)| (lang, benchmark.score))
+            .collect()
+    }
+
+    /// Возвращает значения показателя по языкам; языки без данных
+    /// пропускаются.
+    #[must_use]
+    pub fn metric_scores(&self, metric: BenchmarkMetric) -> FxHashMap<Language, f64> {
+        self.languages
+            .iter()
+            .filter_map(|(&lang, benchmark)| Some((lang, benchmark.metric(metric)?)))
+            .collect()
+    }
+
+    /// Возвращает количество языков в наборе.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.languages.len()
+    }
+
+    /// Проверяет, что набор не содержит языков.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.languages.is_empty()
+    }
+}
@@ -0,0 +1,449 @@
+use crate::Language;
+use rustc_hash::FxHashMap;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+/// Подробные результаты `TechEmpower` по языкам.
+///
+/// Для каждого языка хранит пропускную способность всех его фреймворков по
+/// тестам и композитные показатели, из которых получен итоговый.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TechEmpowerResults {
+    languages: FxHashMap<Language, LanguageTechEmpower>,
+    filter: TechEmpowerFilter,
+    round: Option<u16>,
+}
+
+/// Изменение показателя языка между двумя раундами `TechEmpower`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct TechEmpowerChange {
+    /// Язык.
+    pub lang: Language,
+    /// Показатель в более раннем раунде; `None`, если язык в нём не оценён.
+    pub previous: Option<f64>,
+    /// Показатель в более позднем раунде; `None`, если язык в нём не оценён.
+    pub current: Option<f64>,
+}
+
+/// Результаты одного языка в `TechEmpower`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct LanguageTechEmpower {
+    /// Итоговый показатель по выбранной [`TechEmpowerAggregation`], от 0 до
+    /// [`TECHEMPOWER_MAX_SCORE`](crate::TECHEMPOWER_MAX_SCORE).
+    pub score: f64,
+    /// Фреймворк с лучшим композитным показателем.
+    pub winner: String,
+    /// Лучший композитный показатель.
+    pub best: f64,
+    /// Среднее лучших [`TechEmpowerScoring::top_k`] композитных показателей.
+    pub top_k_mean: f64,
+    /// Медиана композитных показателей.
+    pub median: f64,
+    /// Фреймворки языка от лучшего композитного показателя к худшему;
+    /// фреймворки без полного набора тестов идут последними.
+    pub frameworks: Vec<FrameworkResult>,
+}
+
+/// Результаты одного фреймворка `TechEmpower`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct FrameworkResult {
+    /// Название реализации из раунда (`name` в `testMetadata`, например
+    /// `actix-pg`); у старых раундов без него — название фреймворка.
+    pub framework: String,
+    /// Класс фреймворка по классификации `TechEmpower`.
+    pub classification: Option<FrameworkClassification>,
+    /// Подход к реализации тестов.
+    pub approach: Option<FrameworkApproach>,
+    /// Вид доступа к базе данных.
+    pub orm: Option<FrameworkOrm>,
+    /// База данных, например `Postgres`.
+    pub database: Option<String>,
+    /// Операционная система, например `Linux`.
+    pub os: Option<String>,
+    /// Запросов в секунду в тесте JSON.
+    pub json: Option<f64>,
+    /// Запросов в секунду в тесте Plaintext.
+    pub plaintext: Option<f64>,
+    /// Запросов в секунду в тесте Single Query.
+    pub db: Option<f64>,
+    /// Запросов в секунду в тесте Multiple Queries.
+    pub query: Option<f64>,
+    /// Запросов в секунду в тесте Fortunes.
+    pub fortune: Option<f64>,
+    /// Запросов в секунду в тесте Updates.
+    pub update: Option<f64>,
+    /// Композитный показатель; `None`, если фреймворк прошёл не все тесты.
+    pub composite: Option<f64>,
+}
+
+/// Стратегия вычисления показателя языка по композитам его фреймворков.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TechEmpowerScoring {
+    /// Способ объединения композитов.
+    pub aggregation: TechEmpowerAggregation,
+    /// Число лучших фреймворков для [`TechEmpowerAggregation::TopKMean`] и
+    /// [`LanguageTechEmpower::top_k_mean`].
+    pub top_k: usize,
+    /// Какие фреймворки учитываются.
+    pub filter: TechEmpowerFilter,
+}
+
+/// Отбор фреймворков `TechEmpower` по метаданным раунда.
+///
+/// Пустой список не ограничивает соответствующее поле; фреймворк проходит
+/// фильтр, если каждое непустое поле содержит его значение. Фреймворки без
+/// значения в ограниченном поле отбрасываются.
+///
+/// ```
+/// use langrank::{FrameworkApproach, FrameworkClassification, TechEmpowerFilter};
+///
+/// let realistic_fullstack = TechEmpowerFilter {
+///     classifications: vec![FrameworkClassification::Fullstack],
+///     approaches: vec![FrameworkApproach::Realistic],
+///     ..TechEmpowerFilter::default()
+/// };
+/// assert_eq!(
+///     realistic_fullstack.to_string(),
+///     "classification=fullstack; approach=realistic"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TechEmpowerFilter {
+    /// Допустимые классы фреймворков.
+    pub classifications: Vec<FrameworkClassification>,
+    /// Допустимые подходы к реализации.
+    pub approaches: Vec<FrameworkApproach>,
+    /// Допустимые виды доступа к базе данных.
+    pub orms: Vec<FrameworkOrm>,
+    /// Допустимые базы данных, без учёта регистра.
+    pub databases: Vec<String>,
+    /// Допустимые операционные системы, без учёта регистра.
+    pub operating_systems: Vec<String>,
+}
+
+/// Класс фреймворка в `TechEmpower`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameworkClassification {
+    /// Полноценный фреймворк с маршрутизацией, шаблонами и ORM.
+    Fullstack,
+    /// Микрофреймворк.
+    Micro,
+    /// Платформа без фреймворка поверх неё, например голый HTTP-сервер.
+    Platform,
+}
+
+/// Подход к реализации тестов `TechEmpower`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameworkApproach {
+    /// Реализация в том виде, в каком её написали бы в продакшене.
+    Realistic,
+    /// Реализация, оптимизированная специально под тест.
+    Stripped,
+}
+
+/// Вид доступа к базе данных в тестах `TechEmpower`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameworkOrm {
+    /// Полноценная ORM.
+    Full,
+    /// Легковесная ORM или построитель запросов.
+    Micro,
+    /// Запросы без ORM.
+    Raw,
+}
+
+/// Способ объединения композитных показателей фреймворков языка.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TechEmpowerAggregation {
+    /// Лучший фреймворк.
+    #[default]
+    Best,
+    /// Среднее лучших [`TechEmpowerScoring::top_k`] фреймворков.
+    TopKMean,
+    /// Медиана всех фреймворков.
+    Median,
+}
+
+impl Default for TechEmpowerScoring {
+    fn default() -> Self {
+        Self {
+            aggregation: TechEmpowerAggregation::Best,
+            top_k: 3,
+            filter: TechEmpowerFilter::default(),
+        }
+    }
+}
+
+impl FrameworkClassification {
+    /// Возвращает стабильное строковое имя класса.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Fullstack => "fullstack",
+            Self::Micro => "micro",
+            Self::Platform => "platform",
+        }
+    }
+}
+
+impl FrameworkApproach {
+    /// Возвращает стабильное строковое имя подхода.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Realistic => "realistic",
+            Self::Stripped => "stripped",
+        }
+    }
+}
+
+impl FrameworkOrm {
+    /// Возвращает стабильное строковое имя вида доступа.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Micro => "micro",
+            Self::Raw => "raw",
+        }
+    }
+}
+
+impl Serialize for FrameworkClassification {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl Serialize for FrameworkApproach {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl Serialize for FrameworkOrm {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl fmt::Display for FrameworkClassification {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for FrameworkApproach {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for FrameworkOrm {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl TechEmpowerFilter {
+    /// Проверяет, что фильтр пропускает все фреймворки.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.classifications.is_empty()
+            && self.approaches.is_empty()
+            && self.orms.is_empty()
+            && self.databases.is_empty()
+            && self.operating_systems.is_empty()
+    }
+
+    /// Проверяет, что фреймворк проходит фильтр.
+    #[must_use]
+    pub fn matches(&self, framework: &FrameworkResult) -> bool {
+        allows(&self.classifications, framework.classification.as_ref())
+            && allows(&self.approaches, framework.approach.as_ref())
+            && allows(&self.orms, framework.orm.as_ref())
+            && allows_name(&self.databases, framework.database.as_deref())
+            && allows_name(&self.operating_systems, framework.os.as_deref())
+    }
+}
+
+fn allows<T: PartialEq>(allowed: &[T], value: Option<&T>) -> bool {
+    allowed.is_empty() || value.is_some_and(|value| allowed.contains(value))
+}
+
+fn allows_name(allowed: &[String], value: Option<&str>) -> bool {
+    allowed.is_empty()
+        || value.is_some_and(|value| {
+            allowed
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(value))
+        })
+}
+
+/// Выводит фильтр как `поле=значение,значение; …` или `all frameworks`
+/// для пустого фильтра.
+impl fmt::Display for TechEmpowerFilter {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return formatter.write_str("all frameworks");
+        }
+        let fields = [
+            ("classification", join(&self.classifications)),
+            ("approach", join(&self.approaches)),
+            ("orm", join(&self.orms)),
+            ("database", join(&self.databases)),
+            ("os", join(&self.operating_systems)),
+        ];
+        let mut separator = "";
+        for (name, values) in fields {
+            if values.is_empty() {
+                continue;
+            }
+            write!(formatter, "{separator}{name}={values}")?;
+            separator = "; ";
+        }
+        Ok(())
+    }
+}
+
+fn join<T: fmt::Display>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+impl LanguageTechEmpower {
+    /// Возвращает показатель по указанному способу объединения.
+    #[must_use]
+    pub const fn aggregate(&self, aggregation: TechEmpowerAggregation) -> f64 {
+        match aggregation {
+            TechEmpowerAggregation::Best => self.best,
+            TechEmpowerAggregation::TopKMean => self.top_k_mean,
+            TechEmpowerAggregation::Median => self.median,
+        }
+    }
+}
+
+impl TechEmpowerChange {
+    /// Возвращает изменение показателя; `None`, если язык оценён не в обоих
+    /// раундах.
+    #[must_use]
+    pub fn delta(&self) -> Option<f64> {
+        Some(self.current? - self.previous?)
+    }
+}
+
+impl TechEmpowerResults {
+    /// Создаёт набор результатов.
+    #[must_use]
+    pub const fn new(languages: FxHashMap<Language, LanguageTechEmpower>) -> Self {
+        Self {
+            languages,
+            filter: TechEmpowerFilter {
+                classifications: Vec::new(),
+                approaches: Vec::new(),
+                orms: Vec::new(),
+                databases: Vec::new(),
+                operating_systems: Vec::new(),
+            },
+            round: None,
+        }
+    }
+
+    /// Запоминает номер раунда, из которого получены результаты.
+    #[must_use]
+    pub fn with_round(self, round: u16) -> Self {
+        Self {
+            round: Some(round),
+            ..self
+        }
+    }
+
+    /// Возвращает номер раунда, из которого получены результаты.
+    #[must_use]
+    pub const fn round(&self) -> Option<u16> {
+        self.round
+    }
+
+    /// Сравнивает показатели языков с более ранними результатами.
+    ///
+    /// Изменения упорядочены от наибольшего роста к наибольшему падению;
+    /// языки, оценённые только в одном из наборов, идут последними.
+    #[must_use]
+    pub fn compare(&self, previous: &Self) -> Vec<TechEmpowerChange> {
+        let mut languages: Vec<Language> = self
+            .languages
+            .keys()
+            .chain(previous.languages.keys())
+            .copied()
+            .collect();
+        languages.sort_unstable();
+        languages.dedup();
+        let mut changes: Vec<TechEmpowerChange> = languages
+            .into_iter()
+            .map(|lang| TechEmpowerChange {
+                lang,
+                previous: previous.get(lang).map(|result| result.score),
+                current: self.get(lang).map(|result| result.score),
+            })
+            .collect();
+        changes.sort_by(|left, right| {
+            let left_delta = left.delta().unwrap_or(f64::NEG_INFINITY);
+            let right_delta = right.delta().unwrap_or(f64::NEG_INFINITY);
+            right_delta
+                .total_cmp(&left_delta)
+                .then_with(|| left.lang.cmp(&right.lang))
+        });
+        changes
+    }
+
+    /// Запоминает фильтр, которым отобраны фреймворки.
+    #[must_use]
+    pub fn with_filter(self, filter: TechEmpowerFilter) -> Self {
+        Self { filter, ..self }
+    }
+
+    /// Возвращает фильтр, которым отобраны фреймворки.
+    #[must_use]
+    pub const fn filter(&self) -> &TechEmpowerFilter {
+        &self.filter
+    }
+
+    /// Возвращает результаты языка.
+    #[must_use]
+    pub fn get(&self, lang: Language) -> Option<&LanguageTechEmpower> {
+        self.languages.get(&lang)
+    }
+
+    /// Возвращает результаты всех языков.
+    #[must_use]
+    pub const fn languages(&self) -> &FxHashMap<Language, LanguageTechEmpower> {
+        &self.languages
+    }
+
+    /// Возвращает итоговые показатели языков.
+    #[must_use]
+    pub fn scores(&self) -> FxHashMap<Language, f64> {
+        self.languages
+            .iter()
+            .map(|(&lang, result)| (lang, result.score))
+            .collect()
+    }
+
+    /// Возвращает количество языков в наборе.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.languages.len()
+    }
+
+    /// Проверяет, что набор не содержит языков.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.languages.is_empty()
+    }
+}
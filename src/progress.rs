@@ -1,4 +1,3 @@
-use anyhow::Result;
 use colored::Colorize;
 use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
 use std::future::Future;
@@ -95,12 +94,12 @@ fn format_stage_message(stage: Stage, label: &str) -> String {
     )
 }
 
-pub async fn run_with_spinner<T>(
+pub async fn run_with_spinner<T, E>(
     progress: &ProgressState,
     stage: Stage,
     label: &str,
-    fut: impl Future<Output = Result<T>>,
-) -> Result<T> {
+    fut: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
     let message = format_stage_message(stage, label);
     let bar = progress.spinner(message);
     let result = fut.await;
@@ -0,0 +1,30 @@
+use crate::{RankingEntry, Transport};
+use anyhow::Result;
+use std::future::Future;
+use std::pin::Pin;
+
+/// Future, возвращаемый [`RankingProvider::fetch`].
+pub type ProviderFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<RankingEntry>>> + Send + 'a>>;
+
+/// Пользовательский источник рейтинга популярности.
+///
+/// Провайдер регистрируется в [`Fetcher`](crate::Fetcher) и загружается через
+/// тот же транспорт, что и встроенные источники. Его записи проходят ту же
+/// нормализацию имён языков и проверку минимального количества записей, что и
+/// TIOBE, PYPL и Languish.
+///
+/// Языки записей получаются через
+/// [`AliasRegistry::canonicalize`](crate::AliasRegistry::canonicalize); загрузчик
+/// повторно сводит их имена через собственный реестр, поэтому провайдеру
+/// достаточно [`AliasRegistry::empty`](crate::AliasRegistry::empty), чтобы
+/// передать имена как есть.
+pub trait RankingProvider: Send + Sync {
+    /// Возвращает стабильный идентификатор источника, например `"redmonk"`.
+    ///
+    /// Идентификатор используется в [`RankingSource::Custom`](crate::RankingSource::Custom)
+    /// и в сообщениях об ошибках.
+    fn id(&self) -> &'static str;
+
+    /// Загружает записи рейтинга.
+    fn fetch<'a>(&'a self, transport: &'a dyn Transport) -> ProviderFuture<'a>;
+}
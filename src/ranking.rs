@@ -1,27 +1,45 @@
-use serde::Serialize;
+mod languish;
+mod tiobe;
+
+pub use languish::{
+    CoreWeights, LanguishHistory, LanguishQuarter, LanguishScoring, LanguishSignal, LanguishSignals,
+};
+pub use tiobe::{TiobeAward, TiobeHistory};
+
+use crate::Language;
+use rustc_hash::FxHashMap;
+use serde::{Serialize, Serializer};
 use std::fmt;
 
 /// Запись рейтинга одного языка.
+///
+/// Вне крейта запись создаётся через [`RankingEntry::new`] и методы `with_*`.
 #[derive(Debug, Serialize, Clone, PartialEq)]
+#[non_exhaustive]
 pub struct RankingEntry {
-    /// Каноническое имя языка программирования.
-    pub lang: String,
+    /// Канонический язык программирования.
+    pub lang: Language,
     /// Позиция в исходном рейтинге, если источник её предоставляет.
     pub rank: Option<u32>,
-    /// Доля или нормализованный показатель источника.
-    pub share: f64,
+    /// Доля или нормализованный показатель источника; `None`, если источник
+    /// публикует для языка только место.
+    pub share: Option<f64>,
     /// Изменение показателя, если источник его предоставляет.
     pub trend: Option<f64>,
+    /// Место в прошлогоднем рейтинге, если источник его публикует (сейчас
+    /// только топ-20 TIOBE).
+    pub previous_rank: Option<u32>,
 }
 
 /// Источник рейтинга популярности.
-#[derive(Debug, Serialize, Copy, Clone, Eq, PartialEq, Hash)]
-#[serde(rename_all = "lowercase")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 #[non_exhaustive]
 pub enum RankingSource {
     Tiobe,
     Pypl,
     Languish,
+    /// Источник, зарегистрированный через [`RankingProvider`](crate::RankingProvider).
+    Custom(&'static str),
 }
 
 impl RankingSource {
@@ -32,26 +50,125 @@ impl RankingSource {
             Self::Tiobe => "tiobe",
             Self::Pypl => "pypl",
             Self::Languish => "languish",
+            Self::Custom(id) => id,
         }
     }
 }
 
+impl Serialize for RankingSource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
 impl fmt::Display for RankingSource {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         formatter.write_str(self.as_str())
     }
 }
 
+/// Отдельный бюллетень источника в ранжировании Шульце.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SubBallot {
+    /// Стабильное имя бюллетеня внутри источника, например `issues`.
+    pub id: &'static str,
+    /// Показатели языков: больше — лучше; языки без показателя идут последними.
+    pub scores: FxHashMap<Language, f64>,
+}
+
+impl SubBallot {
+    /// Создаёт бюллетень с указанным именем.
+    #[must_use]
+    pub const fn new(id: &'static str, scores: FxHashMap<Language, f64>) -> Self {
+        Self { id, scores }
+    }
+}
+
 /// Нормализованный набор записей из одного источника.
 #[derive(Debug, Serialize, Clone, PartialEq)]
 pub struct RankingDataset {
     source: RankingSource,
     entries: Vec<RankingEntry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    sub_ballots: Vec<SubBallot>,
+}
+
+impl RankingEntry {
+    /// Создаёт запись языка без места, доли и тренда.
+    ///
+    /// ```
+    /// use langrank::{AliasRegistry, RankingEntry};
+    ///
+    /// let rust = AliasRegistry::builtin().canonicalize("rust").expect("known language");
+    /// let entry = RankingEntry::new(rust).with_rank(1).with_share(12.5);
+    /// assert_eq!(entry.rank, Some(1));
+    /// assert_eq!(entry.trend, None);
+    /// ```
+    #[must_use]
+    pub const fn new(lang: Language) -> Self {
+        Self {
+            lang,
+            rank: None,
+            share: None,
+            trend: None,
+            previous_rank: None,
+        }
+    }
+
+    /// Задаёт позицию в исходном рейтинге.
+    #[must_use]
+    pub const fn with_rank(mut self, rank: u32) -> Self {
+        self.rank = Some(rank);
+        self
+    }
+
+    /// Задаёт долю или нормализованный показатель источника.
+    #[must_use]
+    pub const fn with_share(mut self, share: f64) -> Self {
+        self.share = Some(share);
+        self
+    }
+
+    /// Задаёт изменение показателя.
+    #[must_use]
+    pub const fn with_trend(mut self, trend: f64) -> Self {
+        self.trend = Some(trend);
+        self
+    }
+
+    /// Задаёт место в прошлогоднем рейтинге.
+    #[must_use]
+    pub const fn with_previous_rank(mut self, previous_rank: u32) -> Self {
+        self.previous_rank = Some(previous_rank);
+        self
+    }
 }
 
 impl RankingDataset {
-    pub(crate) const fn new(source: RankingSource, entries: Vec<RankingEntry>) -> Self {
-        Self { source, entries }
+    /// Создаёт набор записей указанного источника.
+    #[must_use]
+    pub const fn new(source: RankingSource, entries: Vec<RankingEntry>) -> Self {
+        Self {
+            source,
+            entries,
+            sub_ballots: Vec::new(),
+        }
+    }
+
+    /// Добавляет отдельные бюллетени источника. Набор с ними голосует в
+    /// ранжировании Шульце каждым из этих бюллетеней вместо одного по долям.
+    #[must_use]
+    pub fn with_sub_ballots(mut self, sub_ballots: Vec<SubBallot>) -> Self {
+        self.sub_ballots = sub_ballots;
+        self
+    }
+
+    /// Возвращает отдельные бюллетени источника; пусто, если источник
+    /// голосует одним бюллетенем.
+    #[must_use]
+    pub fn sub_ballots(&self) -> &[SubBallot] {
+        &self.sub_ballots
     }
 
     /// Возвращает источник набора.
@@ -123,39 +240,66 @@ pub fn reconcile_pypl_with_tiobe(tiobe: &[RankingEntry], pypl: &mut Vec<RankingE
         return;
     };
 
+    let (Some(c_share), Some(cpp_share)) = (c_data.share, cpp_data.share) else {
+        return;
+    };
+
     let combined = pypl.remove(position);
-    let share_sum = c_data.share + cpp_data.share;
+    let share_sum = c_share + cpp_share;
     if !share_sum.is_finite() || share_sum <= f64::EPSILON {
         pypl.push(combined);
-        pypl.sort_by(|left, right| left.lang.cmp(&right.lang));
+        pypl.sort_by_key(|entry| entry.lang);
         return;
     }
 
-    let cpp_ratio = cpp_data.share / share_sum;
-    let entries = [("C++", cpp_ratio), ("C", 1.0 - cpp_ratio)];
+    let cpp_ratio = cpp_share / share_sum;
+    let entries = [(cpp_data.lang, cpp_ratio), (c_data.lang, 1.0 - cpp_ratio)];
 
     for (lang, ratio) in entries {
         pypl.push(RankingEntry {
-            lang: lang.to_owned(),
+            lang,
             rank: combined.rank,
-            share: combined.share * ratio,
+            share: combined.share.map(|value| value * ratio),
             trend: combined.trend.map(|value| value * ratio),
+            previous_rank: combined.previous_rank,
         });
     }
 
-    pypl.sort_by(|left, right| left.lang.cmp(&right.lang));
+    pypl.sort_by_key(|entry| entry.lang);
+}
+
+/// Согласует PYPL с TIOBE, если оба источника присутствуют среди наборов.
+#[allow(clippy::redundant_pub_crate)]
+pub(crate) fn reconcile_datasets(datasets: &mut [RankingDataset]) {
+    let find = |datasets: &[RankingDataset], source| {
+        datasets.iter().position(|dataset| dataset.source == source)
+    };
+    let (Some(tiobe), Some(pypl)) = (
+        find(datasets, RankingSource::Tiobe),
+        find(datasets, RankingSource::Pypl),
+    ) else {
+        return;
+    };
+
+    let mut pypl_entries = std::mem::take(&mut datasets[pypl].entries);
+    reconcile_pypl_with_tiobe(&datasets[tiobe].entries, &mut pypl_entries);
+    datasets[pypl].entries = pypl_entries;
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{RankingDataset, RankingEntry, RankingSource, reconcile_pypl_with_tiobe};
+    use super::{
+        RankingDataset, RankingEntry, RankingSource, reconcile_datasets, reconcile_pypl_with_tiobe,
+    };
+    use crate::Language;
 
     fn entry(lang: &str, share: f64, trend: Option<f64>) -> RankingEntry {
         RankingEntry {
-            lang: lang.to_owned(),
+            lang: Language::intern(lang),
             rank: Some(1),
-            share,
+            share: Some(share),
             trend,
+            previous_rank: None,
         }
     }
 
@@ -183,6 +327,23 @@ mod tests {
         assert_eq!(pypl, [combined]);
     }
 
+    #[test]
+    fn reconciles_pypl_dataset_regardless_of_order() {
+        let mut datasets = [
+            RankingDataset::new(RankingSource::Pypl, vec![entry("C/C++", 5.0, None)]),
+            RankingDataset::new(
+                RankingSource::Tiobe,
+                vec![entry("C", 6.0, None), entry("C++", 4.0, None)],
+            ),
+        ];
+
+        reconcile_datasets(&mut datasets);
+
+        let langs: Vec<&str> = datasets[0].iter().map(|entry| entry.lang.name()).collect();
+        assert_eq!(langs, ["C", "C++"]);
+        assert_eq!(datasets[1].len(), 2);
+    }
+
     #[test]
     fn dataset_supports_borrowed_and_owned_iteration() {
         let dataset = RankingDataset::new(
@@ -196,7 +357,7 @@ mod tests {
         assert_eq!(
             dataset
                 .iter()
-                .map(|entry| entry.lang.as_str())
+                .map(|entry| entry.lang.name())
                 .collect::<Vec<_>>(),
             ["Rust", "Go"]
         );
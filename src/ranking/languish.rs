@@ -0,0 +1,497 @@
+use super::{RankingDataset, RankingEntry, RankingSource, SubBallot};
+use crate::Language;
+use rustc_hash::FxHashMap;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::ops::AddAssign;
+
+/// Сигнал популярности Languish.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LanguishSignal {
+    /// Issues на GitHub.
+    Issues,
+    /// Pull request на GitHub.
+    Pulls,
+    /// Вопросы на Stack Overflow.
+    SoQuestions,
+    /// Звёзды на GitHub.
+    Stars,
+}
+
+/// Веса сигналов Languish в итоговом показателе языка.
+///
+/// По умолчанию, в отличие от самого Languish, все четыре сигнала учитываются
+/// с одинаковым весом. Сигналы с нулевым весом не учитываются.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoreWeights {
+    /// Вес доли issues на GitHub.
+    pub issues: f64,
+    /// Вес доли pull request на GitHub.
+    pub pulls: f64,
+    /// Вес доли вопросов на Stack Overflow.
+    pub so_questions: f64,
+    /// Вес доли звёзд на GitHub.
+    pub stars: f64,
+}
+
+/// Стратегия вычисления рейтинга Languish.
+///
+/// ```
+/// use langrank::{CoreWeights, LanguishScoring};
+///
+/// let year_over_year = LanguishScoring {
+///     weights: CoreWeights {
+///         stars: 0.0,
+///         ..CoreWeights::default()
+///     },
+///     trend_quarters: 4,
+///     ..LanguishScoring::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LanguishScoring {
+    /// Веса сигналов.
+    pub weights: CoreWeights,
+    /// Через сколько календарных кварталов сравнивается показатель для
+    /// тренда: 1 — квартал к кварталу (по умолчанию), 4 — год к году; 0
+    /// отключает тренд. Если нужного квартала нет в данных, тренда нет.
+    pub trend_quarters: usize,
+    /// Голосует каждым сигналом отдельным бюллетенем Шульце вместо одного
+    /// бюллетеня по взвешенному показателю. Веса на эти бюллетени не влияют.
+    pub signal_ballots: bool,
+}
+
+/// Доли языка в сигналах Languish за квартал, в процентах от суммы по всем
+/// языкам.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct LanguishSignals {
+    /// Доля issues на GitHub.
+    pub issues: f64,
+    /// Доля pull request на GitHub.
+    pub pulls: f64,
+    /// Доля вопросов на Stack Overflow.
+    pub so_questions: f64,
+    /// Доля звёзд на GitHub.
+    pub stars: f64,
+}
+
+/// Значения языка в Languish за один квартал.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct LanguishQuarter {
+    /// Квартал, например `2024Q2`.
+    pub quarter: String,
+    /// Доли языка в сигналах.
+    pub signals: LanguishSignals,
+    /// Взвешенное среднее долей по [`CoreWeights`], в процентах.
+    pub score: f64,
+}
+
+/// Поквартальная история Languish по языкам.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LanguishHistory {
+    quarters: Vec<String>,
+    languages: FxHashMap<Language, Vec<LanguishQuarter>>,
+}
+
+impl Default for CoreWeights {
+    fn default() -> Self {
+        Self {
+            issues: 1.0,
+            pulls: 1.0,
+            so_questions: 1.0,
+            stars: 1.0,
+        }
+    }
+}
+
+impl Default for LanguishScoring {
+    fn default() -> Self {
+        Self {
+            weights: CoreWeights::default(),
+            trend_quarters: 1,
+            signal_ballots: false,
+        }
+    }
+}
+
+impl LanguishScoring {
+    /// Возвращает квартал, с которым сравнивается `quarter` для тренда,
+    /// например `2023Q2` для `2024Q2` при `trend_quarters = 4`.
+    ///
+    /// Возвращает `None`, если тренд отключён или `quarter` не имеет вида
+    /// `2024Q2`.
+    #[must_use]
+    pub fn trend_quarter(self, quarter: &str) -> Option<String> {
+        if self.trend_quarters == 0 {
+            return None;
+        }
+        let (year, number) = quarter.split_once('Q')?;
+        let year: usize = year.parse().ok()?;
+        let number: usize = number.parse().ok()?;
+        if !(1..=4).contains(&number) {
+            return None;
+        }
+        let index = (year * 4 + number - 1).checked_sub(self.trend_quarters)?;
+        Some(format!("{}Q{}", index / 4, index % 4 + 1))
+    }
+}
+
+impl LanguishSignal {
+    /// Все сигналы в порядке колонок Languish.
+    pub const ALL: [Self; 4] = [Self::Issues, Self::Pulls, Self::SoQuestions, Self::Stars];
+
+    /// Возвращает машинное имя сигнала.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Issues => "issues",
+            Self::Pulls => "pulls",
+            Self::SoQuestions => "so-questions",
+            Self::Stars => "stars",
+        }
+    }
+}
+
+impl Serialize for LanguishSignal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl fmt::Display for LanguishSignal {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl LanguishSignals {
+    /// Превращает доли языков в бюллетени Шульце, по одному на сигнал в
+    /// порядке [`LanguishSignal::ALL`].
+    #[must_use]
+    pub fn ballots(signals: &FxHashMap<Language, Self>) -> Vec<SubBallot> {
+        LanguishSignal::ALL
+            .into_iter()
+            .map(|signal| {
+                let scores = signals
+                    .iter()
+                    .map(|(&lang, shares)| (lang, shares.get(signal)))
+                    .collect();
+                SubBallot::new(signal.as_str(), scores)
+            })
+            .collect()
+    }
+
+    /// Возвращает долю указанного сигнала.
+    #[must_use]
+    pub const fn get(&self, signal: LanguishSignal) -> f64 {
+        match signal {
+            LanguishSignal::Issues => self.issues,
+            LanguishSignal::Pulls => self.pulls,
+            LanguishSignal::SoQuestions => self.so_questions,
+            LanguishSignal::Stars => self.stars,
+        }
+    }
+}
+
+impl AddAssign for LanguishSignals {
+    fn add_assign(&mut self, other: Self) {
+        self.issues += other.issues;
+        self.pulls += other.pulls;
+        self.so_questions += other.so_questions;
+        self.stars += other.stars;
+    }
+}
+
+impl CoreWeights {
+    /// Возвращает сумму весов.
+    #[must_use]
+    pub const fn total(self) -> f64 {
+        self.issues + self.pulls + self.so_questions + self.stars
+    }
+
+    /// Возвращает взвешенное среднее долей сигналов, в процентах.
+    #[must_use]
+    pub fn score(self, signals: &LanguishSignals) -> f64 {
+        let total_weight = self.total();
+        if total_weight <= f64::EPSILON {
+            return 0.0;
+        }
+        let weighted = [
+            (self.issues, signals.issues),
+            (self.pulls, signals.pulls),
+            (self.so_questions, signals.so_questions),
+            (self.stars, signals.stars),
+        ]
+        .into_iter()
+        .filter(|&(weight, share)| weight > 0.0 && share > 0.0)
+        .fold(0.0_f64, |sum, (weight, share)| share.mul_add(weight, sum));
+        weighted / total_weight
+    }
+}
+
+impl LanguishQuarter {
+    /// Создаёт значения языка за квартал.
+    #[must_use]
+    pub fn new(quarter: impl Into<String>, signals: LanguishSignals, score: f64) -> Self {
+        Self {
+            quarter: quarter.into(),
+            signals,
+            score,
+        }
+    }
+}
+
+impl LanguishHistory {
+    /// Создаёт историю из кварталов по возрастанию и рядов языков, выровненных
+    /// по этим кварталам.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если ряд какого-либо языка не совпадает с `quarters` по длине
+    /// или меткам кварталов.
+    #[must_use]
+    pub fn new(
+        quarters: Vec<String>,
+        languages: FxHashMap<Language, Vec<LanguishQuarter>>,
+    ) -> Self {
+        for (lang, series) in &languages {
+            assert!(
+                series
+                    .iter()
+                    .map(|value| value.quarter.as_str())
+                    .eq(quarters.iter().map(String::as_str)),
+                "Languish series of {lang} is not aligned with the history quarters"
+            );
+        }
+        Self {
+            quarters,
+            languages,
+        }
+    }
+
+    /// Возвращает кварталы истории по возрастанию.
+    #[must_use]
+    pub fn quarters(&self) -> &[String] {
+        &self.quarters
+    }
+
+    /// Возвращает ряд языка: по значению на каждый квартал из
+    /// [`Self::quarters`].
+    #[must_use]
+    pub fn get(&self, lang: Language) -> Option<&[LanguishQuarter]> {
+        self.languages.get(&lang).map(Vec::as_slice)
+    }
+
+    /// Возвращает ряды всех языков.
+    #[must_use]
+    pub const fn languages(&self) -> &FxHashMap<Language, Vec<LanguishQuarter>> {
+        &self.languages
+    }
+
+    /// Возвращает рейтинг Languish за квартал: все языки истории по убыванию
+    /// показателя, включая языки с нулевым показателем, тренд — изменение за
+    /// [`LanguishScoring::trend_quarters`] кварталов. С
+    /// [`LanguishScoring::signal_ballots`] набор несёт бюллетени сигналов.
+    ///
+    /// Показатель квартала уже взвешен при загрузке истории, поэтому
+    /// [`LanguishScoring::weights`] здесь не применяются.
+    ///
+    /// Возвращает `None`, если квартала нет в истории.
+    #[must_use]
+    pub fn quarter_dataset(
+        &self,
+        quarter: &str,
+        scoring: LanguishScoring,
+    ) -> Option<RankingDataset> {
+        let index = self.quarter_index(quarter)?;
+        let previous = scoring
+            .trend_quarter(quarter)
+            .and_then(|previous| self.quarter_index(&previous));
+        let mut scored: Vec<(Language, f64, Option<f64>)> = self
+            .languages
+            .iter()
+            .map(|(&lang, series)| {
+                let score = series[index].score;
+                let trend = previous.map(|previous| score - series[previous].score);
+                (lang, score, trend)
+            })
+            .collect();
+        scored.sort_by(|left, right| {
+            right
+                .1
+                .total_cmp(&left.1)
+                .then_with(|| left.0.cmp(&right.0))
+        });
+        let entries = (1_u32..)
+            .zip(scored)
+            .map(|(rank, (lang, share, trend))| RankingEntry {
+                lang,
+                rank: Some(rank),
+                share: Some(share),
+                trend,
+                previous_rank: None,
+            })
+            .collect();
+        let dataset = RankingDataset::new(RankingSource::Languish, entries);
+        if !scoring.signal_ballots {
+            return Some(dataset);
+        }
+        let signals = self.quarter_signals(quarter)?;
+        Some(dataset.with_sub_ballots(LanguishSignals::ballots(&signals)))
+    }
+
+    /// Возвращает доли языков по сигналам за квартал, например для
+    /// [`LanguishSignals::ballots`].
+    ///
+    /// Возвращает `None`, если квартала нет в истории.
+    #[must_use]
+    pub fn quarter_signals(&self, quarter: &str) -> Option<FxHashMap<Language, LanguishSignals>> {
+        let index = self.quarter_index(quarter)?;
+        Some(
+            self.languages
+                .iter()
+                .map(|(&lang, series)| (lang, series[index].signals))
+                .filter(|(_, signals)| *signals != LanguishSignals::default())
+                .collect(),
+        )
+    }
+
+    fn quarter_index(&self, quarter: &str) -> Option<usize> {
+        self.quarters.iter().position(|known| known == quarter)
+    }
+
+    /// Возвращает количество языков в истории.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.languages.len()
+    }
+
+    /// Проверяет, что история не содержит языков.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.languages.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LanguishHistory, LanguishQuarter, LanguishScoring, LanguishSignals};
+    use crate::Language;
+    use rustc_hash::FxHashMap;
+
+    fn series(scores: &[f64]) -> Vec<LanguishQuarter> {
+        ["2024Q1", "2024Q2"]
+            .iter()
+            .zip(scores)
+            .map(|(&quarter, &score)| {
+                let signals = LanguishSignals {
+                    issues: score,
+                    ..LanguishSignals::default()
+                };
+                LanguishQuarter::new(quarter, signals, score)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn builds_quarter_datasets_from_history() {
+        let languages: FxHashMap<Language, Vec<LanguishQuarter>> = [
+            (Language::intern("Rust"), series(&[2.0, 5.0])),
+            (Language::intern("Go"), series(&[4.0, 3.0])),
+            (Language::intern("Zig"), series(&[0.0, 1.0])),
+        ]
+        .into_iter()
+        .collect();
+        let history = LanguishHistory::new(vec!["2024Q1".into(), "2024Q2".into()], languages);
+        let scoring = LanguishScoring::default();
+
+        let first = history
+            .quarter_dataset("2024Q1", scoring)
+            .expect("known quarter");
+        let order: Vec<&str> = first.iter().map(|entry| entry.lang.name()).collect();
+        assert_eq!(order, ["Go", "Rust", "Zig"]);
+        assert_eq!(first.entries()[0].trend, None);
+
+        let latest = history
+            .quarter_dataset("2024Q2", scoring)
+            .expect("known quarter");
+        let order: Vec<&str> = latest.iter().map(|entry| entry.lang.name()).collect();
+        assert_eq!(order, ["Rust", "Go", "Zig"]);
+        assert_eq!(latest.entries()[0].rank, Some(1));
+        assert!(
+            latest.entries()[0]
+                .trend
+                .is_some_and(|trend| (trend - 3.0).abs() < 1e-9)
+        );
+
+        assert!(latest.sub_ballots().is_empty());
+
+        let signals = history.quarter_signals("2024Q1").expect("known quarter");
+        assert_eq!(signals.len(), 2);
+        assert!(history.quarter_dataset("2011Q4", scoring).is_none());
+
+        let ballots = LanguishScoring {
+            trend_quarters: 0,
+            signal_ballots: true,
+            ..scoring
+        };
+        let latest = history
+            .quarter_dataset("2024Q2", ballots)
+            .expect("known quarter");
+        assert_eq!(latest.entries()[0].trend, None);
+        assert_eq!(latest.sub_ballots().len(), 4);
+
+        // В истории с пропуском соседняя строка не подменяет 2024Q1.
+        let relabeled = history
+            .languages()
+            .iter()
+            .map(|(&lang, series)| {
+                let series = ["2023Q3", "2024Q2"]
+                    .iter()
+                    .zip(series)
+                    .map(|(&quarter, value)| {
+                        LanguishQuarter::new(quarter, value.signals, value.score)
+                    })
+                    .collect();
+                (lang, series)
+            })
+            .collect();
+        let gapped = LanguishHistory::new(vec!["2023Q3".into(), "2024Q2".into()], relabeled);
+        let latest = gapped
+            .quarter_dataset("2024Q2", scoring)
+            .expect("known quarter");
+        assert_eq!(latest.entries()[0].trend, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "not aligned")]
+    fn rejects_series_misaligned_with_quarters() {
+        let languages = std::iter::once((Language::intern("Rust"), series(&[1.0, 2.0]))).collect();
+        let _ = LanguishHistory::new(vec!["2024Q2".into()], languages);
+    }
+
+    #[test]
+    fn trend_quarter_counts_calendar_quarters() {
+        let scoring = |trend_quarters| LanguishScoring {
+            trend_quarters,
+            ..LanguishScoring::default()
+        };
+
+        assert_eq!(
+            scoring(1).trend_quarter("2024Q1").as_deref(),
+            Some("2023Q4")
+        );
+        assert_eq!(
+            scoring(4).trend_quarter("2024Q2").as_deref(),
+            Some("2023Q2")
+        );
+        assert_eq!(
+            scoring(6).trend_quarter("2024Q2").as_deref(),
+            Some("2022Q4")
+        );
+        assert_eq!(scoring(0).trend_quarter("2024Q2"), None);
+        assert_eq!(scoring(1).trend_quarter("2024Q5"), None);
+        assert_eq!(scoring(1).trend_quarter("latest"), None);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::Language;
+use rustc_hash::FxHashMap;
+use serde::Serialize;
+
+/// Победитель «Зала славы» TIOBE: язык с наибольшим ростом рейтинга за год.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct TiobeAward {
+    /// Год награды.
+    pub year: u16,
+    /// Язык-победитель.
+    pub lang: Language,
+}
+
+/// Долгосрочная история TIOBE: места языков в таблице «Very Long Term
+/// History» и победители «Зала славы».
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TiobeHistory {
+    years: Vec<u16>,
+    positions: FxHashMap<Language, Vec<Option<u32>>>,
+    hall_of_fame: Vec<TiobeAward>,
+}
+
+impl TiobeHistory {
+    /// Создаёт историю из лет по возрастанию, мест языков, выровненных по этим
+    /// годам, и победителей «Зала славы».
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если число мест какого-либо языка не совпадает с числом лет.
+    #[must_use]
+    pub fn new(
+        years: Vec<u16>,
+        positions: FxHashMap<Language, Vec<Option<u32>>>,
+        hall_of_fame: Vec<TiobeAward>,
+    ) -> Self {
+        for (lang, places) in &positions {
+            assert_eq!(
+                places.len(),
+                years.len(),
+                "TIOBE positions of {lang} are not aligned with the history years"
+            );
+        }
+        Self {
+            years,
+            positions,
+            hall_of_fame,
+        }
+    }
+
+    /// Возвращает годы истории по возрастанию.
+    #[must_use]
+    pub fn years(&self) -> &[u16] {
+        &self.years
+    }
+
+    /// Возвращает места языка: по значению на каждый год из [`Self::years`];
+    /// `None` — язык в тот год не попал в таблицу.
+    #[must_use]
+    pub fn get(&self, lang: Language) -> Option<&[Option<u32>]> {
+        self.positions.get(&lang).map(Vec::as_slice)
+    }
+
+    /// Возвращает места всех языков.
+    #[must_use]
+    pub const fn positions(&self) -> &FxHashMap<Language, Vec<Option<u32>>> {
+        &self.positions
+    }
+
+    /// Возвращает победителей «Зала славы» по возрастанию года.
+    #[must_use]
+    pub fn hall_of_fame(&self) -> &[TiobeAward] {
+        &self.hall_of_fame
+    }
+
+    /// Возвращает количество языков в истории мест.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Проверяет, что история мест не содержит языков.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+}
@@ -1,10 +1,11 @@
 use crate::formatting::{
-    format_optional_float, format_optional_rank, format_perf_score, format_trend_with_class,
+    format_error_chain, format_optional_float, format_optional_rank, format_perf_score,
+    format_source_count, format_sub_ballot_label, format_trend_with_class,
 };
-use crate::schulze::SchulzeRecord;
 use crate::write_output_file;
 use anyhow::Result;
 use chrono::{DateTime, Local};
+use langrank::{FetchError, SchulzeRecord, SubBallotRank, TechEmpowerFilter};
 use maud::{DOCTYPE, Markup, PreEscaped, html};
 use minify_html::{Cfg, minify};
 use std::path::Path;
@@ -16,13 +17,16 @@ pub struct HtmlReportPaths<'a> {
 }
 
 pub struct HtmlReportContext<'a> {
-    pub(crate) tiobe_count: usize,
-    pub(crate) pypl_count: usize,
-    pub(crate) languish_count: usize,
-    pub(crate) benchmark_lang_count: usize,
-    pub(crate) techempower_lang_count: usize,
+    pub(crate) tiobe_count: Option<usize>,
+    pub(crate) pypl_count: Option<usize>,
+    pub(crate) languish_count: Option<usize>,
+    pub(crate) benchmark_lang_count: Option<usize>,
+    pub(crate) techempower_lang_count: Option<usize>,
+    pub(crate) techempower_filter: &'a TechEmpowerFilter,
+    pub(crate) techempower_round: Option<u16>,
     pub(crate) run_started_at: &'a DateTime<Local>,
     pub(crate) schulze_records: &'a [SchulzeRecord],
+    pub(crate) failures: &'a [FetchError],
     pub(crate) full_output: bool,
     pub(crate) archive_csv: bool,
     pub(crate) paths: HtmlReportPaths<'a>,
@@ -62,25 +66,29 @@ fn render_html_report(context: &HtmlReportContext<'_>) -> String {
     } else {
         "Run with --full-output to include the full table.".to_string()
     };
+    let ballots = sub_ballot_columns(context.schulze_records);
     let (table_class, table_header, table_rows) = if context.full_output {
         (
             "table-full",
-            render_full_table_header(),
+            render_full_table_header(ballots),
             render_full_table_rows(context.schulze_records),
         )
     } else {
         (
             "table-compact",
-            render_compact_table_header(),
+            render_compact_table_header(ballots),
             render_compact_table_rows(context.schulze_records, top_n),
         )
     };
     let downloads = render_downloads(context);
-    let table_wrap_class = if context.full_output {
+    let mut table_wrap_class = if context.full_output {
         format!("table-wrap {table_class} show-shares show-trends")
     } else {
         format!("table-wrap {table_class} show-shares")
     };
+    if !ballots.is_empty() {
+        table_wrap_class.push_str(" show-ballots");
+    }
     let title = format!(
         "LangRank Report - {}",
         context.run_started_at.format("%Y-%m-%d")
@@ -153,6 +161,18 @@ fn render_html_report(context: &HtmlReportContext<'_>) -> String {
                                 span class="label" { "Coverage" }
                                 span class="value mono" { (showing) }
                             }
+                            @if let Some(round) = context.techempower_round {
+                                div {
+                                    span class="label" { "TechEmpower round" }
+                                    span class="value mono" { (round) }
+                                }
+                            }
+                            @if !context.techempower_filter.is_empty() {
+                                div {
+                                    span class="label" { "TechEmpower filter" }
+                                    span class="value mono" { (context.techempower_filter) }
+                                }
+                            }
                         }
                     }
 
@@ -161,28 +181,15 @@ fn render_html_report(context: &HtmlReportContext<'_>) -> String {
                             div class="card-label" { "Ranked languages" }
                             div class="card-value" { (total) }
                         }
-                        div class="card" {
-                            div class="card-label" { "TIOBE entries" }
-                            div class="card-value" { (context.tiobe_count) }
-                        }
-                        div class="card" {
-                            div class="card-label" { "PYPL entries" }
-                            div class="card-value" { (context.pypl_count) }
-                        }
-                        div class="card" {
-                            div class="card-label" { "Languish entries" }
-                            div class="card-value" { (context.languish_count) }
-                        }
-                        div class="card" {
-                            div class="card-label" { "Benchmarks langs" }
-                            div class="card-value" { (context.benchmark_lang_count) }
-                        }
-                        div class="card" {
-                            div class="card-label" { "TechEmpower langs" }
-                            div class="card-value" { (context.techempower_lang_count) }
-                        }
+                        (render_source_card("TIOBE entries", context.tiobe_count))
+                        (render_source_card("PYPL entries", context.pypl_count))
+                        (render_source_card("Languish entries", context.languish_count))
+                        (render_source_card("Benchmarks langs", context.benchmark_lang_count))
+                        (render_source_card("TechEmpower langs", context.techempower_lang_count))
                     }
 
+                    (render_missing_sources(context.failures))
+
                     section class="table-section" {
                         div class="section-header" {
                             div {
@@ -191,7 +198,7 @@ fn render_html_report(context: &HtmlReportContext<'_>) -> String {
                                     div class="hint" { (hint) }
                                 }
                             }
-                            (render_table_controls(context.full_output))
+                            (render_table_controls(context.full_output, !ballots.is_empty()))
                         }
                         div class=(table_wrap_class) {
                             table {
@@ -228,13 +235,71 @@ fn render_html_report(context: &HtmlReportContext<'_>) -> String {
     .into_string()
 }
 
-fn render_full_table_header() -> Markup {
+fn render_source_card(label: &str, count: Option<usize>) -> Markup {
+    let card_class = if count.is_some() {
+        "card"
+    } else {
+        "card card-missing"
+    };
+    html! {
+        div class=(card_class) {
+            div class="card-label" { (label) }
+            div class="card-value" { (format_source_count(count)) }
+        }
+    }
+}
+
+fn render_missing_sources(failures: &[FetchError]) -> Markup {
+    html! {
+        @if !failures.is_empty() {
+            section class="notice" role="status" {
+                div class="notice-title" { "Degraded ranking" }
+                p {
+                    "The ranking was computed without "
+                    (failures.len())
+                    " source(s) that could not be fetched:"
+                }
+                ul {
+                    @for failure in failures {
+                        li class="mono" { (format_error_chain(failure)) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Отдельные бюллетени ранжирования; у всех записей они одинаковы по составу.
+fn sub_ballot_columns(records: &[SchulzeRecord]) -> &[SubBallotRank] {
+    records
+        .first()
+        .map_or(&[], |record| record.sub_ballot_ranks.as_slice())
+}
+
+fn render_sub_ballot_headers(ballots: &[SubBallotRank]) -> Markup {
+    html! {
+        @for ballot in ballots {
+            (render_sortable_header(&format_sub_ballot_label(ballot), "num", "col-ballots"))
+        }
+    }
+}
+
+fn render_sub_ballot_cells(record: &SchulzeRecord) -> Markup {
+    html! {
+        @for ballot in &record.sub_ballot_ranks {
+            td class="num col-ballots" { (format_optional_rank(ballot.rank)) }
+        }
+    }
+}
+
+fn render_full_table_header(ballots: &[SubBallotRank]) -> Markup {
     html! {
         thead {
             tr {
                 (render_sortable_header("Pos", "index", ""))
                 (render_sortable_header("Language", "text", ""))
                 (render_sortable_header("T Rank", "num", "col-ranks"))
+                (render_sortable_header("T Prev", "num", "col-ranks"))
                 (render_sortable_header("T Share", "num", "col-shares"))
                 (render_sortable_header("T Trend", "num", "col-trends"))
                 (render_sortable_header("P Rank", "num", "col-ranks"))
@@ -243,6 +308,7 @@ fn render_full_table_header() -> Markup {
                 (render_sortable_header("L Rank", "num", "col-ranks"))
                 (render_sortable_header("L Share", "num", "col-shares"))
                 (render_sortable_header("L Trend", "num", "col-trends"))
+                (render_sub_ballot_headers(ballots))
                 (render_sortable_header("BG", "num", "col-perf-detail"))
                 (render_sortable_header("TE", "num", "col-perf-detail"))
                 (render_sortable_header("Perf", "num", ""))
@@ -252,7 +318,7 @@ fn render_full_table_header() -> Markup {
     }
 }
 
-fn render_compact_table_header() -> Markup {
+fn render_compact_table_header(ballots: &[SubBallotRank]) -> Markup {
     html! {
         thead {
             tr {
@@ -261,6 +327,7 @@ fn render_compact_table_header() -> Markup {
                 (render_sortable_header("TIOBE %", "num", "col-shares"))
                 (render_sortable_header("PYPL %", "num", "col-shares"))
                 (render_sortable_header("Languish %", "num", "col-shares"))
+                (render_sub_ballot_headers(ballots))
                 (render_sortable_header("BG", "num", "col-perf-detail"))
                 (render_sortable_header("TE", "num", "col-perf-detail"))
                 (render_sortable_header("Perf", "num", ""))
@@ -282,7 +349,7 @@ fn render_sortable_header(label: &str, sort: &str, class_name: &str) -> Markup {
     }
 }
 
-fn render_table_controls(full_output: bool) -> Markup {
+fn render_table_controls(full_output: bool, sub_ballots: bool) -> Markup {
     html! {
         div class="table-controls" {
             span class="control-label" { "Columns" }
@@ -291,6 +358,9 @@ fn render_table_controls(full_output: bool) -> Markup {
                 (render_group_toggle("Ranks", "ranks", false))
                 (render_group_toggle("Trends", "trends", true))
             }
+            @if sub_ballots {
+                (render_group_toggle("Sub-ballots", "ballots", true))
+            }
             (render_group_toggle("Perf details", "perf-detail", false))
         }
     }
@@ -326,20 +396,22 @@ fn render_full_table_row(record: &SchulzeRecord) -> Markup {
             td class="num" { (record.position) }
             td class="lang" { (&record.lang) }
             td class="num col-ranks" { (format_optional_rank(record.tiobe_rank)) }
-            td class="num col-shares" { (format!("{:.2}", record.tiobe_share)) }
+            td class="num col-ranks" { (format_optional_rank(record.tiobe_previous_rank)) }
+            td class="num col-shares" { (format_optional_float(record.tiobe_share)) }
             td class="col-trends" {
                 span class=(format!("trend {t_class}")) { (t_trend) }
             }
             td class="num col-ranks" { (format_optional_rank(record.pypl_rank)) }
-            td class="num col-shares" { (format!("{:.2}", record.pypl_share)) }
+            td class="num col-shares" { (format_optional_float(record.pypl_share)) }
             td class="col-trends" {
                 span class=(format!("trend {p_class}")) { (p_trend) }
             }
             td class="num col-ranks" { (format_optional_rank(record.languish_rank)) }
-            td class="num col-shares" { (format!("{:.2}", record.languish_share)) }
+            td class="num col-shares" { (format_optional_float(record.languish_share)) }
             td class="col-trends" {
                 span class=(format!("trend {l_class}")) { (l_trend) }
             }
+            (render_sub_ballot_cells(record))
             td class="num col-perf-detail" { (format_optional_float(record.benchmark_score)) }
             td class="num col-perf-detail" { (format_optional_float(record.techempower_score)) }
             td class="num" { (perf) }
@@ -358,9 +430,10 @@ fn render_compact_table_row(record: &SchulzeRecord) -> Markup {
         tr {
             td class="num" { (record.position) }
             td class="lang" { (&record.lang) }
-            td class="num col-shares" { (format!("{:.2}", record.tiobe_share)) }
-            td class="num col-shares" { (format!("{:.2}", record.pypl_share)) }
-            td class="num col-shares" { (format!("{:.2}", record.languish_share)) }
+            td class="num col-shares" { (format_optional_float(record.tiobe_share)) }
+            td class="num col-shares" { (format_optional_float(record.pypl_share)) }
+            td class="num col-shares" { (format_optional_float(record.languish_share)) }
+            (render_sub_ballot_cells(record))
             td class="num col-perf-detail" { (format_optional_float(record.benchmark_score)) }
             td class="num col-perf-detail" { (format_optional_float(record.techempower_score)) }
             td class="num" { (perf) }
@@ -1,57 +1,198 @@
-use anyhow::{Result, anyhow};
-use langrank::RankingEntry;
-use ndarray::{Array2, Zip};
-use rustc_hash::{FxHashMap, FxHashSet};
+use crate::{
+    Language, LanguishHistory, LanguishScoring, RankingDataset, RankingEntry, RankingSource,
+    SchulzeError, SubBallot, TECHEMPOWER_MAX_SCORE,
+};
+use rustc_hash::{FxBuildHasher, FxHashMap, FxHashSet};
 use serde::Serialize;
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
 
-#[derive(Debug, Serialize)]
+/// Итоговая строка ранжирования по методу Шульце.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
 pub struct SchulzeRecord {
+    /// Позиция в итоговом ранжировании, начиная с 1.
     pub position: usize,
-    pub lang: String,
+    /// Язык программирования.
+    pub lang: Language,
     pub tiobe_rank: Option<u32>,
-    pub tiobe_share: f64,
+    /// Доля TIOBE; `None`, если язык отсутствует в TIOBE или указан только
+    /// местом.
+    pub tiobe_share: Option<f64>,
     pub tiobe_trend: Option<f64>,
     pub pypl_rank: Option<u32>,
-    pub pypl_share: f64,
+    pub pypl_share: Option<f64>,
     pub pypl_trend: Option<f64>,
     pub languish_rank: Option<u32>,
-    pub languish_share: f64,
+    pub languish_share: Option<f64>,
     pub languish_trend: Option<f64>,
+    /// Геометрическое среднее Benchmarks Game.
     pub benchmark_score: Option<f64>,
+    /// Композитный показатель `TechEmpower`.
     pub techempower_score: Option<f64>,
+    /// Объединённый показатель производительности в диапазоне 0..1.
     pub perf_score: f64,
+    /// Количество языков, над которыми язык побеждает по сильнейшим путям.
     pub schulze_wins: usize,
+    /// Место в прошлогоднем рейтинге TIOBE; колонка идёт последней, чтобы не
+    /// сдвигать прежние колонки CSV.
+    pub tiobe_previous_rank: Option<u32>,
+    /// Места языка в отдельных бюллетенях источников
+    /// ([`RankingDataset::sub_ballots`]) в порядке наборов и бюллетеней;
+    /// одинаковы по составу у всех записей одного ранжирования.
+    #[serde(skip)]
+    pub sub_ballot_ranks: Vec<SubBallotRank>,
 }
 
+/// Место языка в отдельном бюллетене источника.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct SubBallotRank {
+    /// Источник бюллетеня.
+    pub source: RankingSource,
+    /// Имя бюллетеня ([`SubBallot::id`]).
+    pub ballot: &'static str,
+    /// Место среди языков с положительным показателем.
+    pub rank: Option<u32>,
+}
+
+/// Параметры ранжирования.
 #[derive(Debug, Clone, Copy)]
 pub struct SchulzeConfig {
+    /// Минимальное число источников, в которых должен встречаться язык.
     pub min_source_overlap: usize,
+    /// Ограничение числа ранжируемых языков; `0` снимает ограничение.
     pub max_ranked_languages: usize,
+    /// Максимально возможный композитный показатель `TechEmpower`.
     pub techempower_max_score: f64,
 }
 
-pub fn compute_schulze_records(
-    tiobe: &[RankingEntry],
-    pypl: &[RankingEntry],
-    languish: &[RankingEntry],
-    benchmark: &FxHashMap<String, f64>,
-    techempower: &FxHashMap<String, f64>,
+/// Дополнительный показатель производительности, например расход памяти по
+/// данным Benchmarks Game.
+#[derive(Debug)]
+pub struct PerformanceMetric<'a, S = FxBuildHasher> {
+    /// Значения показателя по языкам в диапазоне 0..1, больше — лучше.
+    pub scores: &'a HashMap<Language, f64, S>,
+    /// Способ учёта показателя в ранжировании.
+    pub usage: MetricUsage,
+}
+
+impl<S> Clone for PerformanceMetric<'_, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for PerformanceMetric<'_, S> {}
+
+/// Способ учёта дополнительного показателя производительности.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetricUsage {
+    /// Отдельный бюллетень; языки без значения оказываются в конце.
+    Ballot,
+    /// Слагаемое объединённого показателя `perf_score` с указанным весом
+    /// относительно Benchmarks Game и `TechEmpower`, у каждого из которых вес 1.
+    PerfScore { weight: f64 },
+}
+
+impl Default for SchulzeConfig {
+    fn default() -> Self {
+        Self {
+            min_source_overlap: 3,
+            max_ranked_languages: 0,
+            techempower_max_score: TECHEMPOWER_MAX_SCORE,
+        }
+    }
+}
+
+/// Ранжирует языки методом Шульце.
+///
+/// Каждый набор из `rankings` становится отдельным бюллетенем, включая
+/// пользовательские источники; набор с отдельными бюллетенями
+/// ([`RankingDataset::sub_ballots`]) голосует каждым из них. Показатели
+/// Benchmarks Game и `TechEmpower` объединяются в один бюллетень
+/// производительности; если оба набора пусты, этот бюллетень не учитывается.
+/// Колонки TIOBE, PYPL и Languish итоговой записи заполняются из наборов
+/// соответствующих источников.
+///
+/// # Errors
+///
+/// Возвращает ошибку, если после фильтрации по пересечению источников осталось
+/// меньше двух языков.
+pub fn compute_schulze_records<S: BuildHasher>(
+    rankings: &[RankingDataset],
+    benchmark: &HashMap<Language, f64, S>,
+    techempower: &HashMap<Language, f64, S>,
     config: SchulzeConfig,
-) -> Result<Vec<SchulzeRecord>> {
-    let sources = RankingSources::new(tiobe, pypl, languish, benchmark, techempower, config);
-    let languages = collect_language_names(&sources, config.min_source_overlap);
+) -> Result<Vec<SchulzeRecord>, SchulzeError> {
+    compute_schulze_records_with_metrics(rankings, benchmark, techempower, &[], config)
+}
+
+/// Ранжирует языки методом Шульце только по Languish для каждого квартала
+/// истории, начиная с `since`.
+///
+/// Квартал голосует набором [`LanguishHistory::quarter_dataset`] с той же
+/// стратегией `scoring`, что и при загрузке текущего рейтинга; языку
+/// достаточно одного источника. Кварталы ранжируются по мере обхода
+/// итератора.
+pub fn compute_languish_backfill<'a>(
+    history: &'a LanguishHistory,
+    since: Option<&'a str>,
+    scoring: LanguishScoring,
+) -> impl Iterator<Item = (&'a str, Result<Vec<SchulzeRecord>, SchulzeError>)> + 'a {
+    let performance: HashMap<Language, f64> = HashMap::new();
+    let config = SchulzeConfig {
+        min_source_overlap: 1,
+        ..SchulzeConfig::default()
+    };
+    history
+        .quarters()
+        .iter()
+        .map(String::as_str)
+        .filter(move |quarter| since.is_none_or(|since| *quarter >= since))
+        .filter_map(move |quarter| {
+            let dataset = history.quarter_dataset(quarter, scoring)?;
+            let records = compute_schulze_records(&[dataset], &performance, &performance, config);
+            Some((quarter, records))
+        })
+}
+
+/// Ранжирует языки методом Шульце с учётом дополнительных показателей
+/// производительности.
+///
+/// Работает как [`compute_schulze_records`]; каждый показатель из `metrics`
+/// либо добавляет собственный бюллетень, если в нём есть значения, либо входит
+/// с весом в `perf_score`.
+///
+/// # Errors
+///
+/// Возвращает ошибку, если после фильтрации по пересечению источников осталось
+/// меньше двух языков.
+pub fn compute_schulze_records_with_metrics<S: BuildHasher>(
+    rankings: &[RankingDataset],
+    benchmark: &HashMap<Language, f64, S>,
+    techempower: &HashMap<Language, f64, S>,
+    metrics: &[PerformanceMetric<'_, S>],
+    config: SchulzeConfig,
+) -> Result<Vec<SchulzeRecord>, SchulzeError> {
+    let sources = RankingSources::new(rankings, benchmark, techempower, metrics, config);
+    let languages = collect_languages(&sources, config.min_source_overlap);
     let candidates = build_candidates(languages, &sources);
     let candidates = limit_candidates(candidates, config.max_ranked_languages);
 
     if candidates.len() < 2 {
-        return Err(anyhow!(
-            "Not enough overlapping languages ({}) to compute Schulze ranking",
-            candidates.len()
-        ));
+        return Err(SchulzeError::NotEnoughCandidates {
+            actual: candidates.len(),
+        });
     }
 
-    let ballots = build_ballots(&candidates);
+    let ballots = build_ballots(
+        &candidates,
+        &sources.rankings,
+        sources.has_performance(),
+        sources.ballot_metrics.len(),
+    );
     let preference_strengths = build_preference_matrix(candidates.len(), &ballots);
     let ranked_indices = rank_languages(&candidates, &preference_strengths);
 
@@ -59,72 +200,111 @@ pub fn compute_schulze_records(
         &candidates,
         &ranked_indices,
         &preference_strengths,
+        &sources,
     ))
 }
 
-struct RankingSource<'a> {
+struct SourceIndex<'a> {
+    source: RankingSource,
     entries: &'a [RankingEntry],
-    index: FxHashMap<&'a str, usize>,
+    index: FxHashMap<Language, usize>,
+    sub_ballots: &'a [SubBallot],
+    sub_ballot_ranks: Vec<FxHashMap<Language, u32>>,
 }
 
-impl<'a> RankingSource<'a> {
-    fn new(entries: &'a [RankingEntry]) -> Self {
+impl<'a> SourceIndex<'a> {
+    fn new(dataset: &'a RankingDataset) -> Self {
+        let entries = dataset.entries();
         Self {
+            source: dataset.source(),
             entries,
             index: build_ranking_index(entries),
+            sub_ballots: dataset.sub_ballots(),
+            sub_ballot_ranks: dataset
+                .sub_ballots()
+                .iter()
+                .map(build_sub_ballot_ranks)
+                .collect(),
         }
     }
 
-    fn entry(&self, lang: &str) -> Option<&'a RankingEntry> {
-        let &idx = self.index.get(lang)?;
+    fn entry(&self, lang: Language) -> Option<&'a RankingEntry> {
+        let &idx = self.index.get(&lang)?;
         self.entries.get(idx)
     }
 }
 
-struct RankingSources<'a> {
-    tiobe: RankingSource<'a>,
-    pypl: RankingSource<'a>,
-    languish: RankingSource<'a>,
-    benchmark: &'a FxHashMap<String, f64>,
-    techempower: &'a FxHashMap<String, f64>,
+struct RankingSources<'a, S> {
+    rankings: Vec<SourceIndex<'a>>,
+    benchmark: &'a HashMap<Language, f64, S>,
+    techempower: &'a HashMap<Language, f64, S>,
     techempower_max_score: f64,
+    ballot_metrics: Vec<&'a HashMap<Language, f64, S>>,
+    folded_metrics: Vec<(f64, &'a HashMap<Language, f64, S>)>,
 }
 
-impl<'a> RankingSources<'a> {
+impl<'a, S: BuildHasher> RankingSources<'a, S> {
     fn new(
-        tiobe: &'a [RankingEntry],
-        pypl: &'a [RankingEntry],
-        languish: &'a [RankingEntry],
-        benchmark: &'a FxHashMap<String, f64>,
-        techempower: &'a FxHashMap<String, f64>,
+        rankings: &'a [RankingDataset],
+        benchmark: &'a HashMap<Language, f64, S>,
+        techempower: &'a HashMap<Language, f64, S>,
+        metrics: &[PerformanceMetric<'a, S>],
         config: SchulzeConfig,
     ) -> Self {
+        let mut ballot_metrics = Vec::new();
+        let mut folded_metrics = Vec::new();
+        for metric in metrics {
+            match metric.usage {
+                MetricUsage::Ballot if !metric.scores.is_empty() => {
+                    ballot_metrics.push(metric.scores);
+                }
+                MetricUsage::PerfScore { weight } if weight.is_finite() && weight > 0.0 => {
+                    folded_metrics.push((weight, metric.scores));
+                }
+                MetricUsage::Ballot | MetricUsage::PerfScore { .. } => {}
+            }
+        }
+        let mut seen = FxHashSet::default();
+        let rankings = rankings
+            .iter()
+            .filter(|dataset| seen.insert(dataset.source()))
+            .map(SourceIndex::new)
+            .collect();
         Self {
-            tiobe: RankingSource::new(tiobe),
-            pypl: RankingSource::new(pypl),
-            languish: RankingSource::new(languish),
+            rankings,
             benchmark,
             techempower,
             techempower_max_score: config.techempower_max_score,
+            ballot_metrics,
+            folded_metrics,
         }
     }
 
-    fn benchmark_value(&self, lang: &str) -> Option<f64> {
-        self.benchmark.get(lang).copied()
+    fn position(&self, source: RankingSource) -> Option<usize> {
+        self.rankings
+            .iter()
+            .position(|ranking| ranking.source == source)
+    }
+
+    fn has_performance(&self) -> bool {
+        !self.benchmark.is_empty() || !self.techempower.is_empty()
+    }
+
+    fn benchmark_value(&self, lang: Language) -> Option<f64> {
+        self.benchmark.get(&lang).copied()
     }
 
-    fn techempower_value(&self, lang: &str) -> Option<f64> {
-        self.techempower.get(lang).copied()
+    fn techempower_value(&self, lang: Language) -> Option<f64> {
+        self.techempower.get(&lang).copied()
     }
 }
 
 struct LanguageCandidate<'a> {
-    name: String,
-    tiobe: Option<&'a RankingEntry>,
-    pypl: Option<&'a RankingEntry>,
-    languish: Option<&'a RankingEntry>,
+    lang: Language,
+    entries: Vec<Option<&'a RankingEntry>>,
     benchmark_score: Option<f64>,
     techempower_score: Option<f64>,
+    metric_scores: Vec<f64>,
     source_count: usize,
     popularity_score: f64,
     perf_score: f64,
@@ -132,31 +312,39 @@ struct LanguageCandidate<'a> {
 }
 
 impl<'a> LanguageCandidate<'a> {
-    fn new(name: String, sources: &RankingSources<'a>) -> Self {
-        let lang = name.as_str();
-        let tiobe = sources.tiobe.entry(lang);
-        let pypl = sources.pypl.entry(lang);
-        let languish = sources.languish.entry(lang);
+    fn new<S: BuildHasher>(lang: Language, sources: &RankingSources<'a, S>) -> Self {
+        let entries: Vec<Option<&RankingEntry>> = sources
+            .rankings
+            .iter()
+            .map(|ranking| ranking.entry(lang))
+            .collect();
         let benchmark_score = sources.benchmark_value(lang);
         let techempower_score = sources.techempower_value(lang);
         let perf_score = performance_score(
             benchmark_score,
             techempower_score,
             sources.techempower_max_score,
+            &sources
+                .folded_metrics
+                .iter()
+                .map(|&(weight, scores)| (weight, scores.get(&lang).copied()))
+                .collect::<Vec<_>>(),
         );
-        let popularity_score = source_share(tiobe) + source_share(pypl) + source_share(languish);
-        let source_count = usize::from(tiobe.is_some())
-            + usize::from(pypl.is_some())
-            + usize::from(languish.is_some())
+        let metric_scores = sources
+            .ballot_metrics
+            .iter()
+            .map(|scores| scores.get(&lang).copied().unwrap_or(0.0))
+            .collect();
+        let popularity_score = entries.iter().map(|entry| source_share(*entry)).sum();
+        let source_count = entries.iter().filter(|entry| entry.is_some()).count()
             + usize::from(benchmark_score.is_some() || techempower_score.is_some());
 
         Self {
-            name,
-            tiobe,
-            pypl,
-            languish,
+            lang,
+            entries,
             benchmark_score,
             techempower_score,
+            metric_scores,
             source_count,
             popularity_score,
             perf_score,
@@ -164,47 +352,104 @@ impl<'a> LanguageCandidate<'a> {
         }
     }
 
-    const fn lang(&self) -> &str {
-        self.name.as_str()
+    fn entry(&self, position: Option<usize>) -> Option<&'a RankingEntry> {
+        position.and_then(|position| self.entries[position])
     }
 
-    fn record(&self, position: usize, schulze_wins: usize) -> SchulzeRecord {
+    fn record<S: BuildHasher>(
+        &self,
+        position: usize,
+        schulze_wins: usize,
+        sources: &RankingSources<'_, S>,
+    ) -> SchulzeRecord {
+        let tiobe = self.entry(sources.position(RankingSource::Tiobe));
+        let pypl = self.entry(sources.position(RankingSource::Pypl));
+        let languish = self.entry(sources.position(RankingSource::Languish));
+        let sub_ballot_ranks = sources
+            .rankings
+            .iter()
+            .flat_map(|ranking| {
+                ranking
+                    .sub_ballots
+                    .iter()
+                    .zip(&ranking.sub_ballot_ranks)
+                    .map(|(ballot, ranks)| SubBallotRank {
+                        source: ranking.source,
+                        ballot: ballot.id,
+                        rank: ranks.get(&self.lang).copied(),
+                    })
+            })
+            .collect();
         SchulzeRecord {
             position,
-            lang: self.name.clone(),
-            tiobe_rank: self.tiobe.and_then(|entry| entry.rank),
-            tiobe_share: source_share(self.tiobe),
-            tiobe_trend: self.tiobe.and_then(|entry| entry.trend),
-            pypl_rank: self.pypl.and_then(|entry| entry.rank),
-            pypl_share: source_share(self.pypl),
-            pypl_trend: self.pypl.and_then(|entry| entry.trend),
-            languish_rank: self.languish.and_then(|entry| entry.rank),
-            languish_share: source_share(self.languish),
-            languish_trend: self.languish.and_then(|entry| entry.trend),
+            lang: self.lang,
+            tiobe_rank: tiobe.and_then(|entry| entry.rank),
+            tiobe_share: tiobe.and_then(|entry| entry.share),
+            tiobe_trend: tiobe.and_then(|entry| entry.trend),
+            pypl_rank: pypl.and_then(|entry| entry.rank),
+            pypl_share: pypl.and_then(|entry| entry.share),
+            pypl_trend: pypl.and_then(|entry| entry.trend),
+            languish_rank: languish.and_then(|entry| entry.rank),
+            languish_share: languish.and_then(|entry| entry.share),
+            languish_trend: languish.and_then(|entry| entry.trend),
             benchmark_score: self.benchmark_score,
             techempower_score: self.techempower_score,
             perf_score: self.perf_score,
             schulze_wins,
+            tiobe_previous_rank: tiobe.and_then(|entry| entry.previous_rank),
+            sub_ballot_ranks,
         }
     }
 }
 
-fn build_ranking_index(entries: &[RankingEntry]) -> FxHashMap<&str, usize> {
+fn build_ranking_index(entries: &[RankingEntry]) -> FxHashMap<Language, usize> {
     entries
         .iter()
         .enumerate()
-        .map(|(idx, entry)| (entry.lang.as_str(), idx))
+        .map(|(idx, entry)| (entry.lang, idx))
+        .collect()
+}
+
+fn build_sub_ballot_ranks(ballot: &SubBallot) -> FxHashMap<Language, u32> {
+    let mut ordered: Vec<(Language, f64)> = ballot
+        .scores
+        .iter()
+        .map(|(&lang, &score)| (lang, score))
+        .filter(|&(_, score)| score > 0.0)
+        .collect();
+    ordered.sort_by(|left, right| {
+        right
+            .1
+            .total_cmp(&left.1)
+            .then_with(|| left.0.cmp(&right.0))
+    });
+    (1_u32..)
+        .zip(ordered)
+        .map(|(rank, (lang, _))| (lang, rank))
         .collect()
 }
 
 fn source_share(entry: Option<&RankingEntry>) -> f64 {
-    entry.map_or(0.0, |entry| entry.share)
+    entry.and_then(|entry| entry.share).unwrap_or(0.0)
+}
+
+/// Показатель языка в бюллетене источника: записи только с местом идут после
+/// всех записей с долей, но впереди языков, которых источник не перечислил.
+fn ballot_share(entry: Option<&RankingEntry>) -> f64 {
+    match entry {
+        Some(RankingEntry {
+            share: Some(share), ..
+        }) => *share,
+        Some(entry) => -f64::from(entry.rank.unwrap_or(u32::MAX)),
+        None => f64::NEG_INFINITY,
+    }
 }
 
 fn performance_score(
     benchmark_score: Option<f64>,
     techempower_score: Option<f64>,
     techempower_max_score: f64,
+    folded_metrics: &[(f64, Option<f64>)],
 ) -> f64 {
     let benchmark = benchmark_score.unwrap_or(0.0);
     let techempower = techempower_score.unwrap_or(0.0);
@@ -213,52 +458,64 @@ fn performance_score(
     } else {
         0.0
     };
-    f64::midpoint(benchmark, normalized_techempower)
+    if folded_metrics.is_empty() {
+        return f64::midpoint(benchmark, normalized_techempower);
+    }
+    let total_weight: f64 = 2.0
+        + folded_metrics
+            .iter()
+            .map(|&(weight, _)| weight)
+            .sum::<f64>();
+    let weighted_metrics: f64 = folded_metrics
+        .iter()
+        .map(|&(weight, value)| weight * value.unwrap_or(0.0))
+        .sum();
+    (benchmark + normalized_techempower + weighted_metrics) / total_weight
 }
 
-fn collect_language_names(sources: &RankingSources<'_>, min_sources: usize) -> Vec<String> {
-    let mut counts: FxHashMap<&str, usize> = FxHashMap::default();
-    add_ranking_source(&mut counts, sources.tiobe.entries);
-    add_ranking_source(&mut counts, sources.pypl.entries);
-    add_ranking_source(&mut counts, sources.languish.entries);
+fn collect_languages<S>(sources: &RankingSources<'_, S>, min_sources: usize) -> Vec<Language> {
+    let mut counts: FxHashMap<Language, usize> = FxHashMap::default();
+    for ranking in &sources.rankings {
+        add_ranking_source(&mut counts, ranking.entries);
+    }
     add_performance_source(&mut counts, sources.benchmark, sources.techempower);
 
-    let mut languages: Vec<String> = counts
+    let mut languages: Vec<Language> = counts
         .into_iter()
         .filter(|(_, count)| *count >= min_sources)
-        .map(|(lang, _)| lang.to_owned())
+        .map(|(lang, _)| lang)
         .collect();
     languages.sort_unstable();
     languages
 }
 
-fn add_ranking_source<'a>(counts: &mut FxHashMap<&'a str, usize>, entries: &'a [RankingEntry]) {
+fn add_ranking_source(counts: &mut FxHashMap<Language, usize>, entries: &[RankingEntry]) {
     let mut seen = FxHashSet::default();
     for entry in entries {
-        let lang = entry.lang.as_str();
+        let lang = entry.lang;
         if seen.insert(lang) {
             *counts.entry(lang).or_insert(0) += 1;
         }
     }
 }
 
-fn add_performance_source<'a>(
-    counts: &mut FxHashMap<&'a str, usize>,
-    benchmark: &'a FxHashMap<String, f64>,
-    techempower: &'a FxHashMap<String, f64>,
+fn add_performance_source<S>(
+    counts: &mut FxHashMap<Language, usize>,
+    benchmark: &HashMap<Language, f64, S>,
+    techempower: &HashMap<Language, f64, S>,
 ) {
     let mut seen = FxHashSet::default();
     for lang in benchmark.keys().chain(techempower.keys()) {
-        seen.insert(lang.as_str());
+        seen.insert(*lang);
     }
     for lang in seen {
         *counts.entry(lang).or_insert(0) += 1;
     }
 }
 
-fn build_candidates<'a>(
-    languages: Vec<String>,
-    sources: &RankingSources<'a>,
+fn build_candidates<'a, S: BuildHasher>(
+    languages: Vec<Language>,
+    sources: &RankingSources<'a, S>,
 ) -> Vec<LanguageCandidate<'a>> {
     languages
         .into_iter()
@@ -276,7 +533,7 @@ fn limit_candidates(
 
     candidates.select_nth_unstable_by(max_languages - 1, compare_candidate_scores);
     candidates.truncate(max_languages);
-    candidates.sort_unstable_by(|left, right| left.lang().cmp(right.lang()));
+    candidates.sort_unstable_by_key(|candidate| candidate.lang);
     candidates
 }
 
@@ -289,23 +546,47 @@ fn compare_candidate_scores(
         .cmp(&left.source_count)
         .then_with(|| right.popularity_score.total_cmp(&left.popularity_score))
         .then_with(|| right.perf_score.total_cmp(&left.perf_score))
-        .then_with(|| left.lang().cmp(right.lang()))
+        .then_with(|| left.lang.cmp(&right.lang))
 }
 
-const SOURCE_BALLOT_COUNT: usize = 4;
-
-type Ballots = [Vec<usize>; SOURCE_BALLOT_COUNT];
+/// Бюллетень: группы кандидатов от сильнейшей к слабейшей; кандидаты одной
+/// группы считаются равными.
+type Ballot = Vec<Vec<usize>>;
 
-fn build_ballots(candidates: &[LanguageCandidate<'_>]) -> Ballots {
-    [
-        order_by_metric(candidates, |candidate| source_share(candidate.tiobe)),
-        order_by_metric(candidates, |candidate| source_share(candidate.pypl)),
-        order_by_metric(candidates, |candidate| source_share(candidate.languish)),
-        order_by_metric(candidates, |candidate| candidate.perf_score),
-    ]
+fn build_ballots(
+    candidates: &[LanguageCandidate<'_>],
+    rankings: &[SourceIndex<'_>],
+    has_performance: bool,
+    metric_count: usize,
+) -> Vec<Ballot> {
+    let mut ballots: Vec<Ballot> = Vec::with_capacity(rankings.len() + 1 + metric_count);
+    for (position, ranking) in rankings.iter().enumerate() {
+        if ranking.sub_ballots.is_empty() {
+            ballots.push(order_by_metric(candidates, |candidate| {
+                ballot_share(candidate.entries[position])
+            }));
+            continue;
+        }
+        for ballot in ranking.sub_ballots {
+            ballots.push(order_by_metric(candidates, |candidate| {
+                ballot.scores.get(&candidate.lang).copied().unwrap_or(0.0)
+            }));
+        }
+    }
+    if has_performance {
+        ballots.push(order_by_metric(candidates, |candidate| {
+            candidate.perf_score
+        }));
+    }
+    for position in 0..metric_count {
+        ballots.push(order_by_metric(candidates, |candidate| {
+            candidate.metric_scores[position]
+        }));
+    }
+    ballots
 }
 
-fn order_by_metric<F>(candidates: &[LanguageCandidate<'_>], metric: F) -> Vec<usize>
+fn order_by_metric<F>(candidates: &[LanguageCandidate<'_>], metric: F) -> Ballot
 where
     F: Fn(&LanguageCandidate<'_>) -> f64,
 {
@@ -318,14 +599,17 @@ where
     scored.sort_by(|(idx_a, score_a), (idx_b, score_b)| {
         score_b
             .total_cmp(score_a)
-            .then_with(|| candidates[*idx_a].lang().cmp(candidates[*idx_b].lang()))
+            .then_with(|| candidates[*idx_a].lang.cmp(&candidates[*idx_b].lang))
     });
-    scored.into_iter().map(|(idx, _)| idx).collect()
+    scored
+        .chunk_by(|(_, score_a), (_, score_b)| score_a.total_cmp(score_b).is_eq())
+        .map(|tier| tier.iter().map(|&(idx, _)| idx).collect())
+        .collect()
 }
 
 fn rank_languages(
     candidates: &[LanguageCandidate<'_>],
-    preference_strengths: &Array2<usize>,
+    preference_strengths: &SquareMatrix,
 ) -> Vec<usize> {
     let mut ranked: Vec<usize> = (0..candidates.len()).collect();
     ranked.sort_by(|&left, &right| {
@@ -336,75 +620,108 @@ fn rank_languages(
                     .combined_score
                     .total_cmp(&candidates[left].combined_score)
             })
-            .then_with(|| candidates[left].lang().cmp(candidates[right].lang()))
+            .then_with(|| candidates[left].lang.cmp(&candidates[right].lang))
     });
     ranked
 }
 
-fn build_records(
+fn build_records<S: BuildHasher>(
     candidates: &[LanguageCandidate<'_>],
     ranked_indices: &[usize],
-    preference_strengths: &Array2<usize>,
+    preference_strengths: &SquareMatrix,
+    sources: &RankingSources<'_, S>,
 ) -> Vec<SchulzeRecord> {
     ranked_indices
         .iter()
         .enumerate()
         .map(|(position, &idx)| {
             let wins = schulze_wins(preference_strengths, idx);
-            candidates[idx].record(position + 1, wins)
+            candidates[idx].record(position + 1, wins, sources)
         })
         .collect()
 }
 
-fn schulze_wins(preference_strengths: &Array2<usize>, candidate_idx: usize) -> usize {
-    (0..preference_strengths.nrows())
+fn schulze_wins(preference_strengths: &SquareMatrix, candidate_idx: usize) -> usize {
+    (0..preference_strengths.size())
         .filter(|&other_idx| {
             other_idx != candidate_idx
-                && preference_strengths[[candidate_idx, other_idx]]
-                    > preference_strengths[[other_idx, candidate_idx]]
+                && preference_strengths.get(candidate_idx, other_idx)
+                    > preference_strengths.get(other_idx, candidate_idx)
         })
         .count()
 }
 
-fn build_preference_matrix(candidate_count: usize, ballots: &Ballots) -> Array2<usize> {
+/// Квадратная матрица попарных предпочтений в построчном хранении.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SquareMatrix {
+    size: usize,
+    cells: Vec<usize>,
+}
+
+impl SquareMatrix {
+    fn zeros(size: usize) -> Self {
+        Self {
+            size,
+            cells: vec![0; size * size],
+        }
+    }
+
+    const fn size(&self) -> usize {
+        self.size
+    }
+
+    fn get(&self, row: usize, column: usize) -> usize {
+        self.cells[row * self.size + column]
+    }
+
+    fn set(&mut self, row: usize, column: usize, value: usize) {
+        self.cells[row * self.size + column] = value;
+    }
+}
+
+fn build_preference_matrix(candidate_count: usize, ballots: &[Ballot]) -> SquareMatrix {
     let direct_preferences = build_direct_preference_matrix(candidate_count, ballots);
     let strongest_paths = build_initial_strongest_paths(&direct_preferences);
     compute_strongest_paths(strongest_paths)
 }
 
-fn build_direct_preference_matrix(candidate_count: usize, ballots: &Ballots) -> Array2<usize> {
-    let mut preferences = Array2::<usize>::zeros((candidate_count, candidate_count));
+fn build_direct_preference_matrix(candidate_count: usize, ballots: &[Ballot]) -> SquareMatrix {
+    let mut preferences = SquareMatrix::zeros(candidate_count);
     for ballot in ballots {
-        for (preferred_pos, &preferred_idx) in ballot.iter().enumerate() {
-            for &weaker_idx in &ballot[preferred_pos + 1..] {
-                preferences[[preferred_idx, weaker_idx]] += 1;
+        for (tier_pos, tier) in ballot.iter().enumerate() {
+            for &preferred_idx in tier {
+                for &weaker_idx in ballot[tier_pos + 1..].iter().flatten() {
+                    let current = preferences.get(preferred_idx, weaker_idx);
+                    preferences.set(preferred_idx, weaker_idx, current + 1);
+                }
             }
         }
     }
     preferences
 }
 
-fn build_initial_strongest_paths(direct_preferences: &Array2<usize>) -> Array2<usize> {
-    let mut paths = Array2::<usize>::zeros(direct_preferences.dim());
-    Zip::from(&mut paths)
-        .and(direct_preferences)
-        .and(&direct_preferences.t())
-        .for_each(|path, &left, &right| {
-            if left > right {
-                *path = left;
+fn build_initial_strongest_paths(direct_preferences: &SquareMatrix) -> SquareMatrix {
+    let size = direct_preferences.size();
+    let mut paths = SquareMatrix::zeros(size);
+    for from in 0..size {
+        for to in 0..size {
+            let left = direct_preferences.get(from, to);
+            if left > direct_preferences.get(to, from) {
+                paths.set(from, to, left);
             }
-        });
+        }
+    }
     paths
 }
 
-fn compute_strongest_paths(mut paths: Array2<usize>) -> Array2<usize> {
-    let candidate_count = paths.nrows();
+fn compute_strongest_paths(mut paths: SquareMatrix) -> SquareMatrix {
+    let candidate_count = paths.size();
     for pivot in 0..candidate_count {
         for from in 0..candidate_count {
             if from == pivot {
                 continue;
             }
-            let path_to_pivot = paths[[from, pivot]];
+            let path_to_pivot = paths.get(from, pivot);
             if path_to_pivot == 0 {
                 continue;
             }
@@ -412,9 +729,9 @@ fn compute_strongest_paths(mut paths: Array2<usize>) -> Array2<usize> {
                 if to == pivot || to == from {
                     continue;
                 }
-                let candidate = path_to_pivot.min(paths[[pivot, to]]);
-                if candidate > paths[[from, to]] {
-                    paths[[from, to]] = candidate;
+                let candidate = path_to_pivot.min(paths.get(pivot, to));
+                if candidate > paths.get(from, to) {
+                    paths.set(from, to, candidate);
                 }
             }
         }
@@ -424,31 +741,47 @@ fn compute_strongest_paths(mut paths: Array2<usize>) -> Array2<usize> {
 
 #[cfg(test)]
 mod tests {
-    use super::{SchulzeConfig, compute_schulze_records, rank_languages};
-    use langrank::RankingEntry;
-    use ndarray::array;
+    use super::{
+        MetricUsage, PerformanceMetric, SchulzeConfig, SquareMatrix, compute_schulze_records,
+        compute_schulze_records_with_metrics, rank_languages,
+    };
+    use crate::{Language, LanguishSignals, RankingDataset, RankingEntry, RankingSource};
     use rustc_hash::FxHashMap;
 
     fn entry(lang: &str, rank: u32, share: f64, trend: f64) -> RankingEntry {
         RankingEntry {
-            lang: lang.to_owned(),
+            lang: Language::intern(lang),
             rank: Some(rank),
-            share,
+            share: Some(share),
             trend: Some(trend),
+            previous_rank: None,
         }
     }
 
-    fn performance_scores(scores: &[(&str, f64)]) -> FxHashMap<String, f64> {
+    fn performance_scores(scores: &[(&str, f64)]) -> FxHashMap<Language, f64> {
         scores
             .iter()
-            .map(|&(lang, score)| (lang.to_owned(), score))
+            .map(|&(lang, score)| (Language::intern(lang), score))
             .collect()
     }
 
+    fn matrix(rows: &[&[usize]]) -> SquareMatrix {
+        let mut matrix = SquareMatrix::zeros(rows.len());
+        for (row, values) in rows.iter().enumerate() {
+            for (column, &value) in values.iter().enumerate() {
+                matrix.set(row, column, value);
+            }
+        }
+        matrix
+    }
+
     #[test]
     fn stable_ranking_on_fixed_snapshot() {
         let tiobe = vec![
-            entry("Rust", 1, 20.0, 0.5),
+            RankingEntry {
+                previous_rank: Some(2),
+                ..entry("Rust", 1, 20.0, 0.5)
+            },
             entry("Go", 2, 15.0, 0.1),
             entry("Python", 3, 10.0, -0.1),
         ];
@@ -467,9 +800,11 @@ mod tests {
         let techempower = performance_scores(&[("Rust", 5.4), ("Go", 4.8), ("Python", 3.2)]);
 
         let records = compute_schulze_records(
-            &tiobe,
-            &pypl,
-            &languish,
+            &[
+                RankingDataset::new(RankingSource::Tiobe, tiobe),
+                RankingDataset::new(RankingSource::Pypl, pypl),
+                RankingDataset::new(RankingSource::Languish, languish),
+            ],
             &benchmark,
             &techempower,
             SchulzeConfig {
@@ -480,8 +815,11 @@ mod tests {
         )
         .expect("snapshot ranking should be computed");
 
-        let order: Vec<&str> = records.iter().map(|record| record.lang.as_str()).collect();
+        let order: Vec<&str> = records.iter().map(|record| record.lang.name()).collect();
         assert_eq!(order, vec!["Rust", "Go", "Python"]);
+        assert_eq!(records[0].pypl_rank, Some(2));
+        assert_eq!(records[0].tiobe_previous_rank, Some(2));
+        assert_eq!(records[1].tiobe_previous_rank, None);
     }
 
     #[test]
@@ -493,9 +831,11 @@ mod tests {
         let techempower = FxHashMap::default();
 
         let error = compute_schulze_records(
-            &tiobe,
-            &pypl,
-            &languish,
+            &[
+                RankingDataset::new(RankingSource::Tiobe, tiobe),
+                RankingDataset::new(RankingSource::Pypl, pypl),
+                RankingDataset::new(RankingSource::Languish, languish),
+            ],
             &benchmark,
             &techempower,
             SchulzeConfig {
@@ -512,20 +852,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn custom_datasets_vote_as_separate_ballots() {
+        let tiobe = vec![entry("Alpha", 1, 2.0, 0.0), entry("Beta", 2, 1.0, 0.0)];
+        let survey = vec![entry("Alpha", 2, 1.0, 0.0), entry("Beta", 1, 5.0, 0.0)];
+        let extra = vec![entry("Alpha", 2, 1.0, 0.0), entry("Beta", 1, 3.0, 0.0)];
+        let performance = FxHashMap::default();
+
+        let records = compute_schulze_records(
+            &[
+                RankingDataset::new(RankingSource::Tiobe, tiobe),
+                RankingDataset::new(RankingSource::Custom("survey"), survey),
+                RankingDataset::new(RankingSource::Custom("extra"), extra),
+            ],
+            &performance,
+            &performance,
+            SchulzeConfig {
+                min_source_overlap: 2,
+                ..SchulzeConfig::default()
+            },
+        )
+        .expect("custom sources should produce a ranking");
+
+        assert_eq!(records[0].lang, "Beta");
+        assert_eq!(records[0].tiobe_rank, Some(2));
+        assert_eq!(records[0].tiobe_previous_rank, None);
+        assert_eq!(records[0].pypl_rank, None);
+    }
+
     #[test]
     fn cyclic_preferences_produce_a_deterministic_ranking() {
-        let tiobe = vec![
-            entry("Alpha", 1, 3.0, 0.0),
-            entry("Beta", 2, 2.0, 0.0),
-            entry("Gamma", 3, 1.0, 0.0),
-        ];
+        let tiobe = [RankingDataset::new(
+            RankingSource::Tiobe,
+            vec![
+                entry("Alpha", 1, 3.0, 0.0),
+                entry("Beta", 2, 2.0, 0.0),
+                entry("Gamma", 3, 1.0, 0.0),
+            ],
+        )];
         let performance = FxHashMap::default();
         let sources = super::RankingSources::new(
             &tiobe,
-            &[],
-            &[],
             &performance,
             &performance,
+            &[],
             SchulzeConfig {
                 min_source_overlap: 1,
                 max_ranked_languages: 0,
@@ -533,11 +903,192 @@ mod tests {
             },
         );
         let candidates = super::build_candidates(
-            vec!["Alpha".to_owned(), "Beta".to_owned(), "Gamma".to_owned()],
+            ["Alpha", "Beta", "Gamma"].map(Language::intern).to_vec(),
             &sources,
         );
-        let preferences = array![[0, 2, 1], [1, 0, 2], [2, 1, 0]];
+        let preferences = matrix(&[&[0, 2, 1], &[1, 0, 2], &[2, 1, 0]]);
 
         assert_eq!(rank_languages(&candidates, &preferences), vec![0, 1, 2]);
     }
+
+    #[test]
+    fn performance_metrics_add_ballots_or_fold_into_perf_score() {
+        let tiobe = vec![entry("Alpha", 1, 2.0, 0.0), entry("Beta", 2, 1.0, 0.0)];
+        let pypl = vec![entry("Alpha", 2, 1.0, 0.0), entry("Beta", 1, 2.0, 0.0)];
+        let rankings = [
+            RankingDataset::new(RankingSource::Tiobe, tiobe),
+            RankingDataset::new(RankingSource::Pypl, pypl),
+        ];
+        let benchmark = performance_scores(&[("Alpha", 0.5), ("Beta", 0.4)]);
+        let techempower = FxHashMap::default();
+        let memory = performance_scores(&[("Alpha", 0.25), ("Beta", 1.0)]);
+        let reliability = performance_scores(&[("Alpha", 0.9), ("Beta", 1.0)]);
+        let config = SchulzeConfig {
+            min_source_overlap: 2,
+            max_ranked_languages: 0,
+            techempower_max_score: 1.0,
+        };
+
+        let ballot = compute_schulze_records_with_metrics(
+            &rankings,
+            &benchmark,
+            &techempower,
+            &[
+                PerformanceMetric {
+                    scores: &memory,
+                    usage: MetricUsage::Ballot,
+                },
+                PerformanceMetric {
+                    scores: &reliability,
+                    usage: MetricUsage::Ballot,
+                },
+            ],
+            config,
+        )
+        .expect("metric ballots should produce a ranking");
+        assert_eq!(ballot[0].lang, "Beta");
+        assert!((ballot[0].perf_score - 0.2).abs() < f64::EPSILON);
+
+        let folded = compute_schulze_records_with_metrics(
+            &rankings,
+            &benchmark,
+            &techempower,
+            &[PerformanceMetric {
+                scores: &memory,
+                usage: MetricUsage::PerfScore { weight: 2.0 },
+            }],
+            config,
+        )
+        .expect("folded metric should produce a ranking");
+        assert_eq!(folded[0].lang, "Beta");
+        assert!((folded[0].perf_score - 0.6).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn rank_only_entries_rank_between_shares_and_unlisted_languages() {
+        let rank_only = |lang: &str| RankingEntry {
+            lang: Language::intern(lang),
+            rank: Some(51),
+            share: None,
+            trend: None,
+            previous_rank: None,
+        };
+        let tiobe = vec![
+            entry("Rust", 1, 5.0, 0.0),
+            rank_only("Zig"),
+            rank_only("Abap"),
+        ];
+        let pypl = vec![
+            entry("Ada", 1, 3.0, 0.0),
+            entry("Zig", 2, 2.0, 0.0),
+            entry("Rust", 3, 1.0, 0.0),
+            entry("Abap", 4, 0.5, 0.0),
+        ];
+        let rankings = [
+            RankingDataset::new(RankingSource::Tiobe, tiobe),
+            RankingDataset::new(RankingSource::Pypl, pypl),
+        ];
+        let performance = FxHashMap::default();
+        let sources = super::RankingSources::new(
+            &rankings,
+            &performance,
+            &performance,
+            &[],
+            SchulzeConfig {
+                min_source_overlap: 1,
+                ..SchulzeConfig::default()
+            },
+        );
+        let candidates = super::build_candidates(
+            ["Abap", "Ada", "Rust", "Zig"]
+                .map(Language::intern)
+                .to_vec(),
+            &sources,
+        );
+
+        let ballots = super::build_ballots(&candidates, &sources.rankings, false, 0);
+        // Хвост TIOBE делит одно место и не даёт Abap преимущества над Zig.
+        assert_eq!(ballots[0], [vec![2], vec![0, 3], vec![1]]);
+        assert_eq!(ballots[1], [vec![1], vec![3], vec![2], vec![0]]);
+
+        let preferences = super::build_direct_preference_matrix(candidates.len(), &ballots);
+        assert_eq!(preferences.get(0, 3), 0);
+        assert_eq!(preferences.get(3, 0), 1);
+    }
+
+    #[test]
+    fn languish_signals_vote_as_separate_ballots() {
+        let tiobe = vec![entry("Alpha", 1, 2.0, 0.0), entry("Beta", 2, 1.0, 0.0)];
+        let pypl = vec![entry("Alpha", 2, 1.0, 0.0), entry("Beta", 1, 2.0, 0.0)];
+        let languish = vec![entry("Alpha", 1, 30.0, 0.0), entry("Beta", 2, 20.0, 0.0)];
+        let signals = |issues, pulls, so_questions, stars| LanguishSignals {
+            issues,
+            pulls,
+            so_questions,
+            stars,
+        };
+        let languish_signals: FxHashMap<Language, LanguishSignals> = [
+            (Language::intern("Alpha"), signals(10.0, 10.0, 10.0, 90.0)),
+            (Language::intern("Beta"), signals(20.0, 20.0, 20.0, 0.0)),
+        ]
+        .into_iter()
+        .collect();
+        let performance = FxHashMap::default();
+        let config = SchulzeConfig {
+            min_source_overlap: 2,
+            ..SchulzeConfig::default()
+        };
+        let rankings = |languish: RankingDataset| {
+            [
+                RankingDataset::new(RankingSource::Tiobe, tiobe.clone()),
+                RankingDataset::new(RankingSource::Pypl, pypl.clone()),
+                languish,
+            ]
+        };
+
+        let averaged = compute_schulze_records(
+            &rankings(RankingDataset::new(
+                RankingSource::Languish,
+                languish.clone(),
+            )),
+            &performance,
+            &performance,
+            config,
+        )
+        .expect("averaged Languish ballot should produce a ranking");
+        assert_eq!(averaged[0].lang, "Alpha");
+        assert!(averaged[0].sub_ballot_ranks.is_empty());
+
+        let split = compute_schulze_records(
+            &rankings(
+                RankingDataset::new(RankingSource::Languish, languish)
+                    .with_sub_ballots(LanguishSignals::ballots(&languish_signals)),
+            ),
+            &performance,
+            &performance,
+            config,
+        )
+        .expect("Languish signal ballots should produce a ranking");
+        assert_eq!(split[0].lang, "Beta");
+        let ranks = |record: &super::SchulzeRecord| -> Vec<_> {
+            record
+                .sub_ballot_ranks
+                .iter()
+                .map(|rank| (rank.ballot, rank.rank))
+                .collect()
+        };
+        assert_eq!(
+            ranks(&split[0]),
+            [
+                ("issues", Some(1)),
+                ("pulls", Some(1)),
+                ("so-questions", Some(1)),
+                ("stars", None)
+            ]
+        );
+        assert_eq!(split[1].sub_ballot_ranks[2].rank, Some(2));
+        assert_eq!(split[1].sub_ballot_ranks[3].rank, Some(1));
+        assert_eq!(split[1].sub_ballot_ranks[3].source, RankingSource::Languish);
+        assert_eq!(split[1].languish_rank, Some(1));
+    }
 }
@@ -0,0 +1,289 @@
+use crate::{HttpResponse, Transport, TransportFuture};
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Версия формата каталога снимка.
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const BODIES_DIR: &str = "bodies";
+
+/// Снимок сырых HTTP-ответов, полученных при загрузке источников.
+///
+/// Снимок сохраняется в каталог с файлом `manifest.json` и телами ответов в
+/// `bodies/`. Сам снимок реализует [`Transport`], поэтому загруженный с диска
+/// снимок позволяет повторить расчёт без обращения к сети. Адреса, которых нет
+/// в снимке, отвечают статусом 404.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    created_at: u64,
+    langrank_version: String,
+    responses: Vec<(String, HttpResponse)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    format_version: u32,
+    langrank_version: String,
+    created_at: u64,
+    responses: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestEntry {
+    url: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Snapshot {
+    /// Создаёт пустой снимок с текущим временем и версией библиотеки.
+    #[must_use]
+    pub fn new() -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Self {
+            created_at,
+            langrank_version: env!("CARGO_PKG_VERSION").to_owned(),
+            responses: Vec::new(),
+        }
+    }
+
+    /// Загружает снимок из каталога.
+    ///
+    /// # Errors
+    ///
+    /// Возвращает ошибку, если манифест или тела ответов не удалось прочитать
+    /// либо версия формата не поддерживается.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let manifest_bytes = fs::read(&manifest_path)
+            .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+        let manifest: Manifest = serde_json::from_slice(&manifest_bytes)
+            .with_context(|| format!("failed to parse {}", manifest_path.display()))?;
+        if manifest.format_version != SNAPSHOT_FORMAT_VERSION {
+            return Err(anyhow!(
+                "unsupported snapshot format version {} in {} (expected {SNAPSHOT_FORMAT_VERSION})",
+                manifest.format_version,
+                manifest_path.display()
+            ));
+        }
+
+        let mut responses = Vec::with_capacity(manifest.responses.len());
+        for entry in manifest.responses {
+            let body_path = dir.join(&entry.body);
+            let body = fs::read(&body_path)
+                .with_context(|| format!("failed to read {}", body_path.display()))?;
+            let response = entry.headers.into_iter().fold(
+                HttpResponse::new(entry.status, body),
+                |response, (name, value)| response.with_header(name, value),
+            );
+            responses.push((entry.url, response));
+        }
+
+        Ok(Self {
+            created_at: manifest.created_at,
+            langrank_version: manifest.langrank_version,
+            responses,
+        })
+    }
+
+    /// Сохраняет снимок в каталог, создавая его при необходимости.
+    ///
+    /// # Errors
+    ///
+    /// Возвращает ошибку, если файлы снимка не удалось записать.
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let bodies_dir = dir.join(BODIES_DIR);
+        fs::create_dir_all(&bodies_dir)
+            .with_context(|| format!("failed to create directory {}", bodies_dir.display()))?;
+
+        let mut entries = Vec::with_capacity(self.responses.len());
+        for (index, (url, response)) in self.responses.iter().enumerate() {
+            let body = format!("{BODIES_DIR}/{:04}.bin", index + 1);
+            let body_path = dir.join(&body);
+            fs::write(&body_path, response.body())
+                .with_context(|| format!("failed to write {}", body_path.display()))?;
+            entries.push(ManifestEntry {
+                url: url.clone(),
+                status: response.status(),
+                headers: response.headers().to_vec(),
+                body,
+            });
+        }
+
+        let manifest = Manifest {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            langrank_version: self.langrank_version.clone(),
+            created_at: self.created_at,
+            responses: entries,
+        };
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let serialized = serde_json::to_vec_pretty(&manifest)
+            .context("failed to serialize snapshot manifest")?;
+        fs::write(&manifest_path, serialized)
+            .with_context(|| format!("failed to write {}", manifest_path.display()))
+    }
+
+    /// Добавляет ответ, заменяя ранее сохранённый ответ для того же адреса.
+    pub fn insert(&mut self, url: impl Into<String>, response: HttpResponse) {
+        let url = url.into();
+        if let Some((_, existing)) = self.responses.iter_mut().find(|(known, _)| *known == url) {
+            *existing = response;
+        } else {
+            self.responses.push((url, response));
+        }
+    }
+
+    /// Возвращает сохранённый ответ для адреса.
+    #[must_use]
+    pub fn get(&self, url: &str) -> Option<&HttpResponse> {
+        self.responses
+            .iter()
+            .find(|(known, _)| known == url)
+            .map(|(_, response)| response)
+    }
+
+    /// Возвращает адреса сохранённых ответов в порядке записи.
+    pub fn urls(&self) -> impl Iterator<Item = &str> {
+        self.responses.iter().map(|(url, _)| url.as_str())
+    }
+
+    /// Возвращает количество сохранённых ответов.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    /// Проверяет, что снимок не содержит ответов.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+
+    /// Возвращает время создания снимка в секундах Unix.
+    #[must_use]
+    pub const fn created_at(&self) -> u64 {
+        self.created_at
+    }
+
+    /// Возвращает версию langrank, которая записала снимок.
+    #[must_use]
+    pub fn langrank_version(&self) -> &str {
+        &self.langrank_version
+    }
+}
+
+impl Default for Snapshot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transport for Snapshot {
+    fn get<'a>(&'a self, url: &'a str) -> TransportFuture<'a> {
+        let response = Self::get(self, url).cloned().unwrap_or_else(|| {
+            HttpResponse::new(
+                404,
+                format!("{url} is not recorded in snapshot").into_bytes(),
+            )
+        });
+        Box::pin(async move { Ok(response) })
+    }
+}
+
+/// Транспорт, который сохраняет в [`Snapshot`] каждый полученный ответ.
+#[derive(Debug)]
+pub struct RecordingTransport<T> {
+    inner: T,
+    snapshot: Mutex<Snapshot>,
+}
+
+impl<T> RecordingTransport<T> {
+    /// Оборачивает транспорт, начиная запись в пустой снимок.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            snapshot: Mutex::new(Snapshot::new()),
+        }
+    }
+
+    /// Возвращает копию записанного к этому моменту снимка.
+    pub fn snapshot(&self) -> Snapshot {
+        self.snapshot
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Завершает запись и возвращает снимок.
+    pub fn into_snapshot(self) -> Snapshot {
+        self.snapshot
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T: Transport> Transport for RecordingTransport<T> {
+    fn get<'a>(&'a self, url: &'a str) -> TransportFuture<'a> {
+        self.get_with_headers(url, &[])
+    }
+
+    fn get_with_headers<'a>(
+        &'a self,
+        url: &'a str,
+        headers: &'a [(String, String)],
+    ) -> TransportFuture<'a> {
+        Box::pin(async move {
+            let response = self.inner.get_with_headers(url, headers).await?;
+            self.snapshot
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert(url, response.clone());
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RecordingTransport, Snapshot};
+    use crate::{HttpResponse, Transport};
+
+    #[tokio::test]
+    async fn records_and_replays_responses_through_a_directory() {
+        let mut source = Snapshot::new();
+        source.insert(
+            "https://example.com/a",
+            HttpResponse::new(200, b"alpha".to_vec()).with_header("ETag", "\"1\""),
+        );
+        let recorder = RecordingTransport::new(source);
+        let response = recorder
+            .get("https://example.com/a")
+            .await
+            .expect("recorded URL should respond");
+        assert_eq!(response.body(), b"alpha");
+
+        let dir = std::env::temp_dir().join(format!("langrank-snapshot-{}", std::process::id()));
+        let snapshot = recorder.into_snapshot();
+        snapshot.save(&dir).expect("snapshot should be saved");
+        let replayed = Snapshot::load(&dir).expect("snapshot should be loaded");
+        std::fs::remove_dir_all(&dir).expect("temporary snapshot should be removed");
+
+        assert_eq!(replayed, snapshot);
+        let hit = replayed
+            .get("https://example.com/a")
+            .expect("URL should be replayed");
+        assert_eq!(hit.header("etag"), Some("\"1\""));
+        let miss = Transport::get(&replayed, "https://example.com/b")
+            .await
+            .expect("missing URL still produces a response");
+        assert_eq!(miss.status(), 404);
+    }
+}
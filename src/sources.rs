@@ -4,128 +4,396 @@ mod pypl;
 mod techempower;
 mod tiobe;
 
-pub use benchmarks::{download_benchmark_data, load_benchmark_scores};
-pub use languish::fetch_languish;
-pub use pypl::fetch_pypl;
-pub use techempower::{TECHEMPOWER_MAX_SCORE, fetch_techempower};
-pub use tiobe::fetch_tiobe;
+pub use benchmarks::{
+    download_benchmark_data, download_benchmark_data_with, load_benchmark_results,
+    load_benchmark_results_with, load_benchmark_results_with_options, load_benchmark_scores,
+    load_benchmark_scores_with_options,
+};
+pub use languish::{
+    fetch_languish, fetch_languish_history, fetch_languish_history_with, fetch_languish_with,
+    fetch_languish_with_options,
+};
+pub use pypl::{fetch_pypl, fetch_pypl_with};
+pub use techempower::{
+    TECHEMPOWER_MAX_SCORE, discover_techempower_rounds_with, fetch_techempower,
+    fetch_techempower_results, fetch_techempower_round, fetch_techempower_with,
+    list_techempower_rounds, with_static_rounds,
+};
+pub use tiobe::{fetch_tiobe, fetch_tiobe_history, fetch_tiobe_history_with, fetch_tiobe_with};
 
-use crate::RankingEntry;
+use crate::cache::{CachedResponse, HttpCache, PublicationPeriod, unix_now};
 use crate::parsing::{parse_percent, parse_u32};
-use anyhow::{Context, Result, anyhow};
-use reqwest::{Client, Response};
+use crate::{
+    AliasRegistry, DataSource, HttpResponse, Language, RankingEntry, SourceError, Transport,
+};
+use anyhow::{Result, anyhow};
+use reqwest::Url;
 use rustc_hash::FxHashMap;
 use scraper::ElementRef;
-use std::sync::OnceLock;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
 use std::time::Duration;
-use tokio::time::sleep;
+use tokio::time::{sleep, timeout};
 
-const MAX_RETRIES: usize = 3;
+const NOT_MODIFIED: u16 = 304;
 
-mod raw_entry {
-    use super::CanonicalLanguage;
+/// Параметры повторных HTTP-запросов.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+    pub jitter: bool,
+    pub request_timeout: Duration,
+}
+
+impl RetryPolicy {
+    pub const DEFAULT: Self = Self {
+        max_attempts: 3,
+        base_backoff: Duration::from_secs(2),
+        max_backoff: Duration::from_mins(1),
+        jitter: true,
+        request_timeout: Duration::from_secs(20),
+    };
+
+    fn backoff(self, attempt: u32) -> Duration {
+        let delay = calculate_backoff(self.base_backoff, self.max_backoff, attempt);
+        if self.jitter {
+            apply_jitter(delay)
+        } else {
+            delay
+        }
+    }
+}
+
+/// Транспорт, политика повторов и кэш, с которыми загружается источник.
+#[derive(Clone, Copy)]
+pub struct HttpContext<'a> {
+    transport: &'a dyn Transport,
+    policy: &'a RetryPolicy,
+    cache: Option<&'a HttpCache>,
+    period: Option<PublicationPeriod>,
+    urls: Option<&'a SourceUrls>,
+}
+
+impl<'a> HttpContext<'a> {
+    pub const fn new(transport: &'a dyn Transport, policy: &'a RetryPolicy) -> Self {
+        Self {
+            transport,
+            policy,
+            cache: None,
+            period: None,
+            urls: None,
+        }
+    }
+
+    pub const fn with_cache(mut self, cache: Option<&'a HttpCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub const fn with_period(mut self, period: PublicationPeriod) -> Self {
+        self.period = Some(period);
+        self
+    }
+
+    pub const fn with_urls(mut self, urls: &'a SourceUrls) -> Self {
+        self.urls = Some(urls);
+        self
+    }
+
+    /// Возвращает адрес источника с учётом переопределений.
+    fn url(self, source: impl Into<DataSource>, default: &'a str) -> &'a str {
+        self.urls
+            .and_then(|urls| urls.get(source.into()))
+            .unwrap_or(default)
+    }
+
+    const fn with_defaults(transport: &'a dyn Transport) -> Self {
+        Self::new(transport, &RetryPolicy::DEFAULT)
+    }
+}
+
+/// Адреса встроенных источников, заданные вместо стандартных.
+///
+/// Для Languish и `TechEmpower` это адрес каталога, от которого разрешаются
+/// относительные ссылки на бандлы и результаты раундов.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceUrls {
+    overrides: FxHashMap<DataSource, String>,
+}
+
+impl SourceUrls {
+    pub fn set(&mut self, source: DataSource, url: String) {
+        self.overrides.insert(source, url);
+    }
+
+    fn get(&self, source: DataSource) -> Option<&str> {
+        self.overrides.get(&source).map(String::as_str)
+    }
+}
 
+/// Разрешает ссылку относительно адреса страницы, как это делает браузер.
+fn resolve_url(base_url: &str, href: &str) -> String {
+    if href.starts_with("http://") || href.starts_with("https://") {
+        return href.to_string();
+    }
+    if let Ok(base) = Url::parse(base_url)
+        && let Ok(joined) = base.join(href)
+    {
+        return joined.to_string();
+    }
+    if href.starts_with('/') {
+        format!("{base_url}{href}")
+    } else {
+        format!("{base_url}/{href}")
+    }
+}
+
+mod raw_entry {
     #[derive(Debug)]
     pub(super) struct RawEntry {
-        lang: CanonicalLanguage,
+        lang: String,
         rank: Option<u32>,
-        share: f64,
+        share: Option<f64>,
         trend: Option<f64>,
+        previous_rank: Option<u32>,
     }
 
     impl RawEntry {
         pub(super) fn parse(
             lang: &str,
             rank: Option<u32>,
-            share: f64,
+            share: Option<f64>,
             trend: Option<f64>,
         ) -> Option<Self> {
-            let lang = CanonicalLanguage::parse(lang)?;
+            let lang = lang.trim();
+            if lang.is_empty() {
+                return None;
+            }
             Some(Self {
-                lang,
+                lang: lang.to_owned(),
                 rank,
                 share,
                 trend,
+                previous_rank: None,
             })
         }
 
-        pub(super) fn into_parts(self) -> (CanonicalLanguage, Option<u32>, f64, Option<f64>) {
+        pub(super) const fn with_previous_rank(mut self, previous_rank: Option<u32>) -> Self {
+            self.previous_rank = previous_rank;
+            self
+        }
+
+        pub(super) fn into_parts(self) -> (String, Option<u32>, Option<f64>, Option<f64>) {
             (self.lang, self.rank, self.share, self.trend)
         }
+
+        pub(super) const fn previous_rank(&self) -> Option<u32> {
+            self.previous_rank
+        }
     }
 }
 
 use raw_entry::RawEntry;
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-struct CanonicalLanguage(String);
-
-impl CanonicalLanguage {
-    fn parse(input: &str) -> Option<Self> {
-        canonicalize_language(input).map(Self)
-    }
-
-    fn into_string(self) -> String {
-        self.0
-    }
-}
-
 #[derive(Default)]
 struct AggregatedEntry {
     min_rank: Option<u32>,
+    min_previous_rank: Option<u32>,
     share_sum: f64,
+    share_seen: bool,
     trend_sum: f64,
     trend_seen: bool,
 }
 
-async fn fetch_text_with_retry(client: &Client, url: &str) -> Result<String> {
-    send_with_retry(client, url)
-        .await?
-        .text()
-        .await
-        .with_context(|| format!("failed to read response body from {url}"))
+async fn fetch_text_with_retry(http: HttpContext<'_>, url: &str) -> Result<String> {
+    let body = fetch_bytes_with_retry(http, url).await?;
+    Ok(String::from_utf8(body).unwrap_or_else(|error| {
+        let valid_up_to = error.utf8_error().valid_up_to();
+        eprintln!(
+            "Warning: {url} is not valid UTF-8 (first bad byte at offset {valid_up_to}); \
+             invalid bytes are replaced with U+FFFD."
+        );
+        String::from_utf8_lossy(error.as_bytes()).into_owned()
+    }))
 }
 
-async fn fetch_bytes_with_retry(client: &Client, url: &str) -> Result<Vec<u8>> {
-    let bytes = send_with_retry(client, url)
-        .await?
-        .bytes()
-        .await
-        .with_context(|| format!("failed to read response body from {url}"))?;
-    Ok(bytes.to_vec())
+async fn fetch_bytes_with_retry(http: HttpContext<'_>, url: &str) -> Result<Vec<u8>> {
+    Ok(send_with_retry(http, url).await?.into_body())
 }
 
-async fn send_with_retry(client: &Client, url: &str) -> Result<Response> {
+async fn send_with_retry(http: HttpContext<'_>, url: &str) -> Result<HttpResponse> {
+    let Some(cache) = http.cache else {
+        return request_with_retry(http, url, &[]).await;
+    };
+
+    let now = unix_now();
+    let cached = cache.load(url);
+    if let Some(cached) = &cached
+        && cache.is_fresh(cached, http.period, now)
+    {
+        return Ok(cached.clone().into_response());
+    }
+
+    let validators = cached
+        .as_ref()
+        .map(CachedResponse::validators)
+        .unwrap_or_default();
+    let response = request_with_retry(http, url, &validators).await?;
+    match cached {
+        Some(cached) if response.status() == NOT_MODIFIED => {
+            let cached = cached.into_response();
+            cache.store(url, &cached, now);
+            Ok(cached)
+        }
+        _ => {
+            cache.store(url, &response, now);
+            Ok(response)
+        }
+    }
+}
+
+async fn request_with_retry(
+    http: HttpContext<'_>,
+    url: &str,
+    headers: &[(String, String)],
+) -> Result<HttpResponse> {
+    let policy = http.policy;
+    let conditional = !headers.is_empty();
+    let max_attempts = policy.max_attempts.max(1);
     let mut last_err: Option<anyhow::Error> = None;
-    for attempt in 1..=MAX_RETRIES {
-        match client.get(url).send().await {
-            Ok(response) => match response.error_for_status() {
-                Ok(success) => return Ok(success),
-                Err(err) => last_err = Some(err.into()),
-            },
-            Err(err) => last_err = Some(err.into()),
+    let mut last_status = None;
+    let mut attempts = 0;
+    while attempts < max_attempts {
+        attempts += 1;
+        let mut retry_after = None;
+        match timeout(
+            policy.request_timeout,
+            http.transport.get_with_headers(url, headers),
+        )
+        .await
+        {
+            Ok(Ok(response))
+                if response.is_success() || (conditional && response.status() == NOT_MODIFIED) =>
+            {
+                return Ok(response);
+            }
+            Ok(Ok(response)) => {
+                let status = response.status();
+                last_status = Some(status);
+                last_err = Some(anyhow!("HTTP status {status} for {url}"));
+                if !is_retryable_status(status) {
+                    break;
+                }
+                if matches!(status, 429 | 503) {
+                    retry_after = parse_retry_after(&response, unix_now());
+                }
+            }
+            Ok(Err(err)) => {
+                last_status = None;
+                last_err = Some(err);
+            }
+            Err(_) => {
+                last_status = None;
+                last_err = Some(anyhow!(
+                    "request to {url} timed out after {:?}",
+                    policy.request_timeout
+                ));
+            }
         }
 
-        if attempt < MAX_RETRIES {
-            sleep(calculate_backoff(attempt)).await;
+        if attempts < max_attempts {
+            let delay = retry_after.map_or_else(
+                || policy.backoff(attempts),
+                |delay| delay.min(policy.max_backoff),
+            );
+            sleep(delay).await;
         }
     }
 
     let detail = last_err
         .as_ref()
         .map_or_else(|| "unknown error".to_string(), describe_error);
-    Err(anyhow!(
-        "failed to fetch {url} after {MAX_RETRIES} attempts: {detail}"
-    ))
+    Err(SourceError::Http {
+        url: url.to_owned(),
+        status: last_status,
+        attempts,
+        detail,
+    }
+    .into())
 }
 
-fn calculate_backoff(attempt: usize) -> Duration {
+const fn is_retryable_status(status: u16) -> bool {
+    !matches!(status, 400..=499) || matches!(status, 408 | 425 | 429)
+}
+
+/// Разбирает `Retry-After` в секундах или датой HTTP в формате IMF-fixdate
+/// (`Sun, 06 Nov 1994 08:49:37 GMT`); устаревшие форматы RFC 850 и asctime
+/// не поддерживаются. Прошедшая дата означает повтор без ожидания.
+fn parse_retry_after(response: &HttpResponse, now: u64) -> Option<Duration> {
+    let value = response.header("retry-after")?.trim();
+    let seconds = match value.parse::<u64>() {
+        Ok(seconds) => seconds,
+        Err(_) => parse_http_date(value)?.saturating_sub(now),
+    };
+    Some(Duration::from_secs(seconds))
+}
+
+/// Переводит дату IMF-fixdate в секунды Unix.
+fn parse_http_date(value: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let (_, date) = value.split_once(", ")?;
+    let [day, month, year, time, "GMT"] = date.split(' ').collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    let month = MONTHS.iter().position(|&name| name == month)?;
+    let [hours, minutes, seconds] = time.split(':').collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    let days = days_from_civil(
+        year.parse().ok()?,
+        u64::try_from(month).ok()? + 1,
+        day.parse().ok()?,
+    )?;
+    let (hours, minutes, seconds): (u64, u64, u64) = (
+        hours.parse().ok()?,
+        minutes.parse().ok()?,
+        seconds.parse().ok()?,
+    );
+    if hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+    Some(days * 86_400 + hours * 3_600 + minutes * 60 + seconds)
+}
+
+/// Число дней от 1970-01-01 до даты григорианского календаря.
+fn days_from_civil(year: u64, month: u64, day: u64) -> Option<u64> {
+    if year < 1970 || !(1..=31).contains(&day) {
+        return None;
+    }
+    // Год начинается с марта, чтобы високосный день оказался в конце года.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    (era * 146_097 + day_of_era).checked_sub(719_468)
+}
+
+fn calculate_backoff(base: Duration, max: Duration, attempt: u32) -> Duration {
     const MAX_BACKOFF_EXPONENT: u32 = 10;
-    let exponent = u32::try_from(attempt)
-        .unwrap_or(MAX_BACKOFF_EXPONENT)
-        .min(MAX_BACKOFF_EXPONENT);
-    let seconds = 2_u64.saturating_pow(exponent);
-    Duration::from_secs(seconds)
+    let exponent = attempt.saturating_sub(1).min(MAX_BACKOFF_EXPONENT);
+    base.saturating_mul(2_u32.pow(exponent)).min(max)
+}
+
+fn apply_jitter(delay: Duration) -> Duration {
+    let half = delay / 2;
+    let random = RandomState::new().build_hasher().finish();
+    let span = u64::try_from(half.as_nanos()).unwrap_or(u64::MAX);
+    half + Duration::from_nanos(random % span.saturating_add(1))
 }
 
 fn describe_error(error: &anyhow::Error) -> String {
@@ -149,16 +417,29 @@ fn describe_error(error: &anyhow::Error) -> String {
     }
 }
 
-fn aggregate_entries(entries: Vec<RawEntry>) -> Vec<RankingEntry> {
-    let mut aggregated: FxHashMap<CanonicalLanguage, AggregatedEntry> = FxHashMap::default();
+fn aggregate_entries(entries: Vec<RawEntry>, aliases: &AliasRegistry) -> Vec<RankingEntry> {
+    let mut aggregated: FxHashMap<Language, AggregatedEntry> = FxHashMap::default();
 
     for entry in entries {
+        let previous_rank = entry.previous_rank();
         let (lang, rank, share, trend) = entry.into_parts();
+        let Some(lang) = aliases.canonicalize(&lang) else {
+            continue;
+        };
         let agg = aggregated.entry(lang).or_default();
-        agg.share_sum += share;
+        if let Some(share) = share {
+            agg.share_sum += share;
+            agg.share_seen = true;
+        }
         if let Some(rank) = rank {
             agg.min_rank = Some(agg.min_rank.map_or(rank, |existing| existing.min(rank)));
         }
+        if let Some(rank) = previous_rank {
+            agg.min_previous_rank = Some(
+                agg.min_previous_rank
+                    .map_or(rank, |existing| existing.min(rank)),
+            );
+        }
         if let Some(trend) = trend {
             agg.trend_sum += trend;
             agg.trend_seen = true;
@@ -168,21 +449,32 @@ fn aggregate_entries(entries: Vec<RawEntry>) -> Vec<RankingEntry> {
     let mut result: Vec<RankingEntry> = aggregated
         .into_iter()
         .map(|(lang, agg)| RankingEntry {
-            lang: lang.into_string(),
+            lang,
             rank: agg.min_rank,
-            share: agg.share_sum,
-            trend: if agg.trend_seen {
-                Some(agg.trend_sum)
-            } else {
-                None
-            },
+            share: agg.share_seen.then_some(agg.share_sum),
+            trend: agg.trend_seen.then_some(agg.trend_sum),
+            previous_rank: agg.min_previous_rank,
         })
         .collect();
 
-    result.sort_by(|a, b| a.lang.cmp(&b.lang));
+    result.sort_by_key(|entry| entry.lang);
     result
 }
 
+pub fn canonicalize_entries(
+    entries: Vec<RankingEntry>,
+    aliases: &AliasRegistry,
+) -> Vec<RankingEntry> {
+    let raw_entries = entries
+        .into_iter()
+        .filter_map(|entry| {
+            RawEntry::parse(entry.lang.name(), entry.rank, entry.share, entry.trend)
+                .map(|raw| raw.with_previous_rank(entry.previous_rank))
+        })
+        .collect();
+    aggregate_entries(raw_entries, aliases)
+}
+
 fn extract_cell_text(cell: ElementRef<'_>) -> String {
     let mut out = String::new();
     for chunk in cell.text() {
@@ -198,134 +490,178 @@ fn extract_cell_text(cell: ElementRef<'_>) -> String {
     out
 }
 
-fn normalize_alias_key(input: &str) -> String {
-    let mut out = String::with_capacity(input.len());
-    for ch in input.chars() {
-        if ch.is_whitespace() {
-            continue;
-        }
-        out.extend(ch.to_lowercase());
+#[cfg(test)]
+mod tests {
+    use super::{
+        HttpContext, RetryPolicy, apply_jitter, calculate_backoff, parse_retry_after,
+        send_with_retry,
+    };
+    use crate::cache::{PublicationPeriod, unix_now};
+    use crate::{HttpCache, HttpResponse, Transport, TransportFuture};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    const NO_WAIT: RetryPolicy = RetryPolicy {
+        max_attempts: 3,
+        base_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+        jitter: false,
+        request_timeout: Duration::from_secs(5),
+    };
+
+    struct ScriptedTransport {
+        responses: Mutex<Vec<HttpResponse>>,
+        calls: Mutex<usize>,
+        last_headers: Mutex<Vec<(String, String)>>,
     }
-    out
-}
 
-fn canonicalize_language(input: &str) -> Option<String> {
-    let trimmed = input.trim();
-    if trimmed.is_empty() {
-        return None;
+    impl ScriptedTransport {
+        fn new(mut responses: Vec<HttpResponse>) -> Self {
+            responses.reverse();
+            Self {
+                responses: Mutex::new(responses),
+                calls: Mutex::new(0),
+                last_headers: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn calls(&self) -> usize {
+            *self.calls.lock().expect("call counter lock")
+        }
     }
-    let lookup_key = normalize_alias_key(trimmed);
-    let alias_map = canonical_aliases();
-    if let Some(&alias) = alias_map.get(lookup_key.as_str()) {
-        if alias.is_empty() {
-            return None;
+
+    impl Transport for ScriptedTransport {
+        fn get<'a>(&'a self, url: &'a str) -> TransportFuture<'a> {
+            self.get_with_headers(url, &[])
+        }
+
+        fn get_with_headers<'a>(
+            &'a self,
+            _url: &'a str,
+            headers: &'a [(String, String)],
+        ) -> TransportFuture<'a> {
+            headers.clone_into(&mut self.last_headers.lock().expect("headers lock"));
+            *self.calls.lock().expect("call counter lock") += 1;
+            let response = self
+                .responses
+                .lock()
+                .expect("response script lock")
+                .pop()
+                .expect("script should cover every request");
+            Box::pin(async move { Ok(response) })
         }
-        return Some(alias.to_string());
     }
-    Some(trimmed.to_string())
-}
 
-fn canonical_aliases() -> &'static FxHashMap<&'static str, &'static str> {
-    static CANONICAL_ALIASES: OnceLock<FxHashMap<&'static str, &'static str>> = OnceLock::new();
-    CANONICAL_ALIASES.get_or_init(|| {
-        [
-            ("delphi/objectpascal", "Delphi/Pascal"),
-            ("matlab", "Matlab"),
-            ("cobol", "COBOL"),
-            ("powershell", "PowerShell"),
-            ("vbscript", "VBA/VBS"),
-            ("vba", "VBA/VBS"),
-            // ("classicvisualbasic", "VBA/VBS"),
-            ("abap", "Abap"),
-            ("(visual)foxpro", "FoxPro"),
-            ("c", "C"),
-            ("c#", "C#"),
-            ("csharp", "C#"),
-            ("c-sharp", "C#"),
-            ("c++", "C++"),
-            ("c/c++", "C/C++"),
-            ("f#", "F#"),
-            ("fsharp", "F#"),
-            ("f-sharp", "F#"),
-            ("javascript", "JavaScript"),
-            ("js", "JavaScript"),
-            ("node", "JavaScript"),
-            ("node.js", "JavaScript"),
-            ("nodejs", "JavaScript"),
-            ("typescript", "TypeScript"),
-            ("ts", "TypeScript"),
-            ("objective-c", "Objective-C"),
-            ("objectivec", "Objective-C"),
-            ("obj-c", "Objective-C"),
-            ("objc", "Objective-C"),
-            ("golang", "Go"),
-            ("go", "Go"),
-            ("cpp", "C++"),
-            ("vb", "Visual Basic"),
-            ("vb.net", "Visual Basic"),
-            ("vbnet", "Visual Basic"),
-            ("visualbasic", "Visual Basic"),
-            ("visualbasic.net", "Visual Basic"),
-            ("cfml", "CFML"),
-            ("clojure", "Clojure"),
-            ("commonlisp", "Lisp"),
-            ("crystal", "Crystal"),
-            ("d", "D"),
-            ("dart", "Dart"),
-            ("elixir", "Elixir"),
-            ("fortran", "Fortran"),
-            ("haskell", "Haskell"),
-            ("julia", "Julia"),
-            ("kotlin", "Kotlin"),
-            ("lua", "Lua"),
-            ("luau", "Luau"),
-            ("nim", "Nim"),
-            ("pascal", "Delphi/Pascal"),
-            ("prolog", "Prolog"),
-            ("python", "Python"),
-            ("r", "R"),
-            ("ruby", "Ruby"),
-            ("rust", "Rust"),
-            ("scala", "Scala"),
-            ("swift", "Swift"),
-            ("ur", "Ur"),
-            ("v", "V"),
-            ("vala", "Vala"),
-            ("zig", "Zig"),
-            // Benchmarks Game aliases and runtimes.
-            ("chapel", "Chapel"),
-            ("clang", "C/C++"),
-            ("csharpaot", "C#"),
-            ("csharpcore", "C#"),
-            ("dartexe", "Dart"),
-            ("dartjit", "Dart"),
-            ("erlang", "Erlang"),
-            ("fpascal", "Delphi/Pascal"),
-            ("fsharpcore", "F#"),
-            ("gcc", "C/C++"),
-            ("ghc", "Haskell"),
-            ("gnat", "Ada"),
-            ("gpp", "C/C++"),
-            ("graalvm", "Graal"),
-            ("icx", "C/C++"),
-            ("ifc", "Fortran"),
-            ("ifx", "Fortran"),
-            ("java", "Java"),
-            ("javaxint", "Java"),
-            ("micropython", "Python"),
-            ("mri", "Ruby"),
-            ("ocaml", "OCaml"),
-            ("openj9", "Java"),
-            ("perl", "Perl"),
-            ("pharo", "Smalltalk"),
-            ("php", "PHP"),
-            ("python3", "Python"),
-            ("racket", "Racket"),
-            ("sbcl", "Lisp"),
-            ("toit", "Toit"),
-            ("vw", ""),
-        ]
-        .into_iter()
-        .collect()
-    })
+    #[tokio::test]
+    async fn does_not_retry_non_retryable_client_errors() {
+        let transport = ScriptedTransport::new(vec![HttpResponse::new(404, Vec::new())]);
+
+        let error = send_with_retry(HttpContext::new(&transport, &NO_WAIT), "https://x.test")
+            .await
+            .expect_err("404 should fail");
+
+        assert_eq!(transport.calls(), 1);
+        assert!(error.to_string().contains("after 1 attempts"));
+    }
+
+    #[tokio::test]
+    async fn retries_rate_limited_responses_until_success() {
+        let transport = ScriptedTransport::new(vec![
+            HttpResponse::new(429, Vec::new()).with_header("Retry-After", "120"),
+            HttpResponse::new(503, Vec::new()),
+            HttpResponse::new(200, b"ok".to_vec()),
+        ]);
+
+        let response = send_with_retry(HttpContext::new(&transport, &NO_WAIT), "https://x.test")
+            .await
+            .expect("third attempt should succeed");
+
+        assert_eq!(response.body(), b"ok");
+        assert_eq!(transport.calls(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_for_the_retry_after_delay() {
+        let transport = ScriptedTransport::new(vec![
+            HttpResponse::new(503, Vec::new()).with_header("Retry-After", "7"),
+            HttpResponse::new(200, b"ok".to_vec()),
+        ]);
+        let policy = RetryPolicy {
+            max_backoff: Duration::from_mins(1),
+            ..NO_WAIT
+        };
+
+        let started = tokio::time::Instant::now();
+        let response = send_with_retry(HttpContext::new(&transport, &policy), "https://x.test")
+            .await
+            .expect("second attempt should succeed");
+
+        assert_eq!(response.body(), b"ok");
+        assert_eq!(started.elapsed(), Duration::from_secs(7));
+    }
+
+    #[test]
+    fn parses_retry_after_seconds_and_http_dates() {
+        let retry_after = |value| {
+            parse_retry_after(
+                &HttpResponse::new(503, Vec::new()).with_header("Retry-After", value),
+                784_111_747,
+            )
+        };
+
+        assert_eq!(retry_after(" 90 "), Some(Duration::from_secs(90)));
+        assert_eq!(
+            retry_after("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            retry_after("Tue, 29 Feb 2000 00:00:00 GMT"),
+            Some(Duration::from_secs(951_782_400 - 784_111_747))
+        );
+        assert_eq!(
+            retry_after("Sat, 05 Nov 1994 00:00:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(retry_after("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(retry_after("soon"), None);
+    }
+
+    #[tokio::test]
+    async fn revalidates_stale_cache_entries_with_conditional_requests() {
+        let dir = std::env::temp_dir().join(format!("langrank-revalidate-{}", std::process::id()));
+        let cache = HttpCache::new(&dir);
+        let stored = HttpResponse::new(200, b"cached".to_vec()).with_header("ETag", "\"v1\"");
+        cache.store("https://x.test", &stored, 0);
+        let transport = ScriptedTransport::new(vec![HttpResponse::new(304, Vec::new())]);
+
+        let http = HttpContext::new(&transport, &NO_WAIT)
+            .with_cache(Some(&cache))
+            .with_period(PublicationPeriod::Monthly);
+        let response = send_with_retry(http, "https://x.test").await;
+        let reloaded = cache.load("https://x.test");
+        std::fs::remove_dir_all(&dir).expect("temporary cache should be removed");
+
+        assert_eq!(response.expect("304 should reuse the cached body"), stored);
+        assert_eq!(transport.calls(), 1);
+        assert_eq!(
+            *transport.last_headers.lock().expect("headers lock"),
+            [("If-None-Match".to_owned(), "\"v1\"".to_owned())]
+        );
+        let reloaded = reloaded.expect("revalidated entry should stay in the cache");
+        assert!(cache.is_fresh(&reloaded, Some(PublicationPeriod::Monthly), unix_now()));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_limit_and_jitter_stays_in_range() {
+        let base = Duration::from_secs(2);
+        let max = Duration::from_secs(10);
+
+        assert_eq!(calculate_backoff(base, max, 1), Duration::from_secs(2));
+        assert_eq!(calculate_backoff(base, max, 2), Duration::from_secs(4));
+        assert_eq!(calculate_backoff(base, max, 4), max);
+
+        let jittered = apply_jitter(Duration::from_secs(8));
+        assert!(jittered >= Duration::from_secs(4));
+        assert!(jittered <= Duration::from_secs(8));
+    }
 }
@@ -1,12 +1,19 @@
-use anyhow::{Context, Result, anyhow};
+use crate::aliases::builtin as builtin_aliases;
+use crate::{
+    AliasRegistry, AliasResolution, BenchmarkResults, BenchmarkScoring, Language,
+    LanguageBenchmark, PerformanceSource, RunSelection, SourceError, TaskBenchmark, TimeMeasure,
+    Transport, VariantScore,
+};
+use anyhow::{Context, Result};
 use csv::StringRecord;
-use reqwest::Client;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use std::io::Cursor;
+use std::sync::Arc;
 use tokio::task;
 
-use super::{CanonicalLanguage, fetch_bytes_with_retry};
+use super::{HttpContext, fetch_bytes_with_retry};
 
+const BENCHMARK_CSV: &str = "Benchmarks Game CSV";
 const BENCH_URL: &str = "https://salsa.debian.org/benchmarksgame-team/benchmarksgame/-/raw/master/public/data/alldata.csv";
 
 #[derive(Default)]
@@ -27,22 +34,145 @@ impl StringInterner {
         id
     }
 
-    const fn len(&self) -> usize {
-        self.values.len()
-    }
-
     fn into_strings(self) -> Vec<String> {
         self.values
     }
 }
 
-type LanguageId = usize;
 type TaskId = usize;
 
-#[derive(Clone, Copy, Default)]
-struct GeometricMeanStats {
-    log_sum: f64,
-    sample_count: usize,
+/// Колонка CSV с измерением запуска; меньшее значение считается лучшим.
+#[derive(Clone, Copy)]
+enum Column {
+    ElapsedTime,
+    CpuTime,
+    Memory,
+    SourceSize,
+}
+
+impl Column {
+    const ALL: [Self; 4] = [
+        Self::ElapsedTime,
+        Self::CpuTime,
+        Self::Memory,
+        Self::SourceSize,
+    ];
+
+    const fn header(self) -> &'static str {
+        match self {
+            Self::ElapsedTime => "elapsed-time(s)",
+            Self::CpuTime => "cpu-time(s)",
+            Self::Memory => "mem(KB)",
+            Self::SourceSize => "size(B)",
+        }
+    }
+
+    const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl From<TimeMeasure> for Column {
+    fn from(measure: TimeMeasure) -> Self {
+        match measure {
+            TimeMeasure::Elapsed => Self::ElapsedTime,
+            TimeMeasure::CpuTime => Self::CpuTime,
+        }
+    }
+}
+
+/// Значение языка на задаче после выбора запуска.
+#[derive(Clone, Copy)]
+struct LanguageTaskValue {
+    value: f64,
+    runs: usize,
+}
+
+/// Отношение значения языка к лучшему значению на задаче.
+#[derive(Clone, Copy)]
+struct TaskRatio {
+    task_id: TaskId,
+    value: f64,
+    fastest: f64,
+    ratio: f64,
+    runs: usize,
+}
+
+/// Значения одного измерения по всем успешным запускам.
+#[derive(Default)]
+struct MetricAccumulator {
+    runs_by_language_task: FxHashMap<(Language, TaskId), Vec<f64>>,
+}
+
+impl MetricAccumulator {
+    fn record(&mut self, language: Language, task_id: TaskId, value: f64) {
+        self.runs_by_language_task
+            .entry((language, task_id))
+            .or_default()
+            .push(value);
+    }
+
+    fn select(&self, selection: RunSelection) -> FxHashMap<(Language, TaskId), LanguageTaskValue> {
+        self.runs_by_language_task
+            .iter()
+            .map(|(&key, runs)| {
+                let value = select_run(runs, selection);
+                (
+                    key,
+                    LanguageTaskValue {
+                        value,
+                        runs: runs.len(),
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+fn select_run(runs: &[f64], selection: RunSelection) -> f64 {
+    match selection {
+        RunSelection::Best => runs.iter().copied().fold(f64::INFINITY, f64::min),
+        RunSelection::Median => {
+            let mut sorted = runs.to_vec();
+            sorted.sort_by(f64::total_cmp);
+            let middle = sorted.len() / 2;
+            if sorted.len().is_multiple_of(2) {
+                f64::midpoint(sorted[middle - 1], sorted[middle])
+            } else {
+                sorted[middle]
+            }
+        }
+    }
+}
+
+/// Группирует отношения к лучшему значению по языкам, оставляя только
+/// учитываемые задачи.
+fn ratios_by_language(
+    values: &FxHashMap<(Language, TaskId), LanguageTaskValue>,
+    included: &[bool],
+) -> FxHashMap<Language, Vec<TaskRatio>> {
+    let mut fastest = vec![f64::INFINITY; included.len()];
+    for (&(_, task_id), value) in values {
+        fastest[task_id] = fastest[task_id].min(value.value);
+    }
+
+    let mut ratios: FxHashMap<Language, Vec<TaskRatio>> = FxHashMap::default();
+    for (&(language, task_id), value) in values {
+        if !included[task_id] {
+            continue;
+        }
+        let ratio = fastest[task_id] / value.value;
+        if ratio.is_finite() && ratio > 0.0 {
+            ratios.entry(language).or_default().push(TaskRatio {
+                task_id,
+                value: value.value,
+                fastest: fastest[task_id],
+                ratio,
+                runs: value.runs,
+            });
+        }
+    }
+    ratios
 }
 
 #[derive(Clone, Copy)]
@@ -50,22 +180,26 @@ struct BenchmarkColumns {
     language: usize,
     task: usize,
     status: usize,
-    elapsed: usize,
+    values: [Option<usize>; Column::ALL.len()],
 }
 
 impl BenchmarkColumns {
-    fn parse(headers: &StringRecord) -> Result<Self> {
+    fn parse(headers: &StringRecord, scoring: &BenchmarkScoring) -> Result<Self> {
+        let position = |name| headers.iter().position(|header| header == name);
         let index = |name| {
-            headers
-                .iter()
-                .position(|header| header == name)
-                .ok_or_else(|| anyhow!("missing '{name}' column in benchmark data"))
+            position(name).ok_or_else(|| {
+                SourceError::missing_field(BENCHMARK_CSV, format!("column '{name}'"))
+            })
         };
+        let mut values = Column::ALL.map(|column| position(column.header()));
+        for column in [Column::ElapsedTime, Column::from(scoring.measure)] {
+            values[column.index()] = Some(index(column.header())?);
+        }
         Ok(Self {
             language: index("lang")?,
             task: index("name")?,
             status: index("status")?,
-            elapsed: index("elapsed-time(s)")?,
+            values,
         })
     }
 }
@@ -73,45 +207,93 @@ impl BenchmarkColumns {
 struct BenchmarkRow<'a> {
     language: &'a str,
     task: &'a str,
-    elapsed: f64,
+    /// Измерения успешного запуска; `None` для запуска с ошибкой или
+    /// тайм-аутом.
+    measurement: Option<[Option<f64>; Column::ALL.len()]>,
 }
 
 impl<'a> BenchmarkRow<'a> {
     fn parse(record: &'a StringRecord, columns: BenchmarkColumns) -> Option<Self> {
         let status = record.get(columns.status)?.trim().parse::<i64>().ok()?;
-        if status < 0 {
-            return None;
-        }
-
         let language = record.get(columns.language)?.trim();
         let task = record.get(columns.task)?.trim();
-        let elapsed = record.get(columns.elapsed)?.trim().parse::<f64>().ok()?;
-        if language.is_empty() || task.is_empty() || !elapsed.is_finite() || elapsed <= 0.0 {
+        if language.is_empty() || task.is_empty() {
             return None;
         }
+        if status < 0 {
+            return Some(Self {
+                language,
+                task,
+                measurement: None,
+            });
+        }
 
+        let values = columns
+            .values
+            .map(|column| column.and_then(|column| positive_value(record, column)));
+        values[Column::ElapsedTime.index()]?;
         Some(Self {
             language,
             task,
-            elapsed,
+            measurement: Some(values),
         })
     }
 }
 
-fn canonical_language_id(
+fn positive_value(record: &StringRecord, column: usize) -> Option<f64> {
+    let value = record.get(column)?.trim().parse::<f64>().ok()?;
+    if !value.is_finite() || value <= 0.0 {
+        return None;
+    }
+    Some(value)
+}
+
+/// Язык строки CSV; в режиме реализаций — реализация вместе с языком, к
+/// которому она относится.
+#[derive(Clone, Copy)]
+struct BenchmarkLanguage {
+    language: Language,
+    variant_of: Option<Language>,
+}
+
+fn canonical_language(
     raw: &str,
-    language_id_cache: &mut FxHashMap<String, Option<LanguageId>>,
-    languages: &mut StringInterner,
-) -> Option<LanguageId> {
-    if let Some(&cached) = language_id_cache.get(raw) {
+    aliases: &AliasRegistry,
+    split_variants: bool,
+    language_cache: &mut FxHashMap<String, Option<BenchmarkLanguage>>,
+) -> Option<BenchmarkLanguage> {
+    if let Some(&cached) = language_cache.get(raw) {
         return cached;
     }
 
-    let id = CanonicalLanguage::parse(raw)
-        .map(CanonicalLanguage::into_string)
-        .map(|language| languages.intern(&language));
-    language_id_cache.insert(raw.to_owned(), id);
-    id
+    let language = match aliases.resolve(raw) {
+        // Реализация без отдельного синонима относится к своему языку.
+        AliasResolution::Verbatim(_) if !split_variants => aliases
+            .variant(raw)
+            .and_then(|(language, _)| aliases.canonicalize(language))
+            .or_else(|| aliases.canonicalize(raw)),
+        resolution => resolution.canonical().map(Language::intern),
+    }
+    .map(|language| {
+        let variant = split_variants
+            .then(|| benchmark_variant(raw, aliases))
+            .flatten();
+        variant.unwrap_or(BenchmarkLanguage {
+            language,
+            variant_of: None,
+        })
+    });
+    language_cache.insert(raw.to_owned(), language);
+    language
+}
+
+fn benchmark_variant(raw: &str, aliases: &AliasRegistry) -> Option<BenchmarkLanguage> {
+    let (language, variant) = aliases.variant(raw)?;
+    let language = aliases.canonicalize(language)?;
+    Some(BenchmarkLanguage {
+        language: Language::intern(&format!("{language} ({variant})")),
+        variant_of: Some(language),
+    })
 }
 
 /// Загружает исходный CSV Benchmarks Game.
@@ -119,8 +301,12 @@ fn canonical_language_id(
 /// # Errors
 ///
 /// Возвращает ошибку, если данные не удалось получить по HTTP.
-pub async fn download_benchmark_data(client: &Client) -> Result<Vec<u8>> {
-    fetch_bytes_with_retry(client, BENCH_URL)
+pub async fn download_benchmark_data(transport: &dyn Transport) -> Result<Vec<u8>> {
+    download_benchmark_data_with(HttpContext::with_defaults(transport)).await
+}
+
+pub async fn download_benchmark_data_with(http: HttpContext<'_>) -> Result<Vec<u8>> {
+    fetch_bytes_with_retry(http, http.url(PerformanceSource::BenchmarksGame, BENCH_URL))
         .await
         .context("failed to download benchmark dataset")
 }
@@ -130,14 +316,72 @@ pub async fn download_benchmark_data(client: &Client) -> Result<Vec<u8>> {
 /// # Errors
 ///
 /// Возвращает ошибку при некорректной структуре CSV или сбое фоновой задачи.
-pub async fn load_benchmark_scores(bytes: Vec<u8>) -> Result<FxHashMap<String, f64>> {
-    let scores = task::spawn_blocking(move || compute_benchmark_scores_sync(&bytes))
-        .await
-        .context("failed to read benchmark statistics")??;
-    Ok(scores)
+pub async fn load_benchmark_scores(bytes: Vec<u8>) -> Result<FxHashMap<Language, f64>> {
+    load_benchmark_scores_with_options(bytes, BenchmarkScoring::default()).await
+}
+
+/// Вычисляет нормализованные показатели языков из CSV Benchmarks Game по
+/// заданной стратегии.
+///
+/// # Errors
+///
+/// Возвращает ошибку при некорректной структуре CSV, отсутствии колонки
+/// выбранного времени или сбое фоновой задачи.
+pub async fn load_benchmark_scores_with_options(
+    bytes: Vec<u8>,
+    scoring: BenchmarkScoring,
+) -> Result<FxHashMap<Language, f64>> {
+    Ok(load_benchmark_results_with_options(bytes, scoring)
+        .await?
+        .scores())
 }
 
-fn compute_benchmark_scores_sync(data: &[u8]) -> Result<FxHashMap<String, f64>> {
+/// Вычисляет показатели языков из CSV Benchmarks Game вместе с результатами
+/// по задачам.
+///
+/// # Errors
+///
+/// Возвращает ошибку при некорректной структуре CSV или сбое фоновой задачи.
+pub async fn load_benchmark_results(bytes: Vec<u8>) -> Result<BenchmarkResults> {
+    load_benchmark_results_with_options(bytes, BenchmarkScoring::default()).await
+}
+
+/// Вычисляет показатели языков вместе с результатами по задачам по заданной
+/// стратегии.
+///
+/// # Errors
+///
+/// Возвращает ошибку при некорректной структуре CSV, отсутствии колонки
+/// выбранного времени или сбое фоновой задачи.
+pub async fn load_benchmark_results_with_options(
+    bytes: Vec<u8>,
+    scoring: BenchmarkScoring,
+) -> Result<BenchmarkResults> {
+    let (results, _) =
+        load_benchmark_results_with(bytes, Arc::clone(builtin_aliases()), Arc::new(scoring))
+            .await?;
+    Ok(results)
+}
+
+/// Вычисляет показатели в фоновой задаче и возвращает вместе с ними исходный
+/// CSV, чтобы вызывающему не приходилось копировать его заранее.
+pub async fn load_benchmark_results_with(
+    bytes: Vec<u8>,
+    aliases: Arc<AliasRegistry>,
+    scoring: Arc<BenchmarkScoring>,
+) -> Result<(BenchmarkResults, Vec<u8>)> {
+    task::spawn_blocking(move || {
+        compute_benchmark_results_sync(&bytes, &aliases, &scoring).map(|results| (results, bytes))
+    })
+    .await
+    .context("failed to read benchmark statistics")?
+}
+
+fn compute_benchmark_results_sync(
+    data: &[u8],
+    aliases: &AliasRegistry,
+    scoring: &BenchmarkScoring,
+) -> Result<BenchmarkResults> {
     let cursor = Cursor::new(data);
     let mut reader = csv::ReaderBuilder::new()
         .has_headers(true)
@@ -146,84 +390,263 @@ fn compute_benchmark_scores_sync(data: &[u8]) -> Result<FxHashMap<String, f64>>
 
     let headers = reader
         .headers()
-        .context("missing CSV headers in benchmark data")?
+        .map_err(|error| SourceError::decode(BENCHMARK_CSV, error))?
         .clone();
-    let columns = BenchmarkColumns::parse(&headers)?;
+    let columns = BenchmarkColumns::parse(&headers, scoring)?;
 
-    let mut language_id_cache: FxHashMap<String, Option<LanguageId>> = FxHashMap::default();
-    let mut languages = StringInterner::default();
+    let mut language_cache: FxHashMap<String, Option<BenchmarkLanguage>> = FxHashMap::default();
+    let mut variant_bases: FxHashMap<Language, Language> = FxHashMap::default();
     let mut tasks = StringInterner::default();
-    let mut best_by_language_task: FxHashMap<(LanguageId, TaskId), f64> = FxHashMap::default();
-    let mut best_by_task: Vec<f64> = Vec::new();
+    let mut accumulators: [MetricAccumulator; Column::ALL.len()] = Default::default();
+    let mut failed_runs: FxHashMap<(Language, TaskId), usize> = FxHashMap::default();
 
     for record in reader.records() {
-        let record = record.context("failed to read benchmark record")?;
+        let record = record.map_err(|error| SourceError::decode(BENCHMARK_CSV, error))?;
         let Some(row) = BenchmarkRow::parse(&record, columns) else {
             continue;
         };
 
-        let Some(language_id) =
-            canonical_language_id(row.language, &mut language_id_cache, &mut languages)
+        let Some(BenchmarkLanguage {
+            language,
+            variant_of,
+        }) = canonical_language(
+            row.language,
+            aliases,
+            scoring.split_variants,
+            &mut language_cache,
+        )
         else {
             continue;
         };
-        let task_id = tasks.intern(row.task);
-        best_by_task.resize(tasks.len(), f64::INFINITY);
-
-        let entry = best_by_language_task
-            .entry((language_id, task_id))
-            .or_insert(f64::INFINITY);
-        if row.elapsed < *entry {
-            *entry = row.elapsed;
+        if let Some(base) = variant_of {
+            variant_bases.insert(language, base);
         }
-
-        if row.elapsed < best_by_task[task_id] {
-            best_by_task[task_id] = row.elapsed;
+        let task_id = tasks.intern(row.task);
+        let Some(values) = row.measurement else {
+            *failed_runs.entry((language, task_id)).or_default() += 1;
+            continue;
+        };
+        for (accumulator, value) in accumulators.iter_mut().zip(values) {
+            if let Some(value) = value {
+                accumulator.record(language, task_id, value);
+            }
         }
     }
 
-    let mut stats_by_language = vec![GeometricMeanStats::default(); languages.len()];
-    for ((language_id, task_id), elapsed) in best_by_language_task {
-        let best = best_by_task[task_id];
-        let ratio = best / elapsed;
-        if ratio.is_finite() && ratio > 0.0 {
-            let stats = &mut stats_by_language[language_id];
-            stats.log_sum += ratio.ln();
-            stats.sample_count += 1;
+    let task_names = tasks.into_strings();
+    let main = accumulators[Column::from(scoring.measure).index()].select(scoring.selection);
+    let included = included_tasks(&task_names, &main, &variant_bases, scoring);
+    let included_count = included.iter().filter(|&&included| included).count();
+    // Неудачи считаются по тем же задачам, что и успешные запуски.
+    let mut included_failures: FxHashMap<Language, usize> = FxHashMap::default();
+    for (&(language, task_id), &count) in &failed_runs {
+        if included[task_id] {
+            *included_failures.entry(language).or_default() += count;
         }
     }
 
-    let mut scores: FxHashMap<String, f64> = FxHashMap::default();
-    for (language, stats) in languages.into_strings().into_iter().zip(stats_by_language) {
-        if stats.sample_count == 0 {
+    let [cpu_time, memory, source_size] = [Column::CpuTime, Column::Memory, Column::SourceSize]
+        .map(|column| {
+            let values = accumulators[column.index()].select(scoring.selection);
+            ratios_by_language(&values, &included)
+                .into_iter()
+                .filter_map(|(language, ratios)| {
+                    let score = weighted_geometric_mean(&ratios, &task_names, scoring)?;
+                    Some((language, score))
+                })
+                .collect::<FxHashMap<Language, f64>>()
+        });
+
+    let mut languages: FxHashMap<Language, LanguageBenchmark> = FxHashMap::default();
+    for (language, ratios) in ratios_by_language(&main, &included) {
+        let coverage = task_share(ratios.len(), included_count);
+        if coverage < scoring.min_task_coverage {
             continue;
         }
-        let Ok(sample_count) = u32::try_from(stats.sample_count) else {
-            eprintln!(
-                "Warning: benchmark sample count for {language} too large ({}); skipping score",
-                stats.sample_count
-            );
+        let Some(mut benchmark) = language_benchmark(ratios, &task_names, scoring) else {
             continue;
         };
-        let score = (stats.log_sum / f64::from(sample_count)).exp();
-        if score.is_finite() {
-            scores.insert(language, score);
-        }
+        benchmark.coverage = coverage;
+        benchmark.cpu_time_score = cpu_time.get(&language).copied();
+        benchmark.memory_score = memory.get(&language).copied();
+        benchmark.source_size_score = source_size.get(&language).copied();
+        benchmark.failed_runs = included_failures.get(&language).copied().unwrap_or(0);
+        languages.insert(language, benchmark);
+    }
+
+    if scoring.split_variants {
+        return Ok(group_variants(languages, &variant_bases));
+    }
+
+    if let Some(combined) = languages.get(&Language::intern("C/C++")).cloned() {
+        languages.insert(Language::intern("C"), combined.clone());
+        languages.insert(Language::intern("C++"), combined);
+    }
+
+    Ok(BenchmarkResults::new(languages))
+}
+
+/// Переносит реализации в отдельную таблицу, а языку назначает результаты
+/// лучшей реализации вместе со списком всех реализаций.
+fn group_variants(
+    scored: FxHashMap<Language, LanguageBenchmark>,
+    variant_bases: &FxHashMap<Language, Language>,
+) -> BenchmarkResults {
+    let mut languages: FxHashMap<Language, LanguageBenchmark> = FxHashMap::default();
+    let mut variants: FxHashMap<Language, LanguageBenchmark> = FxHashMap::default();
+    let mut variants_by_language: FxHashMap<Language, Vec<VariantScore>> = FxHashMap::default();
+    for (language, benchmark) in scored {
+        let Some(&base) = variant_bases.get(&language) else {
+            languages.insert(language, benchmark);
+            continue;
+        };
+        variants_by_language
+            .entry(base)
+            .or_default()
+            .push(VariantScore {
+                name: language,
+                score: benchmark.score,
+            });
+        variants.insert(language, benchmark);
+    }
+
+    for (base, mut scores) in variants_by_language {
+        scores.sort_by(|left, right| {
+            right
+                .score
+                .total_cmp(&left.score)
+                .then_with(|| left.name.cmp(&right.name))
+        });
+        let mut best = variants[&scores[0].name].clone();
+        best.variants = scores;
+        languages.insert(base, best);
+    }
+
+    BenchmarkResults::new(languages).with_variants(variants)
+}
+
+/// Отмечает задачи, которые учитываются в показателях: с положительным весом
+/// и достаточным числом участников по основному измерению. Задачи, где ни у
+/// кого нет успешного запуска, не учитываются. Реализации одного языка
+/// считаются одним участником.
+fn included_tasks(
+    task_names: &[String],
+    main: &FxHashMap<(Language, TaskId), LanguageTaskValue>,
+    variant_bases: &FxHashMap<Language, Language>,
+    scoring: &BenchmarkScoring,
+) -> Vec<bool> {
+    let languages: FxHashSet<(Language, TaskId)> = main
+        .keys()
+        .map(|&(language, task_id)| {
+            let base = variant_bases.get(&language).copied().unwrap_or(language);
+            (base, task_id)
+        })
+        .collect();
+    let mut participants = vec![0_usize; task_names.len()];
+    for (_, task_id) in languages {
+        participants[task_id] += 1;
     }
+    task_names
+        .iter()
+        .zip(participants)
+        .map(|(task, participants)| {
+            participants > 0
+                && participants >= scoring.min_task_participants
+                && scoring.task_weight(task) > 0.0
+        })
+        .collect()
+}
 
-    if let Some(value) = scores.get("C/C++").copied() {
-        scores.insert("C".to_string(), value);
-        scores.insert("C++".to_string(), value);
+fn task_share(covered: usize, total: usize) -> f64 {
+    let (Ok(covered), Ok(total)) = (u32::try_from(covered), u32::try_from(total)) else {
+        return 0.0;
+    };
+    if total == 0 {
+        return 0.0;
     }
+    f64::from(covered) / f64::from(total)
+}
 
-    Ok(scores)
+fn language_benchmark(
+    ratios: Vec<TaskRatio>,
+    task_names: &[String],
+    scoring: &BenchmarkScoring,
+) -> Option<LanguageBenchmark> {
+    let score = weighted_geometric_mean(&ratios, task_names, scoring)?;
+    let mut tasks: Vec<TaskBenchmark> = ratios
+        .into_iter()
+        .map(|ratio| {
+            let task = task_names[ratio.task_id].clone();
+            TaskBenchmark {
+                weight: scoring.task_weight(&task),
+                task,
+                time: ratio.value,
+                fastest_time: ratio.fastest,
+                ratio: ratio.ratio,
+                runs: ratio.runs,
+            }
+        })
+        .collect();
+    tasks.sort_by(|left, right| left.task.cmp(&right.task));
+    Some(LanguageBenchmark {
+        score,
+        sample_count: tasks.len(),
+        coverage: 1.0,
+        tasks,
+        cpu_time_score: None,
+        memory_score: None,
+        source_size_score: None,
+        failed_runs: 0,
+        variants: Vec::new(),
+    })
+}
+
+fn weighted_geometric_mean(
+    ratios: &[TaskRatio],
+    task_names: &[String],
+    scoring: &BenchmarkScoring,
+) -> Option<f64> {
+    // Задачи сортируются до суммирования, чтобы показатель не зависел от
+    // порядка обхода хэш-таблицы.
+    let mut weighted: Vec<(&str, f64, f64)> = ratios
+        .iter()
+        .map(|ratio| {
+            let task = task_names[ratio.task_id].as_str();
+            (task, ratio.ratio, scoring.task_weight(task))
+        })
+        .collect();
+    weighted.sort_by(|left, right| left.0.cmp(right.0));
+
+    let total_weight: f64 = weighted.iter().map(|&(_, _, weight)| weight).sum();
+    if total_weight <= 0.0 {
+        return None;
+    }
+    let log_sum: f64 = weighted
+        .iter()
+        .map(|&(_, ratio, weight)| weight * ratio.ln())
+        .sum();
+    let mean = (log_sum / total_weight).exp();
+    if !mean.is_finite() {
+        return None;
+    }
+    Some(mean)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::compute_benchmark_scores_sync;
+    use super::compute_benchmark_results_sync;
+    use crate::{
+        AliasRegistry, BenchmarkMetric, BenchmarkResults, BenchmarkScoring, Language, RunSelection,
+        TimeMeasure,
+    };
+
+    fn compute(csv: &[u8], scoring: &BenchmarkScoring) -> BenchmarkResults {
+        compute_benchmark_results_sync(csv, &AliasRegistry::builtin(), scoring)
+            .expect("fixture should parse")
+    }
 
     #[test]
+    #[allow(clippy::float_cmp)]
     fn computes_geometric_mean_from_best_runs() {
         let csv = b"lang,name,status,elapsed-time(s)\n\
             gpp,task-a,0,1.0\n\
@@ -232,12 +655,270 @@ mod tests {
             python,task-a,0,2.0\n\
             python,task-b,0,2.0\n";
 
-        let scores = compute_benchmark_scores_sync(csv).expect("fixture should parse");
+        let results = compute(csv, &BenchmarkScoring::default());
+        let scores = results.scores();
         let expected = 0.5_f64.sqrt();
+        let score = |name| scores[&Language::intern(name)];
+
+        assert!((score("C/C++") - expected).abs() < f64::EPSILON);
+        assert!((score("Python") - expected).abs() < f64::EPSILON);
+        assert_eq!(score("C"), score("C/C++"));
+        assert_eq!(score("C++"), score("C/C++"));
+    }
+
+    #[test]
+    fn counts_failures_only_on_included_tasks() {
+        let csv = b"lang,name,status,elapsed-time(s)\n\
+            gpp,task-a,0,1.0\n\
+            gpp,task-a,-1,0\n\
+            python,task-a,0,2.0\n\
+            python,task-b,0,3.0\n\
+            python,task-b,-1,0\n\
+            python,task-b,-1,0\n";
+        let scoring = BenchmarkScoring {
+            min_task_participants: 2,
+            ..BenchmarkScoring::default()
+        };
+
+        let results = compute(csv, &scoring);
+        let python = results
+            .get(Language::intern("Python"))
+            .expect("Python should be scored");
+        assert_eq!(python.sample_count, 1);
+        assert_eq!(python.failed_runs, 0);
+        assert_eq!(python.total_runs(), 1);
+        let cpp = results
+            .get(Language::intern("C/C++"))
+            .expect("C/C++ should be scored");
+        assert!((cpp.failure_rate() - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn exposes_per_task_breakdown() {
+        let csv = b"lang,name,status,elapsed-time(s)\n\
+            gpp,task-b,0,4.0\n\
+            gpp,task-a,0,1.5\n\
+            gpp,task-a,0,1.0\n\
+            gpp,task-a,-1,0.5\n\
+            python,task-a,0,2.0\n\
+            python,task-b,0,2.0\n";
+
+        let results = compute(csv, &BenchmarkScoring::default());
+        let cpp = results
+            .get(Language::intern("C/C++"))
+            .expect("C/C++ should be scored");
+
+        assert_eq!(cpp.sample_count, 2);
+        let tasks: Vec<_> = cpp.tasks.iter().map(|task| task.task.as_str()).collect();
+        assert_eq!(tasks, ["task-a", "task-b"]);
+        assert_eq!(cpp.tasks[0].runs, 2);
+        assert!((cpp.tasks[0].time - 1.0).abs() < f64::EPSILON);
+        assert!((cpp.tasks[0].ratio - 1.0).abs() < f64::EPSILON);
+        assert!((cpp.tasks[1].fastest_time - 2.0).abs() < f64::EPSILON);
+        assert!((cpp.tasks[1].ratio - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn scores_optional_metrics_and_counts_failures() {
+        let csv = b"lang,name,status,elapsed-time(s),cpu-time(s),mem(KB),size(B)\n\
+            gpp,task-a,0,1.0,2.0,100,400\n\
+            gpp,task-a,0,1.5,1.0,200,400\n\
+            gpp,task-b,-1,0,0,0,0\n\
+            python,task-a,0,2.0,4.0,50,100\n\
+            python,task-b,-1,0,0,0,0\n\
+            python,task-b,-2,0,0,0,0\n\
+            python,task-b,0,3.0,3.0,,100\n";
+
+        let results = compute(csv, &BenchmarkScoring::default());
+        let cpp = results
+            .get(Language::intern("C/C++"))
+            .expect("C/C++ should be scored");
+        let python = results
+            .get(Language::intern("Python"))
+            .expect("Python should be scored");
+        let close = |left: Option<f64>, right: f64| (left.unwrap() - right).abs() < 1e-12;
+
+        assert!(close(cpp.cpu_time_score, 1.0));
+        assert!(close(cpp.memory_score, 0.5));
+        assert!(close(cpp.source_size_score, 0.25));
+        assert_eq!(cpp.failed_runs, 1);
+        assert!(close(cpp.metric(BenchmarkMetric::Reliability), 2.0 / 3.0));
+
+        assert!(close(python.cpu_time_score, 0.5));
+        assert!(close(python.memory_score, 1.0));
+        assert_eq!(python.failed_runs, 2);
+        assert!((python.failure_rate() - 0.5).abs() < f64::EPSILON);
+
+        let memory = results.metric_scores(BenchmarkMetric::Memory);
+        assert!((memory[&Language::intern("C")] - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn leaves_optional_metrics_empty_without_columns() {
+        let csv = b"lang,name,status,elapsed-time(s)\n\
+            python,task-a,0,2.0\n";
+
+        let results = compute(csv, &BenchmarkScoring::default());
+        let python = results
+            .get(Language::intern("Python"))
+            .expect("Python should be scored");
+
+        assert_eq!(python.cpu_time_score, None);
+        assert_eq!(python.metric(BenchmarkMetric::SourceSize), None);
+        assert!(results.metric_scores(BenchmarkMetric::Memory).is_empty());
+        assert_eq!(python.metric(BenchmarkMetric::Reliability), Some(1.0));
+    }
+
+    #[test]
+    fn applies_median_cpu_time_strategy() {
+        let csv = b"lang,name,status,elapsed-time(s),cpu-time(s)\n\
+            gpp,task-a,0,1.0,1.0\n\
+            gpp,task-a,0,1.0,3.0\n\
+            gpp,task-a,0,1.0,8.0\n\
+            python,task-a,0,2.0,6.0\n";
+        let scoring = BenchmarkScoring {
+            selection: RunSelection::Median,
+            measure: TimeMeasure::CpuTime,
+            ..BenchmarkScoring::default()
+        };
+
+        let results = compute(csv, &scoring);
+        let cpp = results
+            .get(Language::intern("C/C++"))
+            .expect("C/C++ should be scored");
+
+        assert!((cpp.tasks[0].time - 3.0).abs() < f64::EPSILON);
+        assert!((cpp.score - 1.0).abs() < f64::EPSILON);
+        let python = results.scores()[&Language::intern("Python")];
+        assert!((python - 0.5).abs() < f64::EPSILON);
+
+        let missing = compute_benchmark_results_sync(
+            b"lang,name,status,elapsed-time(s)\n",
+            &AliasRegistry::builtin(),
+            &scoring,
+        )
+        .expect_err("CPU time scoring needs the cpu-time column");
+        assert!(missing.to_string().contains("cpu-time(s)"));
+    }
+
+    #[test]
+    fn filters_tasks_and_languages_and_weights_tasks() {
+        let csv = b"lang,name,status,elapsed-time(s)\n\
+            gpp,task-a,0,1.0\n\
+            gpp,task-b,0,4.0\n\
+            gpp,task-c,0,1.0\n\
+            python,task-a,0,2.0\n\
+            python,task-b,0,2.0\n\
+            rust,task-a,0,1.0\n";
+        let mut scoring = BenchmarkScoring {
+            min_task_participants: 2,
+            min_task_coverage: 0.75,
+            ..BenchmarkScoring::default()
+        };
+        scoring.task_weights.insert("task-b".to_owned(), 3.0);
+
+        let results = compute(csv, &scoring);
+        let cpp = results
+            .get(Language::intern("C/C++"))
+            .expect("C/C++ should be scored");
+
+        let tasks: Vec<_> = cpp.tasks.iter().map(|task| task.task.as_str()).collect();
+        assert_eq!(tasks, ["task-a", "task-b"]);
+        assert!((cpp.coverage - 1.0).abs() < f64::EPSILON);
+        assert!((cpp.score - 0.5_f64.powf(0.75)).abs() < 1e-12);
+        assert!((cpp.tasks[1].weight - 3.0).abs() < f64::EPSILON);
+        assert!(results.get(Language::intern("Rust")).is_none());
+
+        scoring.task_weights.insert("task-b".to_owned(), 0.0);
+        let results = compute(csv, &scoring);
+        let rust = results
+            .get(Language::intern("Rust"))
+            .expect("Rust covers every remaining task");
+        assert!((rust.score - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn keeps_compiler_and_runtime_variants_separate() {
+        let csv = b"lang,name,status,elapsed-time(s)\n\
+            gcc,task-a,0,1.0\n\
+            clang,task-a,0,2.0\n\
+            gpp,task-a,0,4.0\n\
+            python3,task-a,0,10.0\n\
+            micropython,task-a,0,40.0\n\
+            rust,task-a,0,1.0\n";
+        let scoring = BenchmarkScoring {
+            split_variants: true,
+            ..BenchmarkScoring::default()
+        };
+
+        let results = compute(csv, &scoring);
+        let c = results
+            .get(Language::intern("C"))
+            .expect("C should be scored");
+        assert_eq!(c.best_variant(), Some(Language::intern("C (GCC)")));
+        assert!((c.score - 1.0).abs() < f64::EPSILON);
+        assert!((c.variant_spread().unwrap() - 0.5).abs() < f64::EPSILON);
+
+        let cpp = results
+            .get(Language::intern("C++"))
+            .expect("C++ should be scored");
+        assert!((cpp.score - 0.25).abs() < f64::EPSILON);
+        assert!(results.get(Language::intern("C/C++")).is_none());
+
+        let micropython = results
+            .variant(Language::intern("Python (MicroPython)"))
+            .expect("MicroPython should be scored separately");
+        assert!((micropython.score - 0.025).abs() < f64::EPSILON);
+        let python = results
+            .get(Language::intern("Python"))
+            .expect("Python should be scored");
+        assert_eq!(python.variants.len(), 2);
+        assert!((python.score - 0.1).abs() < f64::EPSILON);
+
+        let rust = results
+            .get(Language::intern("Rust"))
+            .expect("Rust should be scored");
+        assert!(rust.variants.is_empty());
+        assert_eq!(rust.variant_spread(), None);
+    }
+
+    #[test]
+    fn splits_registry_variants_and_counts_languages_once() {
+        let csv = b"lang,name,status,elapsed-time(s)\n\
+            java,task-a,0,2.0\n\
+            graalvm,task-a,0,1.0\n\
+            java,task-b,0,1.0\n\
+            graalvm,task-b,0,1.0\n\
+            rust,task-b,0,1.0\n";
+        let mut aliases = AliasRegistry::builtin();
+        aliases.insert_variant("graalvm", "Java", "GraalVM");
+        let scoring = BenchmarkScoring {
+            split_variants: true,
+            min_task_participants: 2,
+            ..BenchmarkScoring::default()
+        };
 
-        assert!((scores["C/C++"] - expected).abs() < f64::EPSILON);
-        assert!((scores["Python"] - expected).abs() < f64::EPSILON);
-        assert_eq!(scores["C"], scores["C/C++"]);
-        assert_eq!(scores["C++"], scores["C/C++"]);
+        let results = compute_benchmark_results_sync(csv, &aliases, &scoring)
+            .expect("benchmark CSV should parse");
+        let java = results
+            .get(Language::intern("Java"))
+            .expect("Java should be scored");
+        assert_eq!(
+            java.best_variant(),
+            Some(Language::intern("Java (GraalVM)"))
+        );
+        // Две реализации Java — один участник, поэтому task-a не учитывается.
+        let tasks: Vec<_> = java.tasks.iter().map(|task| task.task.as_str()).collect();
+        assert_eq!(tasks, ["task-b"]);
+
+        let merged = compute_benchmark_results_sync(csv, &aliases, &BenchmarkScoring::default())
+            .expect("benchmark CSV should parse");
+        assert!(merged.get(Language::intern("graalvm")).is_none());
+        assert_eq!(
+            merged
+                .get(Language::intern("Java"))
+                .map(|java| java.tasks.len()),
+            Some(2)
+        );
     }
 }
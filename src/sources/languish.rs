@@ -1,15 +1,19 @@
-use crate::RankingEntry;
-use anyhow::{Context, Result, anyhow};
+use crate::aliases::builtin as builtin_aliases;
+use crate::{
+    AliasRegistry, CoreWeights, Language, LanguishHistory, LanguishQuarter, LanguishScoring,
+    LanguishSignals, RankingDataset, RankingEntry, RankingSource, SourceError, Transport,
+};
+use anyhow::{Context, Result};
 use memchr::memchr;
-use reqwest::Client;
 use rustc_hash::FxHashMap;
 use scraper::{Html, Selector};
 use serde::Deserialize;
 use serde_json::Value;
 use std::sync::OnceLock;
 
-use super::{RawEntry, aggregate_entries, fetch_text_with_retry};
+use super::{HttpContext, fetch_text_with_retry, resolve_url};
 
+const LANGUISH_DATA: &str = "Languish data";
 const LANGUISH_INDEX_URL: &str = "https://tjpalmer.github.io/languish/";
 
 #[derive(Debug, Deserialize)]
@@ -24,106 +28,167 @@ struct LanguishData {
     sums: Table,
 }
 
-#[derive(Clone, Copy)]
-struct CoreWeights {
-    issues: f64,
-    pulls: f64,
-    so_questions: f64,
-    stars: f64,
+/// Загружает и разбирает актуальный рейтинг Languish.
+///
+/// # Errors
+///
+/// Возвращает ошибку при сбое HTTP-запроса или несовместимом формате данных.
+pub async fn fetch_languish(transport: &dyn Transport) -> Result<Vec<RankingEntry>> {
+    fetch_languish_with_options(transport, LanguishScoring::default())
+        .await
+        .map(|dataset| dataset.into_parts().1)
 }
 
-// В отличие от Languish учитываем все четыре сигнала с одинаковым весом.
-const CORE_WEIGHTS: CoreWeights = CoreWeights {
-    issues: 1.0,
-    pulls: 1.0,
-    so_questions: 1.0,
-    stars: 1.0,
-};
-
-impl CoreWeights {
-    const fn total(self) -> f64 {
-        self.issues + self.pulls + self.so_questions + self.stars
-    }
+/// Загружает рейтинг Languish, вычисленный с заданными весами сигналов и
+/// окном тренда.
+///
+/// С [`LanguishScoring::signal_ballots`] набор содержит бюллетени по сигналам
+/// ([`RankingDataset::sub_ballots`]).
+///
+/// # Errors
+///
+/// Возвращает ошибку при сбое HTTP-запроса или несовместимом формате данных.
+pub async fn fetch_languish_with_options(
+    transport: &dyn Transport,
+    scoring: LanguishScoring,
+) -> Result<RankingDataset> {
+    fetch_languish_with(
+        HttpContext::with_defaults(transport),
+        builtin_aliases(),
+        scoring,
+    )
+    .await
 }
 
-/// Загружает и разбирает актуальный рейтинг Languish.
+/// Загружает полную поквартальную историю Languish по языкам и сигналам.
 ///
 /// # Errors
 ///
 /// Возвращает ошибку при сбое HTTP-запроса или несовместимом формате данных.
-pub async fn fetch_languish(client: &Client) -> Result<Vec<RankingEntry>> {
-    let index_html = fetch_text_with_retry(client, LANGUISH_INDEX_URL)
+pub async fn fetch_languish_history(
+    transport: &dyn Transport,
+    weights: CoreWeights,
+) -> Result<LanguishHistory> {
+    fetch_languish_history_with(
+        HttpContext::with_defaults(transport),
+        builtin_aliases(),
+        weights,
+    )
+    .await
+}
+
+pub async fn fetch_languish_with(
+    http: HttpContext<'_>,
+    aliases: &AliasRegistry,
+    scoring: LanguishScoring,
+) -> Result<RankingDataset> {
+    let js_body = fetch_languish_bundle(http).await?;
+    parse_languish_bundle(&js_body, aliases, scoring)
+}
+
+pub async fn fetch_languish_history_with(
+    http: HttpContext<'_>,
+    aliases: &AliasRegistry,
+    weights: CoreWeights,
+) -> Result<LanguishHistory> {
+    let js_body = fetch_languish_bundle(http).await?;
+    parse_languish_history(&js_body, aliases, weights)
+}
+
+async fn fetch_languish_bundle(http: HttpContext<'_>) -> Result<String> {
+    let index_url = http.url(RankingSource::Languish, LANGUISH_INDEX_URL);
+    let index_html = fetch_text_with_retry(http, index_url)
         .await
         .context("failed to download Languish index page")?;
-    let main_js_url = extract_main_js_url(&index_html)
-        .ok_or_else(|| anyhow!("failed to locate Languish main chunk script"))?;
+    let main_js_url =
+        extract_main_js_url(&index_html, index_url).ok_or(SourceError::MarkupNotFound {
+            document: "Languish index page",
+            element: "main chunk script",
+        })?;
 
-    let js_body = fetch_text_with_retry(client, &main_js_url)
+    fetch_text_with_retry(http, &main_js_url)
         .await
-        .with_context(|| format!("failed to download Languish JS bundle: {main_js_url}"))?;
-    parse_languish_bundle(&js_body)
+        .with_context(|| format!("failed to download Languish JS bundle: {main_js_url}"))
 }
 
-fn parse_languish_bundle(js_body: &str) -> Result<Vec<RankingEntry>> {
-    let encoded = extract_json_parse_payload(js_body)
-        .ok_or_else(|| anyhow!("failed to extract Languish embedded JSON payload"))?;
+fn parse_languish_bundle(
+    js_body: &str,
+    aliases: &AliasRegistry,
+    scoring: LanguishScoring,
+) -> Result<RankingDataset> {
+    // Текущий рейтинг — последний квартал истории, поэтому backfill и живой
+    // запуск на одних данных дают один и тот же набор.
+    let history = parse_languish_history(js_body, aliases, scoring.weights)?;
+    let latest = history
+        .quarters()
+        .last()
+        .ok_or_else(|| SourceError::missing_field(LANGUISH_DATA, "dated rows of sums"))?;
+    history
+        .quarter_dataset(latest, scoring)
+        .ok_or_else(|| SourceError::missing_field(LANGUISH_DATA, "latest quarter").into())
+}
 
-    let json_text = decode_js_string_literal(encoded);
-    let tables = parse_languish_tables(&json_text)?;
-    let quarters = recent_quarters(&tables.sums)?;
-    let metrics_by_language = build_recent_metrics(
-        &tables.items,
-        quarters.latest.date,
-        quarters.previous.map(|quarter| quarter.date),
-    )?;
-    let weights = CORE_WEIGHTS;
-    let weight_total = weights.total();
-
-    let mut ranked_languages = Vec::with_capacity(metrics_by_language.len());
-    for (name, metrics) in metrics_by_language {
-        let latest_mean = metrics.latest.as_ref().map_or(0.0, |metrics| {
-            mean_percent(metrics, &quarters.latest.metrics, weights, weight_total)
-        });
-        let previous_mean = quarters.previous.map(|quarter| {
-            metrics.previous.as_ref().map_or(0.0, |metrics| {
-                mean_percent(metrics, &quarter.metrics, weights, weight_total)
-            })
-        });
-        let trend = previous_mean.map(|previous| latest_mean - previous);
-        ranked_languages.push((name.to_owned(), latest_mean, trend));
-    }
+fn parse_languish_history(
+    js_body: &str,
+    aliases: &AliasRegistry,
+    weights: CoreWeights,
+) -> Result<LanguishHistory> {
+    let tables = decode_languish_tables(js_body)?;
+    let history = RawHistory::from_tables(&tables)?;
+    let quarters: Vec<String> = history
+        .quarters
+        .iter()
+        .map(|quarter| quarter.date.to_owned())
+        .collect();
 
-    ranked_languages.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
-    let mut entries: Vec<RawEntry> = Vec::with_capacity(ranked_languages.len());
-    for (index, (name, mean, trend)) in ranked_languages.into_iter().enumerate() {
-        let rank = u32::try_from(index)
-            .ok()
-            .and_then(|value| value.checked_add(1));
-        if rank.is_none() {
-            eprintln!("Warning: Languish rank overflow at index {index}; omitting rank for {name}");
-        }
-        if let Some(entry) = RawEntry::parse(name.as_str(), rank, mean, trend) {
-            entries.push(entry);
+    // Несколько названий Languish могут относиться к одному языку; их доли
+    // складываются, как и в рейтинге.
+    let mut languages: FxHashMap<Language, Vec<LanguishQuarter>> = FxHashMap::default();
+    for (name, series) in &history.items {
+        let Some(lang) = aliases.canonicalize(name) else {
+            continue;
+        };
+        let language_series = languages.entry(lang).or_insert_with(|| {
+            quarters
+                .iter()
+                .map(|quarter| {
+                    LanguishQuarter::new(quarter.clone(), LanguishSignals::default(), 0.0)
+                })
+                .collect()
+        });
+        for ((slot, metrics), snapshot) in language_series
+            .iter_mut()
+            .zip(series)
+            .zip(&history.quarters)
+        {
+            slot.signals += metrics.shares(&snapshot.metrics);
         }
     }
+    for slot in languages.values_mut().flatten() {
+        slot.score = weights.score(&slot.signals);
+    }
+
+    Ok(LanguishHistory::new(quarters, languages))
+}
 
-    Ok(aggregate_entries(entries))
+fn decode_languish_tables(js_body: &str) -> Result<LanguishData> {
+    let encoded = extract_json_parse_payload(js_body).ok_or(SourceError::MarkupNotFound {
+        document: "Languish JS bundle",
+        element: "embedded JSON.parse payload",
+    })?;
+
+    let json_text = decode_js_string_literal(encoded);
+    parse_languish_tables(&json_text)
 }
 
-fn extract_main_js_url(index_html: &str) -> Option<String> {
+fn extract_main_js_url(index_html: &str, index_url: &str) -> Option<String> {
     let doc = Html::parse_document(index_html);
     for node in doc.select(script_selector()) {
         if let Some(src) = node.value().attr("src")
             && src.contains("/static/js/main")
             && src.ends_with(".chunk.js")
         {
-            // Относительный путь в HTML задан от корня GitHub Pages.
-            let url = if src.starts_with("http://") || src.starts_with("https://") {
-                src.to_string()
-            } else {
-                format!("https://tjpalmer.github.io{src}")
-            };
-            return Some(url);
+            return Some(resolve_url(index_url, src));
         }
     }
     None
@@ -254,57 +319,69 @@ struct QuarterSnapshot<'a> {
     metrics: Metrics,
 }
 
-struct RecentQuarters<'a> {
-    latest: QuarterSnapshot<'a>,
-    previous: Option<QuarterSnapshot<'a>>,
+/// Значения сигналов по кварталам: суммы по всем языкам и ряды отдельных
+/// названий Languish, выровненные по кварталам.
+struct RawHistory<'a> {
+    quarters: Vec<QuarterSnapshot<'a>>,
+    items: FxHashMap<&'a str, Vec<Metrics>>,
+}
+
+impl<'a> RawHistory<'a> {
+    fn from_tables(tables: &'a LanguishData) -> Result<Self> {
+        let quarters = quarter_snapshots(&tables.sums)?;
+        let items = item_series(&tables.items, &quarters)?;
+        Ok(Self { quarters, items })
+    }
+}
+
+impl Metrics {
+    fn shares(&self, totals: &Self) -> LanguishSignals {
+        let share = |value: f64, total: f64| {
+            if total > 0.0 && value > 0.0 {
+                value / total * 100.0
+            } else {
+                0.0
+            }
+        };
+        LanguishSignals {
+            issues: share(self.issues, totals.issues),
+            pulls: share(self.pulls, totals.pulls),
+            so_questions: share(self.so_questions, totals.so_questions),
+            stars: share(self.stars, totals.stars),
+        }
+    }
 }
 
 fn parse_languish_tables(js: &str) -> Result<LanguishData> {
-    serde_json::from_str(js).context("failed to parse decoded Languish JSON object")
+    serde_json::from_str(js).map_err(|error| SourceError::decode(LANGUISH_DATA, error).into())
 }
 
-fn recent_quarters(sums: &Table) -> Result<RecentQuarters<'_>> {
+fn quarter_snapshots(sums: &Table) -> Result<Vec<QuarterSnapshot<'_>>> {
     let columns = metric_columns(&sums.keys)?;
-    let mut dates: Vec<&str> = sums
-        .rows
-        .iter()
-        .filter_map(|row| parse_metrics_row(row, columns))
-        .map(|row| row.date)
-        .collect();
-    dates.sort_unstable();
-    dates.dedup();
-    let latest_date = dates
-        .last()
-        .copied()
-        .ok_or_else(|| anyhow!("Languish: no dates available in dataset"))?;
-    let previous_date = dates
-        .len()
-        .checked_sub(2)
-        .and_then(|index| dates.get(index))
-        .copied();
-
-    let metrics_for_date = |date| {
-        sums.rows.iter().find_map(|row| {
-            let parsed = parse_metrics_row(row, columns)?;
-            (parsed.date == date).then_some(parsed.metrics)
-        })
-    };
-    let snapshot = |date| {
-        metrics_for_date(date)
-            .map(|metrics| QuarterSnapshot { date, metrics })
-            .ok_or_else(|| anyhow!("Languish: missing sums for date {date}"))
-    };
-
-    Ok(RecentQuarters {
-        latest: snapshot(latest_date)?,
-        previous: previous_date.map(snapshot).transpose()?,
-    })
+    let mut quarters: Vec<QuarterSnapshot<'_>> = Vec::new();
+    for row in &sums.rows {
+        let Some(parsed) = parse_metrics_row(row, columns) else {
+            continue;
+        };
+        if quarters.iter().any(|quarter| quarter.date == parsed.date) {
+            continue;
+        }
+        quarters.push(QuarterSnapshot {
+            date: parsed.date,
+            metrics: parsed.metrics,
+        });
+    }
+    if quarters.is_empty() {
+        return Err(SourceError::missing_field(LANGUISH_DATA, "dated rows of sums").into());
+    }
+    quarters.sort_unstable_by_key(|quarter| quarter.date);
+    Ok(quarters)
 }
 
 fn index_of(keys: &[String], name: &str) -> Result<usize> {
     keys.iter()
         .position(|k| k == name)
-        .ok_or_else(|| anyhow!("missing column '{name}'"))
+        .ok_or_else(|| SourceError::missing_field(LANGUISH_DATA, format!("column '{name}'")).into())
 }
 
 fn metric_columns(keys: &[String]) -> Result<MetricColumns> {
@@ -345,77 +422,64 @@ fn parse_item_row(row: &[Value], columns: ItemColumns) -> Option<ItemRow<'_>> {
     })
 }
 
-#[derive(Default)]
-struct RecentMetrics {
-    latest: Option<Metrics>,
-    previous: Option<Metrics>,
-}
-
-fn build_recent_metrics<'a>(
+fn item_series<'a>(
     items: &'a Table,
-    latest: &str,
-    previous: Option<&str>,
-) -> Result<FxHashMap<&'a str, RecentMetrics>> {
-    let mut metrics_by_language: FxHashMap<&str, RecentMetrics> = FxHashMap::default();
+    quarters: &[QuarterSnapshot<'_>],
+) -> Result<FxHashMap<&'a str, Vec<Metrics>>> {
+    let quarter_index: FxHashMap<&str, usize> = quarters
+        .iter()
+        .enumerate()
+        .map(|(index, quarter)| (quarter.date, index))
+        .collect();
+    let mut series_by_language: FxHashMap<&str, Vec<Metrics>> = FxHashMap::default();
     let columns = item_columns(&items.keys)?;
     for row in &items.rows {
         let Some(parsed) = parse_item_row(row, columns) else {
             continue;
         };
-        if parsed.date < "2012Q1" {
-            continue;
-        }
         // Языки без данных в текущих кварталах остаются в таблице с нулевым рейтингом.
-        let current = metrics_by_language.entry(parsed.name).or_default();
-        if parsed.date == latest {
-            current.latest = Some(parsed.metrics);
-        } else if previous == Some(parsed.date) {
-            current.previous = Some(parsed.metrics);
+        let series = series_by_language
+            .entry(parsed.name)
+            .or_insert_with(|| vec![Metrics::default(); quarters.len()]);
+        if let Some(&index) = quarter_index.get(parsed.date) {
+            series[index] = parsed.metrics;
         }
     }
-    Ok(metrics_by_language)
+    Ok(series_by_language)
 }
 
 fn as_f64(value: &Value) -> f64 {
     value.as_f64().unwrap_or(0.0)
 }
 
-fn mean_percent(
-    metrics: &Metrics,
-    totals: &Metrics,
-    weights: CoreWeights,
-    total_weight: f64,
-) -> f64 {
-    if total_weight <= f64::EPSILON {
-        return 0.0;
-    }
-
-    let mut weighted_sum = 0.0;
-
-    if weights.issues > 0.0 && totals.issues > 0.0 && metrics.issues > 0.0 {
-        weighted_sum = (metrics.issues / totals.issues).mul_add(weights.issues, weighted_sum);
-    }
-    if weights.pulls > 0.0 && totals.pulls > 0.0 && metrics.pulls > 0.0 {
-        weighted_sum = (metrics.pulls / totals.pulls).mul_add(weights.pulls, weighted_sum);
-    }
-    if weights.so_questions > 0.0 && totals.so_questions > 0.0 && metrics.so_questions > 0.0 {
-        weighted_sum = (metrics.so_questions / totals.so_questions)
-            .mul_add(weights.so_questions, weighted_sum);
-    }
-    if weights.stars > 0.0 && totals.stars > 0.0 && metrics.stars > 0.0 {
-        weighted_sum = (metrics.stars / totals.stars).mul_add(weights.stars, weighted_sum);
-    }
-
-    weighted_sum * (100.0 / total_weight)
-}
-
 #[cfg(test)]
 mod tests {
     use super::{
-        as_f64, build_recent_metrics, decode_js_string_literal, extract_json_parse_payload,
-        parse_languish_tables, recent_quarters,
+        Metrics, as_f64, decode_js_string_literal, extract_json_parse_payload, extract_main_js_url,
+        item_series, parse_languish_bundle, parse_languish_history, parse_languish_tables,
+        quarter_snapshots,
+    };
+    use crate::aliases::builtin as builtin_aliases;
+    use crate::{
+        CoreWeights, Language, LanguishScoring, LanguishSignal, SchulzeConfig,
+        compute_languish_backfill, compute_schulze_records,
     };
     use serde_json::Value;
+    use std::collections::HashMap;
+
+    #[test]
+    fn resolves_main_chunk_against_the_index_url() {
+        let html = r#"<script src="/languish/static/js/main.1a2b.chunk.js"></script>"#;
+
+        assert_eq!(
+            extract_main_js_url(html, "https://tjpalmer.github.io/languish/").as_deref(),
+            Some("https://tjpalmer.github.io/languish/static/js/main.1a2b.chunk.js")
+        );
+        assert_eq!(
+            extract_main_js_url(html, "http://127.0.0.1:8080/languish/").as_deref(),
+            Some("http://127.0.0.1:8080/languish/static/js/main.1a2b.chunk.js")
+        );
+    }
 
     #[test]
     fn as_f64_supports_large_unsigned_values() {
@@ -468,23 +532,180 @@ mod tests {
             }
         }"#;
         let tables = parse_languish_tables(json).expect("fixture should parse");
-        let quarters = recent_quarters(&tables.sums).expect("quarters should parse");
-        let items = build_recent_metrics(
-            &tables.items,
-            quarters.latest.date,
-            quarters.previous.map(|quarter| quarter.date),
-        )
-        .expect("items should parse");
-
-        assert_eq!(quarters.latest.date, "2024Q2");
+        let quarters = quarter_snapshots(&tables.sums).expect("quarters should parse");
+        let items = item_series(&tables.items, &quarters).expect("items should parse");
+        let has_metrics = |metrics: &Metrics| metrics.issues > 0.0;
+
+        let dates: Vec<&str> = quarters.iter().map(|quarter| quarter.date).collect();
+        assert_eq!(dates, ["2024Q1", "2024Q2"]);
+        assert!(!items["Legacy"].iter().any(has_metrics));
+        assert!(has_metrics(&items["Current"][1]));
+        assert!(has_metrics(&items["Both"][1]));
+        assert!(has_metrics(&items["Both"][0]));
+    }
+
+    #[test]
+    fn keeps_every_published_quarter() {
+        let json = r#"{
+            "items": {
+                "keys": ["name", "date", "issues", "pulls", "soQuestions", "stars"],
+                "rows": [
+                    ["Early", "2008Q3", 1, 1, 1, 1],
+                    ["Early", "2012Q1", 2, 2, 2, 2]
+                ]
+            },
+            "sums": {
+                "keys": ["date", "issues", "pulls", "soQuestions", "stars"],
+                "rows": [
+                    ["2012Q1", 10, 10, 10, 10],
+                    ["2008Q3", 10, 10, 10, 10]
+                ]
+            }
+        }"#;
+        let tables = parse_languish_tables(json).expect("fixture should parse");
+        let quarters = quarter_snapshots(&tables.sums).expect("quarters should parse");
+        let items = item_series(&tables.items, &quarters).expect("items should parse");
+
+        let dates: Vec<&str> = quarters.iter().map(|quarter| quarter.date).collect();
+        assert_eq!(dates, ["2008Q3", "2012Q1"]);
+        let early: Vec<f64> = items["Early"]
+            .iter()
+            .map(|metrics| metrics.issues)
+            .collect();
+        assert_eq!(early, [1.0, 2.0]);
+    }
+
+    #[test]
+    fn builds_weighted_history_and_year_over_year_trend() {
+        let js = r#"const data = JSON.parse('{
+            "items": {
+                "keys": ["name", "date", "issues", "pulls", "soQuestions", "stars"],
+                "rows": [
+                    ["Rust", "2023Q1", 10, 10, 10, 90],
+                    ["Rust", "2024Q1", 30, 30, 30, 10],
+                    ["Go", "2023Q1", 5, 5, 5, 5],
+                    ["Golang", "2023Q1", 5, 5, 5, 5],
+                    ["Go", "2024Q1", 10, 10, 10, 10]
+                ]
+            },
+            "sums": {
+                "keys": ["date", "issues", "pulls", "soQuestions", "stars"],
+                "rows": [
+                    ["2023Q1", 100, 100, 100, 100],
+                    ["2023Q2", 100, 100, 100, 100],
+                    ["2023Q3", 100, 100, 100, 100],
+                    ["2023Q4", 100, 100, 100, 100],
+                    ["2024Q1", 100, 100, 100, 100]
+                ]
+            }
+        }');"#;
+        let weights = CoreWeights {
+            stars: 0.0,
+            ..CoreWeights::default()
+        };
+
+        let history =
+            parse_languish_history(js, builtin_aliases(), weights).expect("history should parse");
+        assert_eq!(history.quarters().len(), 5);
+        let rust = history.get(Language::intern("Rust")).expect("Rust history");
+        assert_eq!(rust[0].quarter, "2023Q1");
+        assert!((rust[0].signals.stars - 90.0).abs() < 1e-9);
+        assert!((rust[0].score - 10.0).abs() < 1e-9);
+        assert!(rust[2].score.abs() < 1e-9);
+        let go = history.get(Language::intern("Go")).expect("Go history");
+        assert!((go[0].signals.issues - 10.0).abs() < 1e-9);
+
+        let scoring = LanguishScoring {
+            weights,
+            trend_quarters: 4,
+            ..LanguishScoring::default()
+        };
+        let entries =
+            parse_languish_bundle(js, builtin_aliases(), scoring).expect("ranking should parse");
+        let rust = entries
+            .iter()
+            .find(|entry| entry.lang == Language::intern("Rust"))
+            .expect("Rust entry");
+        assert!(rust.share.is_some_and(|share| (share - 30.0).abs() < 1e-9));
+        assert!(rust.trend.is_some_and(|trend| (trend - 20.0).abs() < 1e-9));
+        assert!(entries.sub_ballots().is_empty());
+
+        let quarter_over_quarter = LanguishScoring {
+            weights,
+            trend_quarters: 1,
+            signal_ballots: true,
+        };
+        let entries = parse_languish_bundle(js, builtin_aliases(), quarter_over_quarter)
+            .expect("ranking should parse");
+        let rust = entries
+            .iter()
+            .find(|entry| entry.lang == Language::intern("Rust"))
+            .expect("Rust entry");
+        assert!(rust.trend.is_some_and(|trend| (trend - 30.0).abs() < 1e-9));
+        let ballots: Vec<_> = entries
+            .sub_ballots()
+            .iter()
+            .map(|ballot| ballot.id)
+            .collect();
+        assert_eq!(ballots, LanguishSignal::ALL.map(LanguishSignal::as_str));
+        let stars = &entries.sub_ballots()[3].scores;
+        assert!((stars[&Language::intern("Rust")] - 10.0).abs() < 1e-9);
+        let so_questions = &entries.sub_ballots()[2].scores;
+        assert!((so_questions[&Language::intern("Go")] - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn live_ranking_matches_latest_backfilled_quarter() {
+        let js = r#"const data = JSON.parse('{
+            "items": {
+                "keys": ["name", "date", "issues", "pulls", "soQuestions", "stars"],
+                "rows": [
+                    ["Rust", "2023Q4", 10, 10, 10, 10],
+                    ["Rust", "2024Q1", 30, 30, 30, 30],
+                    ["Go", "2024Q1", 20, 20, 20, 20],
+                    ["Zig", "2023Q4", 5, 5, 5, 5]
+                ]
+            },
+            "sums": {
+                "keys": ["date", "issues", "pulls", "soQuestions", "stars"],
+                "rows": [
+                    ["2023Q4", 100, 100, 100, 100],
+                    ["2024Q1", 100, 100, 100, 100]
+                ]
+            }
+        }');"#;
+        let scoring = LanguishScoring {
+            trend_quarters: 1,
+            signal_ballots: true,
+            ..LanguishScoring::default()
+        };
+
+        let live =
+            parse_languish_bundle(js, builtin_aliases(), scoring).expect("ranking should parse");
+        let zig = live
+            .iter()
+            .find(|entry| entry.lang == Language::intern("Zig"))
+            .expect("zero-score languages stay in the live ranking");
+        assert!(zig.share.is_some_and(|share| share.abs() < 1e-9));
+
+        let history = parse_languish_history(js, builtin_aliases(), scoring.weights)
+            .expect("history should parse");
         assert_eq!(
-            quarters.previous.map(|quarter| quarter.date),
-            Some("2024Q1")
+            history.quarter_dataset("2024Q1", scoring),
+            Some(live.clone())
         );
-        assert!(items["Legacy"].latest.is_none());
-        assert!(items["Legacy"].previous.is_none());
-        assert!(items["Current"].latest.is_some());
-        assert!(items["Both"].latest.is_some());
-        assert!(items["Both"].previous.is_some());
+
+        let config = SchulzeConfig {
+            min_source_overlap: 1,
+            ..SchulzeConfig::default()
+        };
+        let empty: HashMap<Language, f64> = HashMap::new();
+        let expected = compute_schulze_records(&[live], &empty, &empty, config)
+            .expect("live ranking should be ranked");
+        let (quarter, backfilled) = compute_languish_backfill(&history, None, scoring)
+            .last()
+            .expect("history has quarters");
+        assert_eq!(quarter, "2024Q1");
+        assert_eq!(backfilled.expect("quarter should be ranked"), expected);
     }
 }
@@ -1,13 +1,15 @@
-use crate::RankingEntry;
-use anyhow::{Context, Result, anyhow};
-use reqwest::Client;
+use crate::aliases::builtin as builtin_aliases;
+use crate::{AliasRegistry, RankingEntry, RankingSource, SourceError, Transport};
+use anyhow::{Context, Result};
 use scraper::{Html, Selector};
 use std::sync::OnceLock;
 
 use super::{
-    RawEntry, aggregate_entries, extract_cell_text, fetch_text_with_retry, parse_percent, parse_u32,
+    HttpContext, RawEntry, aggregate_entries, extract_cell_text, fetch_text_with_retry,
+    parse_percent, parse_u32,
 };
 
+const PYPL_PAGE: &str = "PYPL page";
 const PYPL_URL: &str = "https://pypl.github.io/PYPL.html";
 
 struct PyplRow<'a> {
@@ -34,7 +36,7 @@ impl<'a> PyplRow<'a> {
         RawEntry::parse(
             self.lang,
             parse_u32(self.rank),
-            parse_percent(self.share).unwrap_or(0.0),
+            parse_percent(self.share),
             parse_percent(self.trend),
         )
     }
@@ -45,14 +47,21 @@ impl<'a> PyplRow<'a> {
 /// # Errors
 ///
 /// Возвращает ошибку при сбое HTTP-запроса или несовместимом формате данных.
-pub async fn fetch_pypl(client: &Client) -> Result<Vec<RankingEntry>> {
-    let body = fetch_text_with_retry(client, PYPL_URL)
+pub async fn fetch_pypl(transport: &dyn Transport) -> Result<Vec<RankingEntry>> {
+    fetch_pypl_with(HttpContext::with_defaults(transport), builtin_aliases()).await
+}
+
+pub async fn fetch_pypl_with(
+    http: HttpContext<'_>,
+    aliases: &AliasRegistry,
+) -> Result<Vec<RankingEntry>> {
+    let body = fetch_text_with_retry(http, http.url(RankingSource::Pypl, PYPL_URL))
         .await
         .context("failed to download PYPL index")?;
-    parse_pypl(body.as_str())
+    parse_pypl(body.as_str(), aliases)
 }
 
-fn parse_pypl(body: &str) -> Result<Vec<RankingEntry>> {
+fn parse_pypl(body: &str, aliases: &AliasRegistry) -> Result<Vec<RankingEntry>> {
     let raw_fragment = extract_all_section(body)?;
     let table_html = build_rows_table_html(raw_fragment);
     let document = Html::parse_fragment(table_html.as_str());
@@ -64,7 +73,7 @@ fn parse_pypl(body: &str) -> Result<Vec<RankingEntry>> {
             entries.push(entry);
         }
     }
-    Ok(aggregate_entries(entries))
+    Ok(aggregate_entries(entries, aliases))
 }
 
 fn extract_all_section(body: &str) -> Result<&str> {
@@ -73,13 +82,21 @@ fn extract_all_section(body: &str) -> Result<&str> {
     let start_idx = body
         .find(start_marker)
         .map(|idx| idx + start_marker.len())
-        .ok_or_else(|| anyhow!("PYPL start marker not found"))?;
-    let end_idx = body[start_idx..]
-        .find(end_marker)
-        .map(|idx| idx + start_idx)
-        .ok_or_else(|| anyhow!("PYPL end marker not found"))?;
-    if start_idx >= end_idx {
-        return Err(anyhow!("PYPL markers are in unexpected order"));
+        .ok_or(SourceError::MarkupNotFound {
+            document: PYPL_PAGE,
+            element: "start marker of section All",
+        })?;
+    let end_idx = body.find(end_marker).ok_or(SourceError::MarkupNotFound {
+        document: PYPL_PAGE,
+        element: "end marker of section All",
+    })?;
+    if start_idx > end_idx {
+        return Err(SourceError::MarkersOutOfOrder {
+            document: PYPL_PAGE,
+            start: "start marker of section All",
+            end: "end marker of section All",
+        }
+        .into());
     }
     Ok(&body[start_idx..end_idx])
 }
@@ -121,6 +138,7 @@ fn row_selector() -> &'static Selector {
 #[cfg(test)]
 mod tests {
     use super::parse_pypl;
+    use crate::{AliasRegistry, SourceError};
 
     #[test]
     fn parses_all_section_with_noise() {
@@ -134,9 +152,34 @@ mod tests {
             </body></html>
         "#;
 
-        let entries = parse_pypl(body).expect("PYPL fixture should parse");
+        let entries =
+            parse_pypl(body, &AliasRegistry::builtin()).expect("PYPL fixture should parse");
         assert_eq!(entries.len(), 2);
         assert_eq!(entries[0].lang, "Go");
         assert_eq!(entries[1].lang, "Rust");
     }
+
+    #[test]
+    fn reports_missing_section_markers_as_markup_errors() {
+        let error = parse_pypl(
+            "<html><body>redesigned</body></html>",
+            &AliasRegistry::builtin(),
+        )
+        .expect_err("page without markers should fail");
+
+        assert!(matches!(
+            error.downcast_ref::<SourceError>(),
+            Some(SourceError::MarkupNotFound { .. })
+        ));
+
+        let error = parse_pypl(
+            "<!-- end section All--><tr><td>1</td></tr><!-- begin section All-->",
+            &AliasRegistry::builtin(),
+        )
+        .expect_err("swapped markers should fail");
+        assert!(matches!(
+            error.downcast_ref::<SourceError>(),
+            Some(SourceError::MarkersOutOfOrder { .. })
+        ));
+    }
 }
@@ -1,20 +1,22 @@
+use crate::aliases::builtin as builtin_aliases;
+use crate::{
+    AliasRegistry, FrameworkApproach, FrameworkClassification, FrameworkOrm, FrameworkResult,
+    Language, LanguageTechEmpower, PerformanceSource, SourceError, TechEmpowerFilter,
+    TechEmpowerResults, TechEmpowerScoring, Transport,
+};
 use anyhow::{Context, Result, anyhow};
-use reqwest::{Client, Url};
 use rustc_hash::{FxHashMap, FxHashSet};
 use scraper::{Html, Selector};
 use serde::Deserialize;
 use std::path::Path;
 
-use super::{CanonicalLanguage, fetch_bytes_with_retry, fetch_text_with_retry};
+use super::{HttpContext, fetch_bytes_with_retry, fetch_text_with_retry, resolve_url};
 
 const TFB_BENCHMARKS_URL: &str = "https://www.techempower.com/benchmarks/";
-const MAX_FALLBACK_RESULTS_URLS: usize = 8;
+const TFB_RESULTS: &str = "TechEmpower results JSON";
+const MAX_FALLBACK_ROUNDS: usize = 8;
 const MIN_SUPPORTED_ROUND: u16 = 21;
-const STATIC_FALLBACK_RESULTS_URLS: [&str; 3] = [
-    "https://www.techempower.com/benchmarks/results/round23/ph.json",
-    "https://www.techempower.com/benchmarks/results/round22/ph.json",
-    "https://www.techempower.com/benchmarks/results/round21/ph.json",
-];
+const STATIC_FALLBACK_ROUNDS: [u16; 3] = [23, 22, 21];
 
 #[derive(Clone, Copy)]
 struct TestConfig {
@@ -57,7 +59,7 @@ struct FrameworkThroughput {
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct TechEmpowerResults<'a> {
+struct RoundResults<'a> {
     #[serde(borrow)]
     raw_data: BenchmarkData<'a>,
     #[serde(borrow)]
@@ -109,8 +111,22 @@ struct BenchmarkRun {
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "snake_case")]
 struct FrameworkMetadata<'a> {
+    /// Реализация, по имени которой индексируется `rawData`; у одного
+    /// фреймворка их может быть несколько с разными ORM, БД и подходом.
+    #[serde(borrow, default)]
+    name: Option<&'a str>,
     framework: &'a str,
     language: &'a str,
+    #[serde(borrow, default)]
+    classification: Option<&'a str>,
+    #[serde(borrow, default)]
+    approach: Option<&'a str>,
+    #[serde(borrow, default)]
+    orm: Option<&'a str>,
+    #[serde(borrow, default)]
+    database: Option<&'a str>,
+    #[serde(borrow, default)]
+    os: Option<&'a str>,
 }
 
 /// Загружает `TechEmpower` и вычисляет лучший показатель для каждого языка.
@@ -119,92 +135,179 @@ struct FrameworkMetadata<'a> {
 ///
 /// Возвращает ошибку, если ни один поддерживаемый источник результатов не
 /// удалось загрузить и разобрать.
-pub async fn fetch_techempower(client: &Client) -> Result<FxHashMap<String, f64>> {
-    let fallback_urls = fallback_results_urls(client).await;
-    let mut errors: Vec<String> = Vec::new();
+pub async fn fetch_techempower(transport: &dyn Transport) -> Result<FxHashMap<Language, f64>> {
+    Ok(
+        fetch_techempower_results(transport, TechEmpowerScoring::default())
+            .await?
+            .scores(),
+    )
+}
 
-    for results_url in fallback_urls {
-        match fetch_techempower_for_results_url(client, &results_url).await {
-            Ok(scores) => return Ok(scores),
-            Err(err) => errors.push(format!("{results_url}: {err:#}")),
-        }
-    }
+/// Загружает `TechEmpower` и возвращает результаты всех фреймворков по
+/// языкам вместе с показателями по заданной стратегии.
+///
+/// # Errors
+///
+/// Возвращает ошибку, если ни один поддерживаемый источник результатов не
+/// удалось загрузить и разобрать.
+pub async fn fetch_techempower_results(
+    transport: &dyn Transport,
+    scoring: TechEmpowerScoring,
+) -> Result<TechEmpowerResults> {
+    fetch_techempower_with(
+        HttpContext::with_defaults(transport),
+        builtin_aliases(),
+        &scoring,
+        None,
+    )
+    .await
+}
 
-    let summary = if errors.is_empty() {
-        "no fallback URLs were available".to_string()
-    } else {
-        errors.join(" | ")
-    };
-    Err(anyhow!(
-        "failed to fetch TechEmpower data from benchmarks results sources; errors: {summary}"
-    ))
+/// Загружает указанный раунд `TechEmpower` без перебора других раундов.
+///
+/// # Errors
+///
+/// Возвращает ошибку, если раунд не поддерживается, не загрузился или не
+/// разобрался.
+pub async fn fetch_techempower_round(
+    transport: &dyn Transport,
+    round: u16,
+    scoring: TechEmpowerScoring,
+) -> Result<TechEmpowerResults> {
+    fetch_techempower_with(
+        HttpContext::with_defaults(transport),
+        builtin_aliases(),
+        &scoring,
+        Some(round),
+    )
+    .await
 }
 
-async fn fetch_techempower_for_results_url(
-    client: &Client,
-    results_url: &str,
-) -> Result<FxHashMap<String, f64>> {
-    let bytes = fetch_bytes_with_retry(client, results_url)
-        .await
-        .with_context(|| format!("failed to download TechEmpower results from {results_url}"))?;
-    parse_techempower_results(&bytes)
+/// Возвращает поддерживаемые раунды `TechEmpower` от нового к старому.
+///
+/// Раунды ищутся в JS-бандле страницы бенчмарков; если страницу не удалось
+/// загрузить или разобрать, возвращаются встроенные известные раунды.
+pub async fn list_techempower_rounds(transport: &dyn Transport) -> Vec<u16> {
+    let discovered = discover_techempower_rounds_with(HttpContext::with_defaults(transport)).await;
+    with_static_rounds(discovered)
 }
 
-fn parse_techempower_results(bytes: &[u8]) -> Result<FxHashMap<String, f64>> {
-    let results: TechEmpowerResults<'_> =
-        serde_json::from_slice(bytes).context("failed to parse TechEmpower results JSON")?;
-    compute_language_scores(&results)
+pub async fn discover_techempower_rounds_with(http: HttpContext<'_>) -> Result<Vec<u16>> {
+    let base_url = http.url(PerformanceSource::TechEmpower, TFB_BENCHMARKS_URL);
+    discover_rounds(http, base_url).await
 }
 
-fn resolve_url(base_url: &str, href: &str) -> String {
-    if href.starts_with("http://") || href.starts_with("https://") {
-        return href.to_string();
+/// Дополняет найденные раунды встроенными и упорядочивает от нового к
+/// старому; при сбое поиска остаются только встроенные раунды.
+pub fn with_static_rounds(discovered: Result<Vec<u16>>) -> Vec<u16> {
+    let mut rounds = discovered.unwrap_or_else(|error| {
+        eprintln!("Warning: {error:#}; listing the built-in TechEmpower rounds.");
+        Vec::new()
+    });
+    rounds.extend(STATIC_FALLBACK_ROUNDS);
+    rounds.sort_unstable_by(|left, right| right.cmp(left));
+    rounds.dedup();
+    rounds
+}
+
+/// Загружает раунд `round` или, если он не задан, первый раунд, который
+/// удалось загрузить и разобрать, начиная с самого свежего.
+pub async fn fetch_techempower_with(
+    http: HttpContext<'_>,
+    aliases: &AliasRegistry,
+    scoring: &TechEmpowerScoring,
+    round: Option<u16>,
+) -> Result<TechEmpowerResults> {
+    if let Some(round) = round {
+        if round < MIN_SUPPORTED_ROUND {
+            return Err(anyhow!(
+                "TechEmpower round {round} is not supported (the oldest supported round is {MIN_SUPPORTED_ROUND})"
+            ));
+        }
+        return fetch_techempower_for_round(http, round, aliases, scoring).await;
     }
-    if let Ok(base) = Url::parse(base_url)
-        && let Ok(joined) = base.join(href)
-    {
-        return joined.to_string();
+
+    let fallback_rounds = fallback_rounds(http).await;
+    let mut errors: Vec<anyhow::Error> = Vec::new();
+
+    for round in fallback_rounds {
+        match fetch_techempower_for_round(http, round, aliases, scoring).await {
+            Ok(scores) => return Ok(scores),
+            Err(err) => errors.push(err),
+        }
     }
-    if href.starts_with('/') {
-        format!("{base_url}{href}")
-    } else {
-        format!("{base_url}/{href}")
+
+    // Причиной считается ошибка самого свежего раунда, чтобы её тип был
+    // доступен через `FetchError::source_error`; остальные идут в описание.
+    let mut errors = errors.into_iter();
+    let Some(first) = errors.next() else {
+        return Err(anyhow!(
+            "failed to fetch TechEmpower data: no fallback URLs were available"
+        ));
+    };
+    let others: Vec<String> = errors.map(|err| format!("{err:#}")).collect();
+    if others.is_empty() {
+        return Err(first);
     }
+    Err(first.context(format!(
+        "failed to fetch TechEmpower data from benchmarks results sources; other errors: {}",
+        others.join(" | ")
+    )))
 }
 
-async fn fallback_results_urls(client: &Client) -> Vec<String> {
-    let mut urls = discover_fallback_results_urls(client)
+async fn fetch_techempower_for_round(
+    http: HttpContext<'_>,
+    round: u16,
+    aliases: &AliasRegistry,
+    scoring: &TechEmpowerScoring,
+) -> Result<TechEmpowerResults> {
+    let base_url = http.url(PerformanceSource::TechEmpower, TFB_BENCHMARKS_URL);
+    let results_url = round_results_url(base_url, round);
+    let bytes = fetch_bytes_with_retry(http, &results_url)
         .await
-        .unwrap_or_default();
+        .with_context(|| format!("failed to download TechEmpower results from {results_url}"))?;
+    let results = parse_techempower_results(&bytes, aliases, scoring)
+        .with_context(|| format!("failed to score TechEmpower round {round}"))?;
+    Ok(results.with_round(round))
+}
 
-    for url in STATIC_FALLBACK_RESULTS_URLS {
-        urls.push(url.to_string());
-    }
+fn parse_techempower_results(
+    bytes: &[u8],
+    aliases: &AliasRegistry,
+    scoring: &TechEmpowerScoring,
+) -> Result<TechEmpowerResults> {
+    let results: RoundResults<'_> =
+        serde_json::from_slice(bytes).map_err(|error| SourceError::decode(TFB_RESULTS, error))?;
+    compute_language_results(&results, aliases, scoring)
+}
+
+async fn fallback_rounds(http: HttpContext<'_>) -> Vec<u16> {
+    let base_url = http.url(PerformanceSource::TechEmpower, TFB_BENCHMARKS_URL);
+    let mut rounds = discover_rounds(http, base_url).await.unwrap_or_default();
+    rounds.extend(STATIC_FALLBACK_ROUNDS);
 
-    urls = dedup_urls_preserve_order(urls);
-    urls.truncate(MAX_FALLBACK_RESULTS_URLS);
-    urls
+    rounds = dedup_preserve_order(rounds);
+    rounds.truncate(MAX_FALLBACK_ROUNDS);
+    rounds
 }
 
-async fn discover_fallback_results_urls(client: &Client) -> Result<Vec<String>> {
-    let html = fetch_text_with_retry(client, TFB_BENCHMARKS_URL)
+async fn discover_rounds(http: HttpContext<'_>, base_url: &str) -> Result<Vec<u16>> {
+    let html = fetch_text_with_retry(http, base_url)
         .await
         .context("failed to fetch TechEmpower benchmarks page for fallback discovery")?;
-    let bundle_url = benchmarks_bundle_url(&html)
+    let bundle_url = benchmarks_bundle_url(&html, base_url)
         .ok_or_else(|| anyhow!("unable to locate benchmarks JS bundle for fallback discovery"))?;
-    let bundle = fetch_text_with_retry(client, &bundle_url)
+    let bundle = fetch_text_with_retry(http, &bundle_url)
         .await
         .with_context(|| format!("failed to fetch TechEmpower benchmarks bundle {bundle_url}"))?;
-    let urls = extract_round_results_urls(&bundle);
-    if urls.is_empty() {
-        return Err(anyhow!(
-            "no fallback URLs found in TechEmpower benchmarks bundle"
-        ));
+    let rounds = extract_rounds(&bundle);
+    if rounds.is_empty() {
+        return Err(anyhow!("no rounds found in TechEmpower benchmarks bundle"));
     }
-    Ok(urls)
+    Ok(rounds)
 }
 
-fn benchmarks_bundle_url(html: &str) -> Option<String> {
+fn benchmarks_bundle_url(html: &str, base_url: &str) -> Option<String> {
     let document = Html::parse_document(html);
     let script_selector = Selector::parse("script[src]").ok()?;
     for script in document.select(&script_selector) {
@@ -214,13 +317,13 @@ fn benchmarks_bundle_url(html: &str) -> Option<String> {
                 .extension()
                 .is_some_and(|ext| ext.eq_ignore_ascii_case("js"))
         {
-            return Some(resolve_url(TFB_BENCHMARKS_URL, src));
+            return Some(resolve_url(base_url, src));
         }
     }
     None
 }
 
-fn extract_round_results_urls(bundle: &str) -> Vec<String> {
+fn extract_rounds(bundle: &str) -> Vec<u16> {
     const ROUND_MARKER: &str = "data-r";
     let mut rounds: Vec<u16> = Vec::new();
     let mut rest = bundle;
@@ -243,32 +346,38 @@ fn extract_round_results_urls(bundle: &str) -> Vec<String> {
     rounds.sort_unstable();
     rounds.dedup();
     rounds.reverse();
+    rounds.retain(|round| *round >= MIN_SUPPORTED_ROUND);
     rounds
-        .into_iter()
-        .filter(|round| *round >= MIN_SUPPORTED_ROUND)
-        .map(|round| format!("{TFB_BENCHMARKS_URL}results/round{round}/ph.json"))
-        .collect()
 }
 
-fn dedup_urls_preserve_order(urls: Vec<String>) -> Vec<String> {
-    let mut seen: FxHashSet<String> = FxHashSet::default();
-    let mut unique: Vec<String> = Vec::new();
-    for url in urls {
-        if seen.insert(url.clone()) {
-            unique.push(url);
+fn round_results_url(base_url: &str, round: u16) -> String {
+    resolve_url(base_url, &format!("results/round{round}/ph.json"))
+}
+
+fn dedup_preserve_order(rounds: Vec<u16>) -> Vec<u16> {
+    let mut seen: FxHashSet<u16> = FxHashSet::default();
+    let mut unique: Vec<u16> = Vec::new();
+    for round in rounds {
+        if seen.insert(round) {
+            unique.push(round);
         }
     }
     unique
 }
 
-fn compute_language_scores(results: &TechEmpowerResults<'_>) -> Result<FxHashMap<String, f64>> {
-    let framework_languages = map_framework_languages(&results.test_metadata);
+fn compute_language_results(
+    results: &RoundResults<'_>,
+    aliases: &AliasRegistry,
+    scoring: &TechEmpowerScoring,
+) -> Result<TechEmpowerResults> {
+    let mut frameworks = describe_frameworks(&results.test_metadata, aliases, &scoring.filter);
 
-    let mut throughput_by_framework: FxHashMap<&str, FrameworkThroughput> = FxHashMap::default();
+    let mut throughput_by_implementation: FxHashMap<&str, FrameworkThroughput> =
+        FxHashMap::default();
     let mut max_rps_by_test = [0.0_f64; TEST_COUNT];
 
     for (test_idx, frameworks) in results.raw_data.tests().into_iter().enumerate() {
-        for (framework, runs) in frameworks {
+        for (implementation, runs) in frameworks {
             let max_rps = runs
                 .iter()
                 .filter_map(BenchmarkRun::requests_per_second)
@@ -276,7 +385,9 @@ fn compute_language_scores(results: &TechEmpowerResults<'_>) -> Result<FxHashMap
             if max_rps <= 0.0 {
                 continue;
             }
-            let entry = throughput_by_framework.entry(framework).or_default();
+            let entry = throughput_by_implementation
+                .entry(implementation)
+                .or_default();
             entry.rps[test_idx] = max_rps;
             if max_rps > max_rps_by_test[test_idx] {
                 max_rps_by_test[test_idx] = max_rps;
@@ -286,50 +397,183 @@ fn compute_language_scores(results: &TechEmpowerResults<'_>) -> Result<FxHashMap
 
     for (test, max_rps) in TESTS.iter().zip(max_rps_by_test) {
         if max_rps <= 0.0 {
-            return Err(anyhow!("missing TechEmpower data for test '{}'", test.name));
+            return Err(
+                SourceError::missing_field(TFB_RESULTS, format!("test '{}'", test.name)).into(),
+            );
         }
     }
 
-    let mut best_by_language: FxHashMap<String, f64> = FxHashMap::default();
-    for (framework, throughput) in throughput_by_framework {
-        if throughput.rps.iter().any(|rps| *rps <= 0.0) {
-            continue;
-        }
-        let mut composite = 0.0_f64;
-        for ((rps, max_rps), test) in throughput.rps.iter().zip(max_rps_by_test).zip(TESTS) {
-            composite = (*rps / max_rps).mul_add(test.weight, composite);
-        }
-        if composite <= 0.0 {
-            continue;
-        }
-        let Some(language) = framework_languages.get(&framework) else {
+    let mut frameworks_by_language: FxHashMap<Language, Vec<FrameworkResult>> =
+        FxHashMap::default();
+    for (implementation, throughput) in throughput_by_implementation {
+        let Some((language, mut result)) = frameworks.remove(implementation) else {
             continue;
         };
-        let entry = best_by_language.entry(language.clone()).or_insert(0.0);
-        if composite > *entry {
-            *entry = composite;
-        }
+        throughput.fill(&mut result, &max_rps_by_test);
+        frameworks_by_language
+            .entry(language)
+            .or_default()
+            .push(result);
     }
 
-    if best_by_language.is_empty() {
+    let languages: FxHashMap<Language, LanguageTechEmpower> = frameworks_by_language
+        .into_iter()
+        .filter_map(|(language, frameworks)| {
+            Some((language, language_result(frameworks, scoring)?))
+        })
+        .collect();
+
+    if languages.is_empty() {
+        if !scoring.filter.is_empty() {
+            return Err(anyhow!(
+                "no TechEmpower frameworks match the filter ({})",
+                scoring.filter
+            ));
+        }
         return Err(anyhow!("no TechEmpower language scores computed"));
     }
 
-    Ok(best_by_language)
+    Ok(TechEmpowerResults::new(languages).with_filter(scoring.filter.clone()))
+}
+
+impl FrameworkThroughput {
+    fn fill(&self, result: &mut FrameworkResult, max_rps_by_test: &[f64; TEST_COUNT]) {
+        if self.rps.iter().all(|rps| *rps > 0.0) {
+            let mut total = 0.0_f64;
+            for ((rps, max_rps), test) in self.rps.iter().zip(max_rps_by_test).zip(TESTS) {
+                total = (rps / max_rps).mul_add(test.weight, total);
+            }
+            if total > 0.0 {
+                result.composite = Some(total);
+            }
+        }
+        [
+            result.json,
+            result.plaintext,
+            result.db,
+            result.query,
+            result.fortune,
+            result.update,
+        ] = self.rps.map(|rps| Some(rps).filter(|rps| *rps > 0.0));
+    }
+}
+
+/// Сводит фреймворки языка к показателю; языки без единого фреймворка с
+/// полным набором тестов пропускаются.
+fn language_result(
+    mut frameworks: Vec<FrameworkResult>,
+    scoring: &TechEmpowerScoring,
+) -> Option<LanguageTechEmpower> {
+    frameworks.sort_by(|left, right| {
+        let left_composite = left.composite.unwrap_or(f64::NEG_INFINITY);
+        let right_composite = right.composite.unwrap_or(f64::NEG_INFINITY);
+        right_composite
+            .total_cmp(&left_composite)
+            .then_with(|| left.framework.cmp(&right.framework))
+    });
+    let composites: Vec<f64> = frameworks
+        .iter()
+        .filter_map(|framework| framework.composite)
+        .collect();
+    let &best = composites.first()?;
+    let top_k_mean = mean(&composites[..scoring.top_k.clamp(1, composites.len())])?;
+    let median = median_of_sorted(&composites);
+    let mut result = LanguageTechEmpower {
+        score: best,
+        winner: frameworks[0].framework.clone(),
+        best,
+        top_k_mean,
+        median,
+        frameworks,
+    };
+    result.score = result.aggregate(scoring.aggregation);
+    Some(result)
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    let count = u32::try_from(values.len())
+        .ok()
+        .filter(|&count| count > 0)?;
+    Some(values.iter().sum::<f64>() / f64::from(count))
+}
+
+fn median_of_sorted(values: &[f64]) -> f64 {
+    let middle = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        f64::midpoint(values[middle - 1], values[middle])
+    } else {
+        values[middle]
+    }
 }
 
-fn map_framework_languages<'a>(metadata: &[FrameworkMetadata<'a>]) -> FxHashMap<&'a str, String> {
+/// Сопоставляет реализациям язык и метаданные, если реализация прошла фильтр.
+fn describe_frameworks<'a>(
+    metadata: &[FrameworkMetadata<'a>],
+    aliases: &AliasRegistry,
+    filter: &TechEmpowerFilter,
+) -> FxHashMap<&'a str, (Language, FrameworkResult)> {
     let mut map = FxHashMap::default();
     for entry in metadata {
-        let Some(language) = CanonicalLanguage::parse(entry.language) else {
+        if map.contains_key(entry.implementation()) {
+            continue;
+        }
+        let Some(language) = aliases.canonicalize(entry.language) else {
             continue;
         };
-        map.entry(entry.framework)
-            .or_insert_with(|| language.into_string());
+        let description = entry.describe();
+        if filter.matches(&description) {
+            map.insert(entry.implementation(), (language, description));
+        }
     }
     map
 }
 
+impl<'a> FrameworkMetadata<'a> {
+    fn implementation(&self) -> &'a str {
+        self.name.unwrap_or(self.framework)
+    }
+
+    fn describe(&self) -> FrameworkResult {
+        FrameworkResult {
+            framework: self.implementation().to_owned(),
+            classification: self.classification.and_then(parse_classification),
+            approach: self.approach.and_then(parse_approach),
+            orm: self.orm.and_then(parse_orm),
+            database: self.database.map(str::to_owned),
+            os: self.os.map(str::to_owned),
+            json: None,
+            plaintext: None,
+            db: None,
+            query: None,
+            fortune: None,
+            update: None,
+            composite: None,
+        }
+    }
+}
+
+fn parse_classification(value: &str) -> Option<FrameworkClassification> {
+    [
+        FrameworkClassification::Fullstack,
+        FrameworkClassification::Micro,
+        FrameworkClassification::Platform,
+    ]
+    .into_iter()
+    .find(|classification| classification.as_str().eq_ignore_ascii_case(value))
+}
+
+fn parse_approach(value: &str) -> Option<FrameworkApproach> {
+    [FrameworkApproach::Realistic, FrameworkApproach::Stripped]
+        .into_iter()
+        .find(|approach| approach.as_str().eq_ignore_ascii_case(value))
+}
+
+fn parse_orm(value: &str) -> Option<FrameworkOrm> {
+    [FrameworkOrm::Full, FrameworkOrm::Micro, FrameworkOrm::Raw]
+        .into_iter()
+        .find(|orm| orm.as_str().eq_ignore_ascii_case(value))
+}
+
 impl BenchmarkRun {
     fn requests_per_second(&self) -> Option<f64> {
         let duration = self.end_time - self.start_time;
@@ -343,8 +587,12 @@ impl BenchmarkRun {
 #[cfg(test)]
 mod tests {
     use super::{
-        TECHEMPOWER_MAX_SCORE, TechEmpowerResults, compute_language_scores,
-        dedup_urls_preserve_order, extract_round_results_urls,
+        RoundResults, TECHEMPOWER_MAX_SCORE, TFB_BENCHMARKS_URL, compute_language_results,
+        dedup_preserve_order, extract_rounds, round_results_url,
+    };
+    use crate::{
+        AliasRegistry, FrameworkApproach, FrameworkClassification, FrameworkOrm, Language,
+        TechEmpowerAggregation, TechEmpowerFilter, TechEmpowerScoring,
     };
 
     #[test]
@@ -358,7 +606,10 @@ mod tests {
                 { tab: "data-r23" },
             ];
         "#;
-        let urls = extract_round_results_urls(bundle);
+        let urls: Vec<String> = extract_rounds(bundle)
+            .into_iter()
+            .map(|round| round_results_url(TFB_BENCHMARKS_URL, round))
+            .collect();
         assert_eq!(
             urls,
             vec![
@@ -370,20 +621,9 @@ mod tests {
     }
 
     #[test]
-    fn dedups_urls_without_reordering() {
-        let urls = vec![
-            "https://www.techempower.com/benchmarks/results/round23/ph.json".to_string(),
-            "https://www.techempower.com/benchmarks/results/round22/ph.json".to_string(),
-            "https://www.techempower.com/benchmarks/results/round23/ph.json".to_string(),
-        ];
-        let unique = dedup_urls_preserve_order(urls);
-        assert_eq!(
-            unique,
-            vec![
-                "https://www.techempower.com/benchmarks/results/round23/ph.json",
-                "https://www.techempower.com/benchmarks/results/round22/ph.json",
-            ]
-        );
+    fn dedups_rounds_without_reordering() {
+        let unique = dedup_preserve_order(vec![23, 22, 23, 21, 22]);
+        assert_eq!(unique, vec![23, 22, 21]);
     }
 
     #[test]
@@ -409,11 +649,251 @@ mod tests {
             }}"#
         );
 
-        let results: TechEmpowerResults<'_> =
-            serde_json::from_str(&json).expect("fixture should parse");
-        let scores = compute_language_scores(&results).expect("scores should compute");
+        let results: RoundResults<'_> = serde_json::from_str(&json).expect("fixture should parse");
+        let scores = compute_language_results(
+            &results,
+            &AliasRegistry::builtin(),
+            &TechEmpowerScoring::default(),
+        )
+        .expect("scores should compute")
+        .scores();
+
+        assert_eq!(
+            scores.get(&Language::intern("Rust")),
+            Some(&TECHEMPOWER_MAX_SCORE)
+        );
+        assert_eq!(
+            scores.get(&Language::intern("Java")),
+            Some(&(TECHEMPOWER_MAX_SCORE / 2.0))
+        );
+    }
+
+    #[test]
+    fn breaks_down_frameworks_and_aggregates_composites() {
+        let run =
+            |requests| format!(r#"{{"totalRequests":{requests},"startTime":1000,"endTime":2000}}"#);
+        let tests = ["json", "plaintext", "db", "query", "fortune", "update"]
+            .map(|name| {
+                let partial = if name == "json" {
+                    format!(r#","partial":[{}]"#, run(500))
+                } else {
+                    String::new()
+                };
+                format!(
+                    r#""{name}":{{"platform":[{}],"full":[{}],"micro":[{}]{partial}}}"#,
+                    run(4000),
+                    run(2000),
+                    run(1000),
+                )
+            })
+            .join(",");
+        let json = format!(
+            r#"{{
+                "rawData":{{{tests}}},
+                "testMetadata":[
+                    {{"framework":"platform","language":"rust"}},
+                    {{"framework":"full","language":"rust"}},
+                    {{"framework":"micro","language":"rust"}},
+                    {{"framework":"partial","language":"rust"}}
+                ]
+            }}"#
+        );
+        let results: RoundResults<'_> = serde_json::from_str(&json).expect("fixture should parse");
+        let scoring = TechEmpowerScoring {
+            aggregation: TechEmpowerAggregation::Median,
+            top_k: 2,
+            ..TechEmpowerScoring::default()
+        };
+
+        let results = compute_language_results(&results, &AliasRegistry::builtin(), &scoring)
+            .expect("scores should compute");
+        let rust = results
+            .get(Language::intern("Rust"))
+            .expect("Rust should be scored");
+
+        assert_eq!(rust.winner, "platform");
+        let frameworks: Vec<_> = rust
+            .frameworks
+            .iter()
+            .map(|framework| framework.framework.as_str())
+            .collect();
+        assert_eq!(frameworks, ["platform", "full", "micro", "partial"]);
+        assert_eq!(rust.frameworks[1].json, Some(2000.0));
+        assert_eq!(rust.frameworks[3].composite, None);
+        assert!((rust.best - TECHEMPOWER_MAX_SCORE).abs() < 1e-12);
+        assert!((rust.top_k_mean / TECHEMPOWER_MAX_SCORE - 0.75).abs() < 1e-12);
+        assert!((rust.median / TECHEMPOWER_MAX_SCORE - 0.5).abs() < 1e-12);
+        assert!((rust.score - rust.median).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn filters_frameworks_by_metadata_and_records_filter() {
+        let run =
+            |requests| format!(r#"{{"totalRequests":{requests},"startTime":1000,"endTime":2000}}"#);
+        let tests = ["json", "plaintext", "db", "query", "fortune", "update"]
+            .map(|name| {
+                format!(
+                    r#""{name}":{{"raw-rust":[{}],"diesel":[{}],"spring":[{}]}}"#,
+                    run(4000),
+                    run(2000),
+                    run(1000),
+                )
+            })
+            .join(",");
+        let json = format!(
+            r#"{{
+                "rawData":{{{tests}}},
+                "testMetadata":[
+                    {{"framework":"raw-rust","language":"rust","classification":"Platform",
+                      "approach":"Stripped","orm":"Raw","database":"Postgres","os":"Linux"}},
+                    {{"framework":"diesel","language":"rust","classification":"Fullstack",
+                      "approach":"Realistic","orm":"Full","database":"Postgres","os":"Linux"}},
+                    {{"framework":"spring","language":"java","classification":"Fullstack",
+                      "approach":"Realistic","orm":"Full","database":"MySQL","os":"Linux"}}
+                ]
+            }}"#
+        );
+        let results: RoundResults<'_> = serde_json::from_str(&json).expect("fixture should parse");
+        let filter = TechEmpowerFilter {
+            classifications: vec![FrameworkClassification::Fullstack],
+            approaches: vec![FrameworkApproach::Realistic],
+            ..TechEmpowerFilter::default()
+        };
+        let scoring = TechEmpowerScoring {
+            filter: filter.clone(),
+            ..TechEmpowerScoring::default()
+        };
+
+        let filtered = compute_language_results(&results, &AliasRegistry::builtin(), &scoring)
+            .expect("scores should compute");
+
+        assert_eq!(filtered.filter(), &filter);
+        let rust = filtered
+            .get(Language::intern("Rust"))
+            .expect("Rust should be scored");
+        assert_eq!(rust.winner, "diesel");
+        assert_eq!(rust.frameworks.len(), 1);
+        assert_eq!(rust.frameworks[0].orm, Some(FrameworkOrm::Full));
+        assert_eq!(rust.frameworks[0].database.as_deref(), Some("Postgres"));
+        assert!((rust.score / TECHEMPOWER_MAX_SCORE - 0.5).abs() < 1e-12);
+
+        let postgres = TechEmpowerScoring {
+            filter: TechEmpowerFilter {
+                databases: vec!["postgres".to_owned()],
+                operating_systems: vec!["linux".to_owned()],
+                ..TechEmpowerFilter::default()
+            },
+            ..TechEmpowerScoring::default()
+        };
+        let filtered = compute_language_results(&results, &AliasRegistry::builtin(), &postgres)
+            .expect("scores should compute");
+        assert!(filtered.get(Language::intern("Java")).is_none());
+        assert_eq!(filtered.len(), 1);
+
+        let nothing = TechEmpowerScoring {
+            filter: TechEmpowerFilter {
+                orms: vec![FrameworkOrm::Micro],
+                ..TechEmpowerFilter::default()
+            },
+            ..TechEmpowerScoring::default()
+        };
+        let error = compute_language_results(&results, &AliasRegistry::builtin(), &nothing)
+            .expect_err("no framework should match");
+        assert_eq!(
+            error.to_string(),
+            "no TechEmpower frameworks match the filter (orm=micro)"
+        );
+    }
+
+    #[test]
+    fn filters_implementations_of_one_framework_separately() {
+        let run =
+            |requests| format!(r#"{{"totalRequests":{requests},"startTime":1000,"endTime":2000}}"#);
+        let tests = ["json", "plaintext", "db", "query", "fortune", "update"]
+            .map(|name| {
+                format!(
+                    r#""{name}":{{"actix-pg":[{}],"actix-diesel":[{}]}}"#,
+                    run(4000),
+                    run(2000),
+                )
+            })
+            .join(",");
+        let json = format!(
+            r#"{{
+                "rawData":{{{tests}}},
+                "testMetadata":[
+                    {{"name":"actix-pg","framework":"actix","language":"rust","orm":"Raw"}},
+                    {{"name":"actix-diesel","framework":"actix","language":"rust","orm":"Full"}}
+                ]
+            }}"#
+        );
+        let results: RoundResults<'_> = serde_json::from_str(&json).expect("fixture should parse");
+        let scoring = TechEmpowerScoring {
+            filter: TechEmpowerFilter {
+                orms: vec![FrameworkOrm::Full],
+                ..TechEmpowerFilter::default()
+            },
+            ..TechEmpowerScoring::default()
+        };
+
+        let filtered = compute_language_results(&results, &AliasRegistry::builtin(), &scoring)
+            .expect("scores should compute");
+        let rust = filtered
+            .get(Language::intern("Rust"))
+            .expect("Rust should be scored");
+        assert_eq!(rust.winner, "actix-diesel");
+        assert_eq!(rust.frameworks.len(), 1);
+        assert_eq!(rust.frameworks[0].json, Some(2000.0));
+        assert_eq!(rust.frameworks[0].orm, Some(FrameworkOrm::Full));
+    }
 
-        assert_eq!(scores.get("Rust"), Some(&TECHEMPOWER_MAX_SCORE));
-        assert_eq!(scores.get("Java"), Some(&(TECHEMPOWER_MAX_SCORE / 2.0)));
+    #[test]
+    fn compares_language_scores_between_rounds() {
+        let round = |rust_rps: u32, java_rps: Option<u32>| {
+            let run = |requests| {
+                format!(r#"{{"totalRequests":{requests},"startTime":1000,"endTime":2000}}"#)
+            };
+            let tests = ["json", "plaintext", "db", "query", "fortune", "update"]
+                .map(|name| {
+                    let java = java_rps
+                        .map_or_else(String::new, |rps| format!(r#","spring":[{}]"#, run(rps)));
+                    format!(
+                        r#""{name}":{{"top":[{}],"actix":[{}]{java}}}"#,
+                        run(4000),
+                        run(rust_rps)
+                    )
+                })
+                .join(",");
+            let json = format!(
+                r#"{{
+                    "rawData":{{{tests}}},
+                    "testMetadata":[
+                        {{"framework":"top","language":"c"}},
+                        {{"framework":"actix","language":"rust"}},
+                        {{"framework":"spring","language":"java"}}
+                    ]
+                }}"#
+            );
+            let results: RoundResults<'_> =
+                serde_json::from_str(&json).expect("fixture should parse");
+            compute_language_results(
+                &results,
+                &AliasRegistry::builtin(),
+                &TechEmpowerScoring::default(),
+            )
+            .expect("scores should compute")
+        };
+        let previous = round(1000, Some(2000)).with_round(21);
+        let current = round(3000, None).with_round(22);
+
+        assert_eq!(current.round(), Some(22));
+        let changes = current.compare(&previous);
+        let languages: Vec<_> = changes.iter().map(|change| change.lang.name()).collect();
+        assert_eq!(languages, ["Rust", "C", "Java"]);
+        let rust_delta = changes[0].delta().expect("Rust is scored in both rounds");
+        assert!((rust_delta / TECHEMPOWER_MAX_SCORE - 0.5).abs() < 1e-12);
+        assert_eq!(changes[1].delta(), Some(0.0));
+        assert_eq!(changes[2].current, None);
+        assert!(changes[2].previous.is_some());
     }
 }
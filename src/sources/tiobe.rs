@@ -1,17 +1,28 @@
-use crate::RankingEntry;
+use crate::aliases::builtin as builtin_aliases;
+use crate::{
+    AliasRegistry, Language, RankingDataset, RankingEntry, RankingSource, SourceError, TiobeAward,
+    TiobeHistory, Transport,
+};
 use anyhow::{Context, Result};
-use reqwest::Client;
-use scraper::{Html, Selector};
+use rustc_hash::FxHashMap;
+use scraper::{ElementRef, Html, Selector};
 use std::sync::OnceLock;
 
 use super::{
-    RawEntry, aggregate_entries, extract_cell_text, fetch_text_with_retry, parse_percent, parse_u32,
+    HttpContext, RawEntry, aggregate_entries, extract_cell_text, fetch_text_with_retry,
+    parse_percent, parse_u32,
 };
 
 const TIOBE_URL: &str = "https://www.tiobe.com/tiobe-index/";
+/// Места 51–100 TIOBE перечисляет по алфавиту, поэтому все они делят первое
+/// место диапазона.
+const TAIL_RANK: u32 = 51;
+/// Часть заголовка, после которого идёт список мест 51–100.
+const TAIL_HEADING: &str = "Next 50";
 
 struct MainRow<'a> {
     rank: &'a str,
+    previous_rank: &'a str,
     lang: &'a str,
     share: &'a str,
     trend: &'a str,
@@ -19,15 +30,16 @@ struct MainRow<'a> {
 
 impl<'a> MainRow<'a> {
     fn parse(cells: &'a [String]) -> Option<Self> {
+        // Третья колонка — стрелка изменения места; она выводится из двух мест.
         match cells {
-            [rank, _, _, _, lang, share, trend, ..] | [rank, _, _, lang, share, trend, ..] => {
-                Some(Self {
-                    rank,
-                    lang,
-                    share,
-                    trend,
-                })
-            }
+            [rank, previous_rank, _, _, lang, share, trend, ..]
+            | [rank, previous_rank, _, lang, share, trend, ..] => Some(Self {
+                rank,
+                previous_rank,
+                lang,
+                share,
+                trend,
+            }),
             _ => None,
         }
     }
@@ -36,9 +48,10 @@ impl<'a> MainRow<'a> {
         RawEntry::parse(
             self.lang,
             parse_u32(self.rank),
-            parse_percent(self.share).unwrap_or(0.0),
+            parse_percent(self.share),
             parse_percent(self.trend),
         )
+        .map(|entry| entry.with_previous_rank(parse_u32(self.previous_rank)))
     }
 }
 
@@ -51,7 +64,12 @@ struct OtherRow<'a> {
 impl<'a> OtherRow<'a> {
     fn parse(cells: &'a [String]) -> Option<Self> {
         match cells {
-            [rank, lang, share, ..] => Some(Self { rank, lang, share }),
+            // Строки без доли становятся записями только с местом.
+            [rank, lang, rest @ ..] => Some(Self {
+                rank,
+                lang,
+                share: rest.first().map_or("", String::as_str),
+            }),
             _ => None,
         }
     }
@@ -60,7 +78,7 @@ impl<'a> OtherRow<'a> {
         RawEntry::parse(
             self.lang,
             parse_u32(self.rank),
-            parse_percent(self.share).unwrap_or(0.0),
+            parse_percent(self.share),
             None,
         )
     }
@@ -68,26 +86,68 @@ impl<'a> OtherRow<'a> {
 
 /// Загружает и разбирает актуальный индекс TIOBE.
 ///
+/// Записи топ-20 содержат прошлогодние места ([`RankingEntry::previous_rank`]).
+///
 /// # Errors
 ///
 /// Возвращает ошибку, если страницу не удалось получить по HTTP.
-pub async fn fetch_tiobe(client: &Client) -> Result<Vec<RankingEntry>> {
-    let body = fetch_text_with_retry(client, TIOBE_URL)
+pub async fn fetch_tiobe(transport: &dyn Transport) -> Result<Vec<RankingEntry>> {
+    fetch_tiobe_with(HttpContext::with_defaults(transport), builtin_aliases())
+        .await
+        .map(|dataset| dataset.into_parts().1)
+}
+
+/// Загружает таблицу долгосрочной истории TIOBE и «Зал славы».
+///
+/// # Errors
+///
+/// Возвращает ошибку, если страницу не удалось получить по HTTP или на ней
+/// нет таблицы долгосрочной истории.
+pub async fn fetch_tiobe_history(transport: &dyn Transport) -> Result<TiobeHistory> {
+    fetch_tiobe_history_with(HttpContext::with_defaults(transport), builtin_aliases()).await
+}
+
+pub async fn fetch_tiobe_with(
+    http: HttpContext<'_>,
+    aliases: &AliasRegistry,
+) -> Result<RankingDataset> {
+    let body = fetch_tiobe_page(http).await?;
+    parse_tiobe_html(body.as_str(), aliases)
+}
+
+pub async fn fetch_tiobe_history_with(
+    http: HttpContext<'_>,
+    aliases: &AliasRegistry,
+) -> Result<TiobeHistory> {
+    let body = fetch_tiobe_page(http).await?;
+    parse_tiobe_history(body.as_str(), aliases)
+}
+
+async fn fetch_tiobe_page(http: HttpContext<'_>) -> Result<String> {
+    fetch_text_with_retry(http, http.url(RankingSource::Tiobe, TIOBE_URL))
         .await
-        .context("failed to download TIOBE index")?;
-    Ok(parse_tiobe_html(body.as_str()))
+        .context("failed to download TIOBE index")
 }
 
-fn parse_tiobe_html(body: &str) -> Vec<RankingEntry> {
+fn parse_tiobe_html(body: &str, aliases: &AliasRegistry) -> Result<RankingDataset> {
     let document = Html::parse_document(body);
     let mut entries = Vec::new();
 
-    if let Some(table) = document.select(main_table_selector()).next() {
-        for row in table.select(row_selector()).skip(1) {
-            let cells: Vec<String> = row.select(cell_selector()).map(extract_cell_text).collect();
-            if let Some(entry) = MainRow::parse(&cells).and_then(MainRow::into_entry) {
-                entries.push(entry);
-            }
+    let table =
+        document
+            .select(main_table_selector())
+            .next()
+            .ok_or(SourceError::MarkupNotFound {
+                document: "TIOBE index page",
+                element: "top 20 table",
+            })?;
+    for row in table.select(row_selector()).skip(1) {
+        let cells: Vec<String> = row.select(cell_selector()).map(extract_cell_text).collect();
+        let Some(row) = MainRow::parse(&cells) else {
+            continue;
+        };
+        if let Some(entry) = row.into_entry() {
+            entries.push(entry);
         }
     }
 
@@ -100,7 +160,117 @@ fn parse_tiobe_html(body: &str) -> Vec<RankingEntry> {
         }
     }
 
-    aggregate_entries(entries)
+    entries.extend(
+        tail_languages(&document)
+            .iter()
+            .filter_map(|lang| RawEntry::parse(lang, Some(TAIL_RANK), None, None)),
+    );
+
+    Ok(RankingDataset::new(
+        RankingSource::Tiobe,
+        aggregate_entries(entries, aliases),
+    ))
+}
+
+/// Возвращает языки мест 51–100: первый список после заголовка «Next 50».
+fn tail_languages(document: &Html) -> Vec<String> {
+    let Some(heading) = document
+        .select(heading_selector())
+        .find(|heading| heading.text().collect::<String>().contains(TAIL_HEADING))
+    else {
+        return Vec::new();
+    };
+    let Some(list) = heading
+        .next_siblings()
+        .filter_map(ElementRef::wrap)
+        .take_while(|element| !heading_selector().matches(element))
+        .find(|element| matches!(element.value().name(), "ul" | "ol"))
+    else {
+        return Vec::new();
+    };
+    list.select(list_item_selector())
+        .flat_map(|item| {
+            extract_cell_text(item)
+                .split(',')
+                .map(|name| name.trim().trim_end_matches('.').to_owned())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn parse_tiobe_history(body: &str, aliases: &AliasRegistry) -> Result<TiobeHistory> {
+    let document = Html::parse_document(body);
+    let table =
+        document
+            .select(history_table_selector())
+            .next()
+            .ok_or(SourceError::MarkupNotFound {
+                document: "TIOBE index page",
+                element: "very long term history table",
+            })?;
+    let mut rows = table.select(row_selector());
+    let header = rows.next().ok_or(SourceError::MarkupNotFound {
+        document: "TIOBE index page",
+        element: "very long term history header",
+    })?;
+    let page_years: Vec<Option<u16>> = header
+        .select(header_cell_selector())
+        .skip(1)
+        .map(|cell| parse_year(&extract_cell_text(cell)))
+        .collect();
+    // На странице годы идут от новых к старым; история хранит их по возрастанию.
+    let mut order: Vec<(usize, u16)> = page_years
+        .iter()
+        .enumerate()
+        .filter_map(|(column, year)| year.map(|year| (column, year)))
+        .collect();
+    order.sort_unstable_by_key(|&(_, year)| year);
+    let years: Vec<u16> = order.iter().map(|&(_, year)| year).collect();
+
+    let mut positions: FxHashMap<Language, Vec<Option<u32>>> = FxHashMap::default();
+    for row in rows {
+        let cells: Vec<String> = row.select(cell_selector()).map(extract_cell_text).collect();
+        let Some((name, values)) = cells.split_first() else {
+            continue;
+        };
+        let Some(lang) = aliases.canonicalize(name) else {
+            continue;
+        };
+        let series = positions
+            .entry(lang)
+            .or_insert_with(|| vec![None; years.len()]);
+        for (slot, &(column, _)) in series.iter_mut().zip(&order) {
+            if let Some(position) = values.get(column).and_then(|value| parse_u32(value)) {
+                *slot = Some(slot.map_or(position, |existing| existing.min(position)));
+            }
+        }
+    }
+
+    let mut hall_of_fame: Vec<TiobeAward> = document
+        .select(hall_of_fame_selector())
+        .next()
+        .into_iter()
+        .flat_map(|table| table.select(row_selector()))
+        .filter_map(|row| parse_award(row, aliases))
+        .collect();
+    hall_of_fame.sort_unstable_by_key(|award| award.year);
+
+    Ok(TiobeHistory::new(years, positions, hall_of_fame))
+}
+
+fn parse_award(row: ElementRef<'_>, aliases: &AliasRegistry) -> Option<TiobeAward> {
+    let cells: Vec<String> = row.select(cell_selector()).map(extract_cell_text).collect();
+    match cells.as_slice() {
+        [year, lang, ..] => Some(TiobeAward {
+            year: parse_year(year)?,
+            lang: aliases.canonicalize(lang)?,
+        }),
+        _ => None,
+    }
+}
+
+fn parse_year(value: &str) -> Option<u16> {
+    parse_u32(value).and_then(|year| u16::try_from(year).ok())
 }
 
 fn main_table_selector() -> &'static Selector {
@@ -118,6 +288,36 @@ fn other_table_selector() -> &'static Selector {
     })
 }
 
+fn heading_selector() -> &'static Selector {
+    static SELECTOR: OnceLock<Selector> = OnceLock::new();
+    SELECTOR
+        .get_or_init(|| Selector::parse("h1, h2, h3, h4").expect("TIOBE heading selector is valid"))
+}
+
+fn list_item_selector() -> &'static Selector {
+    static SELECTOR: OnceLock<Selector> = OnceLock::new();
+    SELECTOR.get_or_init(|| Selector::parse("li").expect("TIOBE list item selector is valid"))
+}
+
+fn history_table_selector() -> &'static Selector {
+    static SELECTOR: OnceLock<Selector> = OnceLock::new();
+    SELECTOR.get_or_init(|| {
+        Selector::parse("table#VLTH").expect("TIOBE history table selector is valid")
+    })
+}
+
+fn hall_of_fame_selector() -> &'static Selector {
+    static SELECTOR: OnceLock<Selector> = OnceLock::new();
+    SELECTOR.get_or_init(|| {
+        Selector::parse("table#PLHoF").expect("TIOBE hall of fame selector is valid")
+    })
+}
+
+fn header_cell_selector() -> &'static Selector {
+    static SELECTOR: OnceLock<Selector> = OnceLock::new();
+    SELECTOR.get_or_init(|| Selector::parse("th").expect("TIOBE header cell selector is valid"))
+}
+
 fn row_selector() -> &'static Selector {
     static SELECTOR: OnceLock<Selector> = OnceLock::new();
     SELECTOR.get_or_init(|| Selector::parse("tr").expect("TIOBE row selector is valid"))
@@ -130,7 +330,8 @@ fn cell_selector() -> &'static Selector {
 
 #[cfg(test)]
 mod tests {
-    use super::parse_tiobe_html;
+    use super::{parse_tiobe_history, parse_tiobe_html};
+    use crate::{AliasRegistry, Language};
 
     #[test]
     fn parses_main_and_other_tables() {
@@ -152,9 +353,111 @@ mod tests {
             </html>
         "#;
 
-        let entries = parse_tiobe_html(html);
+        let dataset =
+            parse_tiobe_html(html, &AliasRegistry::builtin()).expect("TIOBE fixture should parse");
+        let entries = dataset.entries();
         assert_eq!(entries.len(), 2);
         assert_eq!(entries[0].lang, "Go");
         assert_eq!(entries[1].lang, "Rust");
     }
+
+    #[test]
+    fn parses_rank_only_rows_and_next_fifty_list() {
+        let html = r#"
+            <html>
+              <table class="table table-striped table-top20">
+                <tr><th>header</th></tr>
+                <tr>
+                  <td>1</td><td>x</td><td>x</td><td>x</td>
+                  <td>Rust</td><td>10.2%</td><td>+0.6%</td>
+                </tr>
+              </table>
+              <table id="otherPL">
+                <tr><th>header</th></tr>
+                <tr><td>24</td><td>Go</td><td>3.1%</td></tr>
+                <tr><td>50</td><td>Kotlin</td></tr>
+              </table>
+              <h2>The Next 50 Programming Languages</h2>
+              <p>The following list of languages denotes #51 to #100.</p>
+              <ul><li>ABAP, Ada, Zig.</li></ul>
+              <h2>Other</h2>
+              <ul><li>Haskell</li></ul>
+            </html>
+        "#;
+
+        let dataset =
+            parse_tiobe_html(html, &AliasRegistry::builtin()).expect("TIOBE fixture should parse");
+        let find = |name: &str| dataset.iter().find(|entry| entry.lang == name);
+        assert_eq!(dataset.len(), 6);
+        assert_eq!(find("Go").and_then(|entry| entry.share), Some(3.1));
+        let kotlin = find("Kotlin").expect("row without a share is kept");
+        assert_eq!((kotlin.rank, kotlin.share), (Some(50), None));
+        for name in ["Abap", "Ada", "Zig"] {
+            let entry = find(name).expect("tail language is listed");
+            assert_eq!((entry.rank, entry.share), (Some(51), None));
+        }
+        assert!(find("Haskell").is_none());
+    }
+
+    #[test]
+    fn parses_previous_ranks_and_long_term_history() {
+        let html = r#"
+            <html>
+              <table class="table table-striped table-top20">
+                <tr><th>Jan 2025</th><th>Jan 2024</th><th>Change</th><th>Language</th></tr>
+                <tr>
+                  <td>1</td><td>3</td><td><img alt="up"></td><td><img alt=""></td>
+                  <td>Rust</td><td>10.2%</td><td>+0.6%</td>
+                </tr>
+                <tr>
+                  <td>2</td><td>1</td><td><img alt="down"></td>
+                  <td>Golang</td><td>9.1%</td><td>-1.2%</td>
+                </tr>
+              </table>
+              <table id="VLTH">
+                <tr><th>Programming Language</th><th>2025</th><th>2015</th><th>2005</th></tr>
+                <tr><td>Rust</td><td>1</td><td>40</td><td>-</td></tr>
+                <tr><td>Go</td><td>2</td><td>12</td><td>-</td></tr>
+                <tr><td>C</td><td>3</td><td>2</td><td>1</td></tr>
+              </table>
+              <table id="PLHoF">
+                <tr><th>Year</th><th>Winner</th></tr>
+                <tr><td>2024</td><td>Rust</td></tr>
+                <tr><td>2016</td><td>Golang</td></tr>
+              </table>
+            </html>
+        "#;
+        let aliases = AliasRegistry::builtin();
+
+        let dataset = parse_tiobe_html(html, &aliases).expect("TIOBE fixture should parse");
+        let previous_rank = |name: &str| {
+            dataset
+                .iter()
+                .find(|entry| entry.lang == name)
+                .and_then(|entry| entry.previous_rank)
+        };
+        assert_eq!(previous_rank("Rust"), Some(3));
+        assert_eq!(previous_rank("Go"), Some(1));
+
+        let history = parse_tiobe_history(html, &aliases).expect("history should parse");
+        assert_eq!(history.years(), [2005, 2015, 2025]);
+        assert_eq!(
+            history.get(Language::intern("Rust")),
+            Some([None, Some(40), Some(1)].as_slice())
+        );
+        assert_eq!(
+            history.get(Language::intern("C")),
+            Some([Some(1), Some(2), Some(3)].as_slice())
+        );
+        let winners: Vec<(u16, &str)> = history
+            .hall_of_fame()
+            .iter()
+            .map(|award| (award.year, award.lang.name()))
+            .collect();
+        assert_eq!(winners, [(2016, "Go"), (2024, "Rust")]);
+
+        let error = parse_tiobe_history("<html></html>", &aliases)
+            .expect_err("a page without the history table is a format change");
+        assert!(error.to_string().contains("very long term history table"));
+    }
 }
@@ -1,34 +1,48 @@
 use crate::formatting::{
-    format_optional_float, format_optional_rank, format_perf_score, format_trend,
+    format_error_chain, format_optional_float, format_optional_rank, format_perf_score,
+    format_source_count, format_trend,
 };
-use crate::schulze::SchulzeRecord;
 use chrono::{DateTime, Local};
-use colored::Colorize;
+use colored::{ColoredString, Colorize};
+use langrank::{BenchmarkResults, FetchError, NameReport, SchulzeRecord, TechEmpowerFilter};
 use std::path::Path;
 
+const MAX_NAME_ISSUES: usize = 10;
+const MAX_BENCHMARK_VARIANTS: usize = 10;
+
 pub struct SummaryPaths<'a> {
     pub(crate) benchmarks: Option<&'a Path>,
     pub(crate) rankings: Option<&'a Path>,
     pub(crate) schulze: Option<&'a Path>,
     pub(crate) html: Option<&'a Path>,
+    pub(crate) name_report: Option<&'a Path>,
+    pub(crate) snapshot: Option<&'a Path>,
 }
 
 pub struct SummaryContext<'a> {
-    pub(crate) tiobe_count: usize,
-    pub(crate) pypl_count: usize,
-    pub(crate) languish_count: usize,
-    pub(crate) benchmark_lang_count: usize,
-    pub(crate) techempower_lang_count: usize,
+    pub(crate) tiobe_count: Option<usize>,
+    pub(crate) pypl_count: Option<usize>,
+    pub(crate) languish_count: Option<usize>,
+    pub(crate) benchmark_lang_count: Option<usize>,
+    pub(crate) techempower_lang_count: Option<usize>,
+    pub(crate) techempower_filter: &'a TechEmpowerFilter,
+    pub(crate) techempower_round: Option<u16>,
     pub(crate) run_started_at: &'a DateTime<Local>,
     pub(crate) paths: SummaryPaths<'a>,
+    pub(crate) name_report: &'a NameReport,
+    pub(crate) benchmark_results: &'a BenchmarkResults,
     pub(crate) schulze_records: &'a [SchulzeRecord],
+    pub(crate) failures: &'a [FetchError],
     pub(crate) full_output: bool,
 }
 
 pub fn print_summary(context: &SummaryContext<'_>) {
     println!();
     print_summary_header(context);
+    print_missing_sources(context.failures);
     print_summary_paths(&context.paths);
+    print_name_report(context.name_report, context.full_output);
+    print_benchmark_variants(context.benchmark_results, context.full_output);
     println!();
     println!("{}", "Schulze Ranking".bold().bright_magenta());
     let table_width = print_schulze_table(context.schulze_records, context.full_output);
@@ -57,12 +71,53 @@ fn print_summary_header(context: &SummaryContext<'_>) {
     println!(
         "{} {} | {} | {} | {} | {}",
         "Sources".bright_yellow().bold(),
-        format!("TIOBE: {}", context.tiobe_count).bright_white(),
-        format!("PYPL: {}", context.pypl_count).bright_white(),
-        format!("Languish: {}", context.languish_count).bright_white(),
-        format!("Benchmarks: {}", context.benchmark_lang_count).bright_white(),
-        format!("TechEmpower: {}", context.techempower_lang_count).bright_white()
+        source_count_label("TIOBE", context.tiobe_count),
+        source_count_label("PYPL", context.pypl_count),
+        source_count_label("Languish", context.languish_count),
+        source_count_label("Benchmarks", context.benchmark_lang_count),
+        source_count_label("TechEmpower", context.techempower_lang_count)
+    );
+    if let Some(round) = context.techempower_round {
+        println!(
+            "{} {}",
+            "TechEmpower round".bright_yellow().bold(),
+            round.to_string().bright_white()
+        );
+    }
+    if !context.techempower_filter.is_empty() {
+        println!(
+            "{} {}",
+            "TechEmpower filter".bright_yellow().bold(),
+            context.techempower_filter.to_string().bright_white()
+        );
+    }
+}
+
+fn source_count_label(label: &str, count: Option<usize>) -> ColoredString {
+    let text = format!("{label}: {}", format_source_count(count));
+    if count.is_some() {
+        text.bright_white()
+    } else {
+        text.bright_red().bold()
+    }
+}
+
+fn print_missing_sources(failures: &[FetchError]) {
+    if failures.is_empty() {
+        return;
+    }
+    println!(
+        "{} {}",
+        "Degraded".bright_red().bold(),
+        format!("ranking computed without {} source(s):", failures.len()).bright_white()
     );
+    for failure in failures {
+        println!(
+            "  {} {}",
+            "-".bright_red(),
+            format_error_chain(failure).bright_black()
+        );
+    }
 }
 
 fn print_summary_paths(paths: &SummaryPaths<'_>) {
@@ -82,6 +137,118 @@ fn print_summary_paths(paths: &SummaryPaths<'_>) {
         "not saved (use --save-schulze)",
     );
     print_path_line("HTML Report", paths.html, "not saved (use --save-html)");
+    print_path_line(
+        "Name Report",
+        paths.name_report,
+        "not saved (use --save-name-report)",
+    );
+    print_path_line("Snapshot", paths.snapshot, "not recorded (use --record)");
+}
+
+fn print_name_report(report: &NameReport, full_output: bool) {
+    if report.is_empty() {
+        return;
+    }
+    let single_source = report
+        .issues()
+        .iter()
+        .filter(|issue| issue.single_source)
+        .count();
+    let unmapped = report
+        .issues()
+        .iter()
+        .filter(|issue| issue.unmapped)
+        .count();
+    println!(
+        "{} {}",
+        "Names".bright_yellow().bold(),
+        format!("{single_source} in a single source, {unmapped} without an alias entry")
+            .bright_white()
+    );
+    let duplicates: Vec<_> = report.likely_duplicates().collect();
+    let shown = if full_output {
+        duplicates.len()
+    } else {
+        duplicates.len().min(MAX_NAME_ISSUES)
+    };
+    for issue in &duplicates[..shown] {
+        let sources: Vec<_> = issue.sources.iter().map(|source| source.as_str()).collect();
+        let suggestions: Vec<_> = issue
+            .suggestions
+            .iter()
+            .map(|suggestion| suggestion.name.name())
+            .collect();
+        println!(
+            "  {} {} {} {}",
+            "-".bright_yellow(),
+            issue.name.name().bright_white(),
+            format!("({})", sources.join(", ")).bright_black(),
+            format!("looks like {}", suggestions.join(", ")).bright_black()
+        );
+    }
+    if shown < duplicates.len() {
+        println!(
+            "  {}",
+            format!(
+                "... {} more likely duplicates (use --full-output or --save-name-report)",
+                duplicates.len() - shown
+            )
+            .bright_black()
+        );
+    }
+}
+
+fn print_benchmark_variants(results: &BenchmarkResults, full_output: bool) {
+    let mut languages: Vec<_> = results
+        .languages()
+        .iter()
+        .filter(|(_, benchmark)| benchmark.variants.len() > 1)
+        .collect();
+    if languages.is_empty() {
+        return;
+    }
+    languages.sort_by_key(|(lang, _)| **lang);
+    println!(
+        "{} {}",
+        "Variants".bright_yellow().bold(),
+        format!(
+            "{} Benchmarks Game languages with several implementations",
+            languages.len()
+        )
+        .bright_white()
+    );
+    let shown = if full_output {
+        languages.len()
+    } else {
+        languages.len().min(MAX_BENCHMARK_VARIANTS)
+    };
+    for (lang, benchmark) in &languages[..shown] {
+        let best = benchmark
+            .best_variant()
+            .map_or_else(|| lang.name(), |variant| variant.name());
+        println!(
+            "  {} {} {} {}",
+            "-".bright_yellow(),
+            lang.name().bright_white(),
+            format!("best {best} {:.2}", benchmark.score).bright_black(),
+            format!(
+                "spread {} across {}",
+                format_optional_float(benchmark.variant_spread()),
+                benchmark.variants.len()
+            )
+            .bright_black()
+        );
+    }
+    if shown < languages.len() {
+        println!(
+            "  {}",
+            format!(
+                "... {} more languages (use --full-output)",
+                languages.len() - shown
+            )
+            .bright_black()
+        );
+    }
 }
 
 fn print_path_line(label: &str, path: Option<&Path>, hint: &str) {
@@ -138,11 +305,11 @@ fn print_full_schulze_table(records: &[SchulzeRecord]) -> usize {
         let tiobe_rank = format_optional_rank(record.tiobe_rank);
         let pypl_rank = format_optional_rank(record.pypl_rank);
         let languish_rank = format_optional_rank(record.languish_rank);
-        let tiobe_share = format!("{:.2}", record.tiobe_share);
-        let pypl_share = format!("{:.2}", record.pypl_share);
+        let tiobe_share = format_optional_float(record.tiobe_share);
+        let pypl_share = format_optional_float(record.pypl_share);
         let tiobe_trend = format_trend(record.tiobe_trend);
         let pypl_trend = format_trend(record.pypl_trend);
-        let languish_share = format!("{:.2}", record.languish_share);
+        let languish_share = format_optional_float(record.languish_share);
         let languish_trend = format_trend(record.languish_trend);
         let bg = format_optional_float(record.benchmark_score);
         let te = format_optional_float(record.techempower_score);
@@ -191,12 +358,12 @@ fn print_compact_schulze_table(records: &[SchulzeRecord]) -> usize {
             record.techempower_score,
         );
         let line = format!(
-            "{:>3} | {:<13} | {:>6.2} | {:>5.2} | {:>5.2} | {:>4} | {:>4} | {:>4} | {:>4}",
+            "{:>3} | {:<13} | {:>6} | {:>5} | {:>5} | {:>4} | {:>4} | {:>4} | {:>4}",
             record.position,
             record.lang,
-            record.tiobe_share,
-            record.pypl_share,
-            record.languish_share,
+            format_optional_float(record.tiobe_share),
+            format_optional_float(record.pypl_share),
+            format_optional_float(record.languish_share),
             bg,
             te,
             perf,
@@ -0,0 +1,77 @@
+use crate::formatting::{format_optional_float, format_trend};
+use anyhow::{Result, anyhow};
+use colored::Colorize;
+use langrank::{Fetcher, TechEmpowerResults};
+
+pub async fn print_techempower_rounds(fetcher: &Fetcher, compare: &[u16]) -> Result<()> {
+    if compare.is_empty() {
+        let rounds: Vec<String> = fetcher
+            .list_techempower_rounds()
+            .await
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        println!(
+            "{} {}",
+            "TechEmpower rounds".bright_yellow().bold(),
+            rounds.join(", ").bright_white()
+        );
+        return Ok(());
+    }
+    let (Some(&first_round), Some(&last_round)) = (compare.first(), compare.last()) else {
+        return Ok(());
+    };
+    if compare.len() < 2 {
+        return Err(anyhow!("--compare needs at least two rounds"));
+    }
+
+    // Раунды загружаются по очереди, чтобы не нагружать сайт TechEmpower.
+    let mut results: Vec<TechEmpowerResults> = Vec::with_capacity(compare.len());
+    for &round in compare {
+        results.push(
+            fetcher
+                .fetch_techempower_round(round)
+                .await?
+                .results()
+                .clone(),
+        );
+    }
+
+    println!(
+        "{}",
+        format!("TechEmpower score changes from round {first_round} to round {last_round}")
+            .bold()
+            .bright_magenta()
+    );
+    let round_columns: Vec<String> = compare
+        .iter()
+        .map(|round| format!("{:>8}", format!("R{round}")))
+        .collect();
+    let header = format!(
+        "{:<20} {} {:>8}",
+        "Language",
+        round_columns.join(" "),
+        "Change"
+    );
+    println!("{}", header.bright_cyan().bold());
+
+    let (first, last) = (&results[0], &results[results.len() - 1]);
+    for change in last.compare(first) {
+        let scores: Vec<String> = results
+            .iter()
+            .map(|round_results| {
+                let score = round_results.get(change.lang).map(|result| result.score);
+                format!("{:>8}", format_optional_float(score))
+            })
+            .collect();
+        let row = format!("{:<20} {}", change.lang.name(), scores.join(" "));
+        let delta = format!(" {:>8}", format_trend(change.delta()));
+        let delta = match change.delta() {
+            Some(value) if value > 0.0 => delta.bright_green(),
+            Some(value) if value < 0.0 => delta.bright_red(),
+            _ => delta.normal(),
+        };
+        println!("{row}{delta}");
+    }
+    Ok(())
+}
@@ -0,0 +1,176 @@
+use anyhow::{Context, Result};
+use reqwest::Client;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Future, возвращаемый [`Transport::get`].
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<HttpResponse>> + Send + 'a>>;
+
+/// Способ выполнить HTTP GET-запрос к источнику данных.
+///
+/// По умолчанию используется реализация для [`reqwest::Client`]. Собственная
+/// реализация позволяет подставить ответы из памяти или локальных файлов,
+/// например в тестах или в среде без доступа к сети.
+///
+/// Ответ с любым HTTP-статусом возвращается как `Ok`: повторы и проверку
+/// статуса выполняют функции загрузки источников. `Err` означает, что ответ
+/// получить не удалось.
+pub trait Transport: Send + Sync {
+    /// Выполняет GET-запрос по адресу `url`.
+    fn get<'a>(&'a self, url: &'a str) -> TransportFuture<'a>;
+
+    /// Выполняет GET-запрос с дополнительными заголовками.
+    ///
+    /// Используется для условных запросов кэша (`If-None-Match`,
+    /// `If-Modified-Since`). Реализация по умолчанию игнорирует заголовки и
+    /// вызывает [`Transport::get`].
+    fn get_with_headers<'a>(
+        &'a self,
+        url: &'a str,
+        headers: &'a [(String, String)],
+    ) -> TransportFuture<'a> {
+        let _ = headers;
+        self.get(url)
+    }
+}
+
+/// Ответ транспорта: статус, заголовки и тело.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Создаёт ответ с указанным статусом и телом без заголовков.
+    #[must_use]
+    pub const fn new(status: u16, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    /// Добавляет заголовок к ответу.
+    #[must_use]
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Возвращает HTTP-статус.
+    #[must_use]
+    pub const fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// Проверяет, что статус относится к классу 2xx.
+    #[must_use]
+    pub const fn is_success(&self) -> bool {
+        self.status >= 200 && self.status < 300
+    }
+
+    /// Возвращает значение первого заголовка с указанным именем без учёта регистра.
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Возвращает все заголовки в порядке получения.
+    #[must_use]
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// Возвращает тело ответа.
+    #[must_use]
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Забирает тело ответа без копирования.
+    #[must_use]
+    pub fn into_body(self) -> Vec<u8> {
+        self.body
+    }
+}
+
+impl Transport for Client {
+    fn get<'a>(&'a self, url: &'a str) -> TransportFuture<'a> {
+        self.get_with_headers(url, &[])
+    }
+
+    fn get_with_headers<'a>(
+        &'a self,
+        url: &'a str,
+        headers: &'a [(String, String)],
+    ) -> TransportFuture<'a> {
+        Box::pin(async move {
+            let request = headers
+                .iter()
+                .fold(Self::get(self, url), |request, (name, value)| {
+                    request.header(name.as_str(), value.as_str())
+                });
+            let response = request
+                .send()
+                .await
+                .with_context(|| format!("request to {url} failed"))?;
+            let status = response.status().as_u16();
+            let headers = response
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| {
+                    value
+                        .to_str()
+                        .ok()
+                        .map(|value| (name.as_str().to_owned(), value.to_owned()))
+                })
+                .collect();
+            let body = response
+                .bytes()
+                .await
+                .with_context(|| format!("failed to read response body from {url}"))?
+                .to_vec();
+            Ok(HttpResponse {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn get<'a>(&'a self, url: &'a str) -> TransportFuture<'a> {
+        T::get(self, url)
+    }
+
+    fn get_with_headers<'a>(
+        &'a self,
+        url: &'a str,
+        headers: &'a [(String, String)],
+    ) -> TransportFuture<'a> {
+        T::get_with_headers(self, url, headers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HttpResponse;
+
+    #[test]
+    fn looks_up_headers_case_insensitively() {
+        let response = HttpResponse::new(200, b"ok".to_vec()).with_header("ETag", "\"abc\"");
+
+        assert!(response.is_success());
+        assert_eq!(response.header("etag"), Some("\"abc\""));
+        assert_eq!(response.header("last-modified"), None);
+        assert_eq!(response.into_body(), b"ok");
+    }
+}
@@ -1,15 +1,74 @@
-use langrank::{Fetcher, RankingEntry, RankingSource, reconcile_pypl_with_tiobe};
+use langrank::{
+    AliasRegistry, AliasResolution, FetchError, Fetcher, HttpResponse, Language, LanguishScoring,
+    MetricUsage, PerformanceMetric, PerformanceSource, ProviderFuture, RankingDataset,
+    RankingEntry, RankingProvider, RankingSource, SchulzeConfig, SchulzeError, SourceError,
+    Transport, TransportFuture, compute_languish_backfill, compute_schulze_records,
+    compute_schulze_records_with_metrics, reconcile_pypl_with_tiobe,
+};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn language(name: &str) -> Language {
+    AliasRegistry::empty()
+        .canonicalize(name)
+        .expect("test language names are not empty")
+}
 
 fn entry(lang: &str, share: f64) -> RankingEntry {
-    RankingEntry {
-        lang: lang.to_owned(),
-        rank: Some(1),
-        share,
-        trend: None,
+    RankingEntry::new(language(lang))
+        .with_rank(1)
+        .with_share(share)
+}
+
+struct StaticTransport {
+    body: String,
+}
+
+impl Transport for StaticTransport {
+    fn get<'a>(&'a self, _url: &'a str) -> TransportFuture<'a> {
+        Box::pin(async move { Ok(HttpResponse::new(200, self.body.clone().into_bytes())) })
+    }
+}
+
+struct StalledTransport;
+
+impl Transport for StalledTransport {
+    fn get<'a>(&'a self, _url: &'a str) -> TransportFuture<'a> {
+        Box::pin(std::future::pending())
+    }
+}
+
+#[derive(Default)]
+struct UrlLog {
+    urls: Mutex<Vec<String>>,
+}
+
+impl Transport for UrlLog {
+    fn get<'a>(&'a self, url: &'a str) -> TransportFuture<'a> {
+        self.urls.lock().expect("URL log lock").push(url.to_owned());
+        Box::pin(async move { Ok(HttpResponse::new(404, Vec::new())) })
+    }
+}
+
+struct FixedProvider {
+    id: &'static str,
+    names: &'static [&'static str],
+}
+
+impl RankingProvider for FixedProvider {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn fetch<'a>(&'a self, _transport: &'a dyn Transport) -> ProviderFuture<'a> {
+        Box::pin(async move { Ok(self.names.iter().map(|name| entry(name, 1.0)).collect()) })
     }
 }
 
 #[test]
+#[allow(deprecated)]
 fn exposes_types_needed_by_a_data_consumer() {
     let fetcher = Fetcher::new().expect("HTTP client should be created");
     assert!(fetcher.client().get("https://example.com").build().is_ok());
@@ -23,3 +82,334 @@ fn exposes_types_needed_by_a_data_consumer() {
     assert_eq!(pypl[0].lang, "C");
     assert_eq!(pypl[1].lang, "C++");
 }
+
+#[test]
+fn ranks_datasets_with_the_schulze_method() {
+    let rankings = [
+        RankingDataset::new(
+            RankingSource::Tiobe,
+            vec![entry("Rust", 9.0), entry("Go", 5.0)],
+        ),
+        RankingDataset::new(
+            RankingSource::Custom("survey"),
+            vec![entry("Rust", 7.0), entry("Go", 6.0)],
+        ),
+    ];
+    let benchmark = HashMap::from([(language("Rust"), 0.9), (language("Go"), 0.7)]);
+    let techempower = HashMap::new();
+    let config = SchulzeConfig {
+        min_source_overlap: 2,
+        ..SchulzeConfig::default()
+    };
+
+    let records = compute_schulze_records(&rankings, &benchmark, &techempower, config)
+        .expect("two overlapping languages should be ranked");
+    assert_eq!(records[0].lang, "Rust");
+    assert_eq!(records[0].position, 1);
+
+    let memory = HashMap::from([(language("Rust"), 0.5), (language("Go"), 1.0)]);
+    let metric = PerformanceMetric {
+        scores: &memory,
+        usage: MetricUsage::PerfScore { weight: 1.0 },
+    };
+    let records = compute_schulze_records_with_metrics(
+        &rankings,
+        &benchmark,
+        &techempower,
+        &[metric],
+        config,
+    )
+    .expect("metrics in a std HashMap should be accepted");
+    let go = records
+        .iter()
+        .find(|record| record.lang == "Go")
+        .expect("Go should be ranked");
+    assert!((go.perf_score - 1.7 / 3.0).abs() < 1e-9);
+
+    let strict = SchulzeConfig {
+        min_source_overlap: 3,
+        ..config
+    };
+    let error = compute_schulze_records(&rankings[..1], &benchmark, &techempower, strict)
+        .expect_err("a single ranking cannot satisfy an overlap of three");
+    assert!(matches!(
+        error,
+        SchulzeError::NotEnoughCandidates { actual: 0 }
+    ));
+}
+
+#[tokio::test]
+async fn fetches_through_a_custom_transport() {
+    let languages = [
+        "Python",
+        "Java",
+        "JavaScript",
+        "C#",
+        "Rust",
+        "Go",
+        "Kotlin",
+        "Swift",
+        "Ruby",
+        "Dart",
+    ];
+    let mut body = String::from("<!-- begin section All-->\n");
+    for (index, lang) in languages.iter().enumerate() {
+        let rank = index + 1;
+        writeln!(
+            body,
+            "<tr><td>{rank}</td><td></td><td>{lang}</td><td>1.0%</td><td>+0.1%</td></tr>"
+        )
+        .expect("writing to a String cannot fail");
+    }
+    body.push_str("<!-- end section All-->");
+    let fetcher = Fetcher::from_transport(StaticTransport { body });
+
+    let dataset = fetcher
+        .fetch(RankingSource::Pypl)
+        .await
+        .expect("PYPL fixture should be served by the custom transport");
+
+    assert_eq!(dataset.len(), 10);
+    assert!(dataset.iter().any(|entry| entry.lang == "Rust"));
+}
+
+#[tokio::test]
+async fn custom_providers_are_canonicalized_and_validated() {
+    let fetcher = Fetcher::from_transport(StaticTransport {
+        body: String::new(),
+    })
+    .with_provider(FixedProvider {
+        id: "team-survey",
+        names: &[
+            "golang", "Rust", "Python", "Java", "C#", "nodejs", "C++", "Kotlin", "Swift", "Ruby",
+        ],
+    })
+    .with_provider(FixedProvider {
+        id: "tiny",
+        names: &["Rust"],
+    });
+
+    let survey = fetcher
+        .fetch(RankingSource::Custom("team-survey"))
+        .await
+        .expect("registered provider should be fetched");
+    assert_eq!(survey.source().as_str(), "team-survey");
+    assert!(survey.iter().any(|entry| entry.lang == "Go"));
+    assert!(survey.iter().any(|entry| entry.lang == "JavaScript"));
+
+    let error = fetcher
+        .fetch_custom()
+        .await
+        .expect_err("short provider should be rejected");
+    assert!(matches!(
+        error,
+        FetchError::TooFewEntries {
+            ranking_source: RankingSource::Custom("tiny"),
+            actual: 1,
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn downloads_builtin_sources_from_overridden_urls() {
+    let log = Arc::new(UrlLog::default());
+    let fetcher = Fetcher::builder()
+        .transport(Arc::clone(&log))
+        .max_attempts(1)
+        .source_url(RankingSource::Tiobe, "http://mirror.test/tiobe/")
+        .build()
+        .expect("fetcher with a custom transport should be built");
+
+    let error = fetcher
+        .fetch(RankingSource::Tiobe)
+        .await
+        .expect_err("mirror returns 404");
+
+    assert_eq!(error.source_name(), Some("tiobe"));
+    assert_eq!(
+        *log.urls.lock().expect("URL log lock"),
+        ["http://mirror.test/tiobe/"]
+    );
+}
+
+#[tokio::test]
+async fn fetches_only_the_selected_techempower_round() {
+    let log = Arc::new(UrlLog::default());
+    let fetcher = Fetcher::builder()
+        .transport(Arc::clone(&log))
+        .max_attempts(1)
+        .source_url(PerformanceSource::TechEmpower, "http://mirror.test/tfb/")
+        .techempower_round(22)
+        .build()
+        .expect("fetcher with a custom transport should be built");
+
+    fetcher
+        .fetch_techempower()
+        .await
+        .expect_err("mirror returns 404");
+    assert_eq!(
+        *log.urls.lock().expect("URL log lock"),
+        ["http://mirror.test/tfb/results/round22/ph.json"]
+    );
+
+    let error = fetcher
+        .fetch_techempower_round(18)
+        .await
+        .expect_err("round 18 predates the supported format");
+    let cause = std::error::Error::source(&error).map(ToString::to_string);
+    assert!(
+        cause.is_some_and(|cause| cause.contains("round 18 is not supported")),
+        "{error:?}"
+    );
+    assert_eq!(log.urls.lock().expect("URL log lock").len(), 1);
+
+    // Сбой поиска раундов заменяется встроенным списком.
+    let rounds = fetcher.list_techempower_rounds().await;
+    assert_eq!(rounds, [23, 22, 21]);
+}
+
+#[tokio::test]
+async fn separates_unavailable_sites_from_format_changes() {
+    let down = Fetcher::builder()
+        .transport(UrlLog::default())
+        .max_attempts(1)
+        .build()
+        .expect("fetcher with a custom transport should be built");
+    let error = down
+        .fetch(RankingSource::Pypl)
+        .await
+        .expect_err("site returns 404");
+    assert!(matches!(
+        error.source_error(),
+        Some(SourceError::Http {
+            status: Some(404),
+            attempts: 1,
+            ..
+        })
+    ));
+    assert!(!error.is_format_change());
+
+    let redesigned = Fetcher::from_transport(StaticTransport {
+        body: "<html><body>new layout</body></html>".to_owned(),
+    });
+    let error = redesigned
+        .fetch(RankingSource::Pypl)
+        .await
+        .expect_err("page without section markers");
+    assert!(matches!(
+        error.source_error(),
+        Some(SourceError::MarkupNotFound { .. })
+    ));
+    assert!(error.is_format_change());
+
+    let stalled = Fetcher::builder()
+        .transport(StalledTransport)
+        .source_timeout(RankingSource::Pypl, Duration::from_millis(10))
+        .build()
+        .expect("fetcher with a custom transport should be built");
+    let error = stalled
+        .fetch(RankingSource::Pypl)
+        .await
+        .expect_err("site never answers");
+    assert!(matches!(
+        error.source_error(),
+        Some(SourceError::Timeout { limit, .. }) if *limit == Duration::from_millis(10)
+    ));
+    assert!(!error.is_format_change());
+}
+
+#[tokio::test]
+async fn custom_alias_registry_applies_to_every_source() {
+    let mut aliases = AliasRegistry::builtin();
+    aliases
+        .extend_from_toml("ignore = [\"Ruby\"]\n[aliases]\nkt = \"Kotlin\"")
+        .expect("alias file should parse");
+    let fetcher = Fetcher::builder()
+        .transport(UrlLog::default())
+        .aliases(aliases)
+        .provider(FixedProvider {
+            id: "team-survey",
+            names: &[
+                "golang", "Rust", "Python", "Java", "C#", "nodejs", "C++", "kt", "Swift", "Ruby",
+                "Zig",
+            ],
+        })
+        .build()
+        .expect("fetcher with a custom transport should be built");
+
+    let survey = fetcher
+        .fetch(RankingSource::Custom("team-survey"))
+        .await
+        .expect("registered provider should be fetched");
+    let names: Vec<_> = survey.iter().map(|entry| entry.lang.name()).collect();
+    assert!(names.contains(&"Kotlin"));
+    assert!(!names.contains(&"Ruby"));
+    assert_eq!(
+        fetcher.aliases().resolve("KT"),
+        AliasResolution::Alias("Kotlin")
+    );
+}
+
+#[tokio::test]
+async fn backfills_quarterly_rankings_from_languish_history() {
+    let mut items = String::new();
+    let mut sums = String::new();
+    let languages = ["Rust", "Go", "Python"];
+    for (year_index, quarter) in ["2023Q4", "2024Q1"].iter().enumerate() {
+        for (position, name) in languages.iter().enumerate() {
+            // В первом квартале лидирует Python, во втором — Rust.
+            let share = if year_index == 0 {
+                position + 1
+            } else {
+                3 - position
+            };
+            let stars = if *name == "Go" { 10 } else { share };
+            write!(
+                items,
+                r#"["{name}", "{quarter}", {share}, {share}, {share}, {stars}],"#
+            )
+            .expect("writing to a String cannot fail");
+        }
+        write!(sums, r#"["{quarter}", 6, 6, 6, 16],"#).expect("writing to a String cannot fail");
+    }
+    let body = format!(
+        r#"<script src="/languish/static/js/main.1.chunk.js"></script>
+        JSON.parse('{{"items": {{"keys": ["name", "date", "issues", "pulls", "soQuestions", "stars"], "rows": [{}]}},
+        "sums": {{"keys": ["date", "issues", "pulls", "soQuestions", "stars"], "rows": [{}]}}}}')"#,
+        items.trim_end_matches(','),
+        sums.trim_end_matches(',')
+    );
+    let fetcher = Fetcher::builder()
+        .transport(StaticTransport { body })
+        .max_attempts(1)
+        .languish_scoring(LanguishScoring {
+            signal_ballots: true,
+            ..LanguishScoring::default()
+        })
+        .build()
+        .expect("fetcher with a custom transport should be built");
+
+    let history = fetcher
+        .fetch_languish_history()
+        .await
+        .expect("history should be parsed from the bundle");
+    assert_eq!(history.quarters(), ["2023Q4", "2024Q1"]);
+
+    let scoring = fetcher.languish_scoring();
+    let leaders: Vec<(&str, &str)> = compute_languish_backfill(&history, None, scoring)
+        .map(|(quarter, records)| {
+            let records = records.expect("each quarter should be ranked");
+            assert_eq!(records[0].sub_ballot_ranks[0].rank, Some(1));
+            // Тренд есть только у квартала с предыдущим кварталом.
+            assert_eq!(records[0].languish_trend.is_some(), quarter == "2024Q1");
+            (quarter, records[0].lang.name())
+        })
+        .collect();
+    assert_eq!(leaders, [("2023Q4", "Python"), ("2024Q1", "Rust")]);
+
+    let since: Vec<&str> = compute_languish_backfill(&history, Some("2024Q1"), scoring)
+        .map(|(quarter, _)| quarter)
+        .collect();
+    assert_eq!(since, ["2024Q1"]);
+}